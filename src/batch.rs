@@ -0,0 +1,342 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{HelmError, InstallArg, InstallResult};
+
+/// How many releases of a batch run at once by default
+const DEFAULT_MAX_PARALLEL: usize = 4;
+
+/// A release of a batch and the releases it must wait for
+#[derive(Debug, Clone)]
+pub struct BatchEntry<'a> {
+    pub arg: InstallArg<'a>,
+    /// Names of releases of the batch installed before this one
+    pub after: Vec<String>,
+}
+
+/// Releases installed or upgraded concurrently, within the given limits
+/// and in dependency order, e.g. a sys chart before the app chart using
+/// its CRDs
+#[derive(Debug, Clone)]
+pub struct BatchArg<'a> {
+    pub entries: Vec<BatchEntry<'a>>,
+    /// Most releases run at once
+    pub max_parallel: usize,
+    /// Most releases run at once in the same namespace, unlimited if `None`
+    pub max_per_namespace: Option<usize>,
+}
+
+impl Default for BatchArg<'_> {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            max_parallel: DEFAULT_MAX_PARALLEL,
+            max_per_namespace: None,
+        }
+    }
+}
+
+impl<'a> BatchArg<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// add a release without dependencies
+    pub fn release(self, arg: InstallArg<'a>) -> Self {
+        self.release_after::<_, String>(arg, [])
+    }
+
+    /// add a release run once the named releases of the batch succeeded
+    pub fn release_after<I, S>(mut self, arg: InstallArg<'a>, after: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.entries.push(BatchEntry {
+            arg,
+            after: after.into_iter().map(Into::into).collect(),
+        });
+        self
+    }
+
+    /// set how many releases run at once, at least one
+    pub fn max_parallel(mut self, max: usize) -> Self {
+        self.max_parallel = max.max(1);
+        self
+    }
+
+    /// set how many releases run at once in the same namespace, at least one
+    pub fn max_per_namespace(mut self, max: usize) -> Self {
+        self.max_per_namespace = Some(max.max(1));
+        self
+    }
+
+    /// Checks that release names are unique and dependencies name releases
+    /// of the batch without forming a cycle
+    pub fn validate(&self) -> Result<(), HelmError> {
+        let mut names = HashSet::new();
+        for entry in &self.entries {
+            if !names.insert(&*entry.arg.name) {
+                return Err(HelmError::InvalidArg(format!(
+                    "release {} is in the batch twice",
+                    entry.arg.name
+                )));
+            }
+        }
+        for entry in &self.entries {
+            if let Some(missing) = entry.after.iter().find(|dep| !names.contains(dep.as_str())) {
+                return Err(HelmError::InvalidArg(format!(
+                    "release {} depends on {} which is not in the batch",
+                    entry.arg.name, missing
+                )));
+            }
+        }
+
+        // releases are removed once their dependencies are, what remains is a cycle
+        let mut remaining: Vec<&BatchEntry<'_>> = self.entries.iter().collect();
+        let mut resolved: HashSet<&str> = HashSet::new();
+        while !remaining.is_empty() {
+            let before = remaining.len();
+            remaining.retain(|entry| {
+                if entry
+                    .after
+                    .iter()
+                    .all(|dep| resolved.contains(dep.as_str()))
+                {
+                    resolved.insert(&entry.arg.name);
+                    false
+                } else {
+                    true
+                }
+            });
+            if remaining.len() == before {
+                let names: Vec<&str> = remaining.iter().map(|e| &*e.arg.name).collect();
+                return Err(HelmError::InvalidArg(format!(
+                    "releases {} depend on each other",
+                    names.join(", ")
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// What happened to a release of a batch
+#[derive(Debug)]
+pub enum BatchOutcome {
+    Installed(InstallResult),
+    Failed(HelmError),
+    /// Not attempted because a release it depends on failed or was skipped
+    Skipped,
+}
+
+/// Outcome of a batch, one entry per release in batch order
+#[derive(Debug)]
+pub struct BatchReport {
+    pub releases: Vec<(String, BatchOutcome)>,
+}
+
+impl BatchReport {
+    /// whether every release was installed
+    pub fn is_success(&self) -> bool {
+        self.releases
+            .iter()
+            .all(|(_, outcome)| matches!(outcome, BatchOutcome::Installed(_)))
+    }
+
+    /// names of the releases which failed
+    pub fn failed(&self) -> Vec<&str> {
+        self.releases
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, BatchOutcome::Failed(_)))
+            .map(|(name, _)| name.as_str())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Skipped,
+}
+
+/// Decides which releases of a batch to start next
+#[derive(Debug)]
+pub(crate) struct Scheduler<'a> {
+    batch: &'a BatchArg<'a>,
+    states: Vec<State>,
+}
+
+impl<'a> Scheduler<'a> {
+    pub(crate) fn new(batch: &'a BatchArg<'a>) -> Self {
+        Self {
+            batch,
+            states: vec![State::Pending; batch.entries.len()],
+        }
+    }
+
+    fn state_of(&self, name: &str) -> Option<State> {
+        self.batch
+            .entries
+            .iter()
+            .position(|entry| entry.arg.name == name)
+            .map(|index| self.states[index])
+    }
+
+    /// Marks the releases whose dependencies failed as skipped, returning
+    /// their indexes
+    pub(crate) fn skip_blocked(&mut self) -> Vec<usize> {
+        let mut skipped = vec![];
+        // skipping a release may block others depending on it
+        loop {
+            let blocked: Vec<usize> = (0..self.states.len())
+                .filter(|&index| self.states[index] == State::Pending)
+                .filter(|&index| {
+                    self.batch.entries[index].after.iter().any(|dep| {
+                        matches!(
+                            self.state_of(dep),
+                            Some(State::Failed) | Some(State::Skipped)
+                        )
+                    })
+                })
+                .collect();
+            if blocked.is_empty() {
+                return skipped;
+            }
+            for index in blocked {
+                self.states[index] = State::Skipped;
+                skipped.push(index);
+            }
+        }
+    }
+
+    /// Marks the releases which may start now as running, returning their
+    /// indexes
+    pub(crate) fn next(&mut self) -> Vec<usize> {
+        let mut running = self.states.iter().filter(|s| **s == State::Running).count();
+        let mut per_namespace: HashMap<&str, usize> = HashMap::new();
+        for (index, state) in self.states.iter().enumerate() {
+            if *state == State::Running {
+                *per_namespace.entry(self.namespace(index)).or_default() += 1;
+            }
+        }
+
+        let mut started = vec![];
+        for index in 0..self.states.len() {
+            if running >= self.batch.max_parallel {
+                break;
+            }
+            let ready = self.states[index] == State::Pending
+                && self.batch.entries[index]
+                    .after
+                    .iter()
+                    .all(|dep| self.state_of(dep) == Some(State::Succeeded));
+            let namespace = self.namespace(index);
+            let namespace_full = self.batch.max_per_namespace.is_some_and(|max| {
+                per_namespace.get(namespace).copied().unwrap_or_default() >= max
+            });
+            if ready && !namespace_full {
+                *per_namespace.entry(namespace).or_default() += 1;
+                running += 1;
+                started.push(index);
+            }
+        }
+        for index in &started {
+            self.states[*index] = State::Running;
+        }
+        started
+    }
+
+    /// Records the end of a running release
+    pub(crate) fn finish(&mut self, index: usize, succeeded: bool) {
+        self.states[index] = if succeeded {
+            State::Succeeded
+        } else {
+            State::Failed
+        };
+    }
+
+    /// whether no release is pending or running
+    pub(crate) fn is_done(&self) -> bool {
+        self.states
+            .iter()
+            .all(|state| !matches!(state, State::Pending | State::Running))
+    }
+
+    fn namespace(&self, index: usize) -> &'a str {
+        self.batch.entries[index]
+            .arg
+            .namespace
+            .as_deref()
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arg<'a>(name: &'a str, namespace: &'a str) -> InstallArg<'a> {
+        InstallArg::new(name, format!("fluvio/{}", name)).namespace(namespace)
+    }
+
+    #[test]
+    fn test_validate_batch() {
+        let batch = BatchArg::new()
+            .release(arg("fluvio-sys", "fluvio"))
+            .release_after(arg("fluvio", "fluvio"), ["fluvio-sys"]);
+        assert!(batch.validate().is_ok());
+
+        let unknown = BatchArg::new().release_after(arg("fluvio", "fluvio"), ["fluvio-sys"]);
+        assert!(unknown.validate().is_err());
+
+        let cycle = BatchArg::new()
+            .release_after(arg("a", "default"), ["b"])
+            .release_after(arg("b", "default"), ["a"]);
+        assert!(cycle.validate().is_err());
+    }
+
+    #[test]
+    fn test_scheduler() {
+        let batch = BatchArg::new()
+            .release(arg("fluvio-sys", "fluvio"))
+            .release_after(arg("fluvio", "fluvio"), ["fluvio-sys"])
+            .release(arg("monitoring", "fluvio"))
+            .release(arg("ingress", "infra"))
+            .release(arg("dns", "infra"))
+            .max_parallel(3)
+            .max_per_namespace(1);
+        let mut scheduler = Scheduler::new(&batch);
+
+        // one per namespace, fluvio waits for fluvio-sys
+        assert_eq!(scheduler.next(), vec![0, 3]);
+        assert!(scheduler.next().is_empty());
+
+        scheduler.finish(0, true);
+        assert!(scheduler.skip_blocked().is_empty());
+        assert_eq!(scheduler.next(), vec![1]);
+        scheduler.finish(3, true);
+        assert_eq!(scheduler.next(), vec![4]);
+        scheduler.finish(1, true);
+        assert_eq!(scheduler.next(), vec![2]);
+        scheduler.finish(2, true);
+        scheduler.finish(4, true);
+        assert!(scheduler.is_done());
+    }
+
+    #[test]
+    fn test_scheduler_skips_dependents_of_failures() {
+        let batch = BatchArg::new()
+            .release(arg("fluvio-sys", "fluvio"))
+            .release_after(arg("fluvio", "fluvio"), ["fluvio-sys"])
+            .release_after(arg("connector", "fluvio"), ["fluvio"]);
+        let mut scheduler = Scheduler::new(&batch);
+        assert_eq!(scheduler.next(), vec![0]);
+        scheduler.finish(0, false);
+        assert_eq!(scheduler.skip_blocked(), vec![1, 2]);
+        assert!(scheduler.next().is_empty());
+        assert!(scheduler.is_done());
+    }
+}
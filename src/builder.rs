@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+
+use semver::{Version, VersionReq};
+
+use crate::process::run_tracked;
+use crate::version::parse_version;
+use crate::{HelmClient, HelmError, Transport, DEFAULT_INVENTORY_MAX_AGE};
+
+/// Range of helm versions the client works with by default
+pub const DEFAULT_SUPPORTED_HELM_VERSIONS: &str = ">=3.0.0";
+
+/// Builder for a [`HelmClient`]
+#[derive(Debug)]
+pub struct HelmClientBuilder {
+    supported_versions: VersionReq,
+    transport: Option<Arc<dyn Transport>>,
+}
+
+impl Default for HelmClientBuilder {
+    fn default() -> Self {
+        Self {
+            supported_versions: VersionReq::parse(DEFAULT_SUPPORTED_HELM_VERSIONS)
+                .expect("valid default version requirement"),
+            transport: None,
+        }
+    }
+}
+
+impl HelmClientBuilder {
+    /// set the range of helm versions accepted by `build`
+    pub fn supported_versions(mut self, versions: VersionReq) -> Self {
+        self.supported_versions = versions;
+        self
+    }
+
+    /// run helm and kubectl through a transport instead of the local binaries
+    pub fn transport<T: Transport + 'static>(mut self, transport: T) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Creates the client, checking that helm is installed and that its
+    /// version is in the supported range.
+    pub fn build(self) -> Result<HelmClient, HelmError> {
+        let mut command = Command::new("helm");
+        command.args(["version", "--short"]);
+        let output = run_tracked(&mut command, self.transport.as_deref(), None)
+            .map_err(HelmError::from_helm_check)?;
+        let out_str = String::from_utf8(output.stdout).map_err(HelmError::Utf8Error)?;
+
+        let found = parse_helm_version(&out_str)
+            .ok_or_else(|| HelmError::HelmVersionNotFound(out_str.clone()))?;
+        // pre-releases of a supported version are accepted too
+        let release = Version::new(found.major, found.minor, found.patch);
+        if !self.supported_versions.matches(&release) {
+            return Err(HelmError::UnsupportedHelmVersion {
+                found: found.to_string(),
+                required: self.supported_versions.to_string(),
+            });
+        }
+
+        Ok(HelmClient {
+            credentials: HashMap::new(),
+            kube_context: None,
+            local_registries: false,
+            chart_aliases: HashMap::new(),
+            inventory: Mutex::new(None),
+            inventory_max_age: DEFAULT_INVENTORY_MAX_AGE,
+            receipts: None,
+            constraints: HashMap::new(),
+            transport: self.transport,
+            repo_lock_file: None,
+            lockfile: None,
+            strict_stderr: false,
+            allowed_stderr: vec![],
+        })
+    }
+}
+
+/// Finds the version in `helm version --short` output.
+///
+/// Helm 3 prints `v3.15.4+gfa9efb0`, helm 2 prints one line per component
+/// such as `Client: v2.16.1+gbbdfe5e`.
+fn parse_helm_version(output: &str) -> Option<Version> {
+    output.split_whitespace().find_map(parse_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_helm_version() {
+        assert_eq!(
+            parse_helm_version("v3.15.4+gfa9efb0\n"),
+            Some(Version::parse("3.15.4+gfa9efb0").unwrap())
+        );
+        assert_eq!(
+            parse_helm_version("Client: v2.16.1+gbbdfe5e\nServer: v2.16.1+gbbdfe5e\n"),
+            Some(Version::parse("2.16.1+gbbdfe5e").unwrap())
+        );
+        assert_eq!(parse_helm_version("unknown"), None);
+
+        let supported = VersionReq::parse(DEFAULT_SUPPORTED_HELM_VERSIONS).unwrap();
+        assert!(!supported.matches(&Version::new(2, 16, 1)));
+        assert!(supported.matches(&Version::new(3, 15, 4)));
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use serde_json::Value;
+
+use crate::hooks::parse_yaml_documents;
+use crate::process::CommandRecord;
+use crate::HelmError;
+
+/// Replacement for masked values
+const MASK: &str = "********";
+
+/// Key fragments marking a value as sensitive, matched case insensitively
+const SENSITIVE_KEYS: &[&str] = &["password", "secret", "token", "key", "credential"];
+
+/// Writes one file of a support bundle.
+///
+/// A section which could not be collected is written with the error
+/// instead, so one failure doesn't prevent the rest of the bundle.
+pub(crate) fn write_section(
+    dir: &Path,
+    file: &str,
+    content: Result<String, HelmError>,
+) -> Result<(), HelmError> {
+    let content = content.unwrap_or_else(|err| format!("unable to collect: {:#?}\n", err));
+    let path = dir.join(file);
+    fs::write(&path, content).map_err(|source| HelmError::Io { path, source })
+}
+
+/// Masks the values of sensitive looking keys
+pub(crate) fn mask_values(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                let key = key.to_lowercase();
+                if SENSITIVE_KEYS.iter().any(|fragment| key.contains(fragment))
+                    && !(value.is_object() || value.is_array())
+                {
+                    *value = Value::String(MASK.to_string());
+                } else {
+                    mask_values(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(mask_values),
+        _ => {}
+    }
+}
+
+/// Masks the `data` and `stringData` of the Secrets in a manifest
+pub(crate) fn mask_manifest(manifest: &str) -> Result<String, HelmError> {
+    let mut masked = String::new();
+    for mut document in parse_yaml_documents(manifest)? {
+        if document["kind"].as_str() == Some("Secret") {
+            for field in ["data", "stringData"] {
+                if let Some(data) = document
+                    .get_mut(field)
+                    .and_then(serde_yaml::Value::as_mapping_mut)
+                {
+                    for (_, value) in data.iter_mut() {
+                        *value = serde_yaml::Value::String(MASK.to_string());
+                    }
+                }
+            }
+        }
+        masked.push_str("---\n");
+        masked.push_str(&serde_yaml::to_string(&document)?);
+    }
+    Ok(masked)
+}
+
+/// Formats helm invocations one per line, as `<unix time> <exit code> <duration> <command>`
+pub(crate) fn format_commands(commands: &[CommandRecord]) -> String {
+    commands
+        .iter()
+        .map(|record| {
+            let started = record
+                .started_at
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let exit_code = record
+                .exit_code
+                .map(|code| code.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            format!(
+                "{} {} {:?} {}\n",
+                started,
+                exit_code,
+                record.duration,
+                record.argv.join(" ")
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_mask_values() {
+        let mut values = json!({
+            "image": {"tag": "0.9.2"},
+            "auth": {"adminPassword": "hunter2", "apiToken": "abc"},
+            "tls": {"secretName": "fluvio-tls"},
+            "keys": [{"privateKey": "xyz"}],
+        });
+        mask_values(&mut values);
+        assert_eq!(
+            values,
+            json!({
+                "image": {"tag": "0.9.2"},
+                "auth": {"adminPassword": MASK, "apiToken": MASK},
+                "tls": {"secretName": MASK},
+                "keys": [{"privateKey": MASK}],
+            })
+        );
+    }
+
+    #[test]
+    fn test_mask_manifest() {
+        const MANIFEST: &str = r#"---
+apiVersion: v1
+kind: Secret
+metadata:
+  name: fluvio-credentials
+data:
+  password: aHVudGVyMg==
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: fluvio-config
+data:
+  password: not-a-secret-resource
+"#;
+        let masked = mask_manifest(MANIFEST).expect("can not mask manifest");
+        assert!(!masked.contains("aHVudGVyMg=="));
+        assert!(masked.contains("not-a-secret-resource"));
+    }
+}
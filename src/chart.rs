@@ -0,0 +1,188 @@
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::process::Command;
+
+use sha2::{Digest, Sha256};
+
+use crate::{HelmError, RepoUrl};
+
+/// Reference to a chart as passed to install/upgrade
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChartRef {
+    /// A chart name as understood by helm, e.g. `fluvio/fluvio-app` or a local path
+    Name(String),
+    /// A chart addressed by absolute url, e.g. `oci://ghcr.io/infinyon/fluvio-app`
+    Url(RepoUrl),
+    /// A chart looked up in a repository which is not configured locally (`--repo`)
+    Repo { repo: RepoUrl, name: String },
+}
+
+impl ChartRef {
+    /// chart addressed by url, validating the scheme.
+    ///
+    /// OCI references may be pinned to a digest, e.g.
+    /// `oci://ghcr.io/infinyon/fluvio-app@sha256:<hex>`.
+    pub fn url<S: Into<String>>(url: S) -> Result<Self, HelmError> {
+        let chart = Self::Url(RepoUrl::new(url)?);
+        if let Some(digest) = chart.digest() {
+            validate_digest(digest)?;
+        }
+        Ok(chart)
+    }
+
+    /// chart looked up by name in the given repository
+    pub fn repo<N: Into<String>>(repo: RepoUrl, name: N) -> Self {
+        Self::Repo {
+            repo,
+            name: name.into(),
+        }
+    }
+
+    /// the repository this chart is fetched from, if given by url
+    pub fn repo_url(&self) -> Option<&RepoUrl> {
+        match self {
+            Self::Name(_) => None,
+            Self::Url(url) => Some(url),
+            Self::Repo { repo, .. } => Some(repo),
+        }
+    }
+
+    /// add chart positional argument and any related flags
+    pub(crate) fn apply(&self, command: &mut Command) {
+        match self {
+            Self::Name(name) => {
+                command.arg(name);
+            }
+            Self::Url(url) => {
+                command.arg(url.as_str());
+            }
+            Self::Repo { repo, name } => {
+                command.arg(name).args(["--repo", repo.as_str()]);
+            }
+        }
+    }
+
+    /// the digest an OCI chart reference is pinned to, if any
+    pub fn digest(&self) -> Option<&str> {
+        match self {
+            Self::Url(url) if url.is_oci() => {
+                let chart = url.as_str().rsplit('/').next().unwrap_or_default();
+                chart.split_once('@').map(|(_, digest)| digest)
+            }
+            _ => None,
+        }
+    }
+
+    /// path of the chart archive, if this refers to a local `.tgz`
+    pub fn local_archive(&self) -> Option<&Path> {
+        match self {
+            Self::Name(name) if name.ends_with(".tgz") => {
+                Some(Path::new(name)).filter(|path| path.is_file())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for ChartRef {
+    fn from(name: &str) -> Self {
+        Self::Name(name.to_string())
+    }
+}
+
+impl From<String> for ChartRef {
+    fn from(name: String) -> Self {
+        Self::Name(name)
+    }
+}
+
+impl From<RepoUrl> for ChartRef {
+    fn from(url: RepoUrl) -> Self {
+        Self::Url(url)
+    }
+}
+
+impl fmt::Display for ChartRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Name(name) => f.write_str(name),
+            Self::Url(url) => write!(f, "{}", url),
+            Self::Repo { repo, name } => write!(f, "{} (repo {})", name, repo),
+        }
+    }
+}
+
+/// Documentation bundled with a chart, for display in chart catalogs
+#[derive(Debug, Clone, Default)]
+pub struct ChartDocs {
+    /// The chart README, empty if the chart has none
+    pub readme: String,
+    /// The default values.yaml
+    pub values: String,
+    /// The CRD manifests shipped in the chart's `crds/` directory
+    pub crds: String,
+}
+
+/// Computes the sha256 digest of a chart archive, as `sha256:<hex>`
+pub fn chart_digest<P: AsRef<Path>>(path: P) -> Result<String, HelmError> {
+    let path = path.as_ref();
+    let io_error = |source| HelmError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+
+    let mut file = File::open(path).map_err(io_error)?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher).map_err(io_error)?;
+    Ok(format!("sha256:{:x}", hasher.finalize()))
+}
+
+/// Checks the digest has the `sha256:<64 hex chars>` form
+fn validate_digest(digest: &str) -> Result<(), HelmError> {
+    let valid = digest
+        .strip_prefix("sha256:")
+        .map(|hex| hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .unwrap_or(false);
+    if valid {
+        Ok(())
+    } else {
+        Err(HelmError::InvalidArg(format!(
+            "chart digest must be sha256:<64 hex characters>, got '{}'",
+            digest
+        )))
+    }
+}
+
+/// Finds the `Digest: sha256:...` line helm prints after fetching from an OCI registry
+pub(crate) fn parse_oci_digest(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Digest:"))
+        .map(|digest| digest.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_oci_digest() {
+        const STDERR: &str = "Pulled: ghcr.io/infinyon/fluvio-app:0.10.0\nDigest: sha256:4a5b6c\n";
+        assert_eq!(parse_oci_digest(STDERR).as_deref(), Some("sha256:4a5b6c"));
+        assert_eq!(parse_oci_digest("NAME: fluvio\n"), None);
+    }
+
+    #[test]
+    fn test_chart_digest_ref() {
+        let digest = format!("sha256:{}", "a".repeat(64));
+        let chart = ChartRef::url(format!("oci://ghcr.io/infinyon/fluvio-app@{}", digest))
+            .expect("valid digest ref");
+        assert_eq!(chart.digest(), Some(digest.as_str()));
+
+        assert!(ChartRef::url("oci://ghcr.io/infinyon/fluvio-app@sha256:abc").is_err());
+        let chart = ChartRef::url("oci://ghcr.io/infinyon/fluvio-app").unwrap();
+        assert_eq!(chart.digest(), None);
+    }
+}
@@ -0,0 +1,160 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::{chart_digest, split_chart_id, HelmError};
+
+/// A dependency pinned in Chart.lock
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct LockedDependency {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub repository: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChartLock {
+    #[serde(default)]
+    dependencies: Vec<LockedDependency>,
+}
+
+/// A dependency archive vendored in `charts/`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VendoredDependency {
+    pub archive: PathBuf,
+    /// sha256 digest of the archive, as `sha256:<hex>`
+    pub digest: String,
+}
+
+/// How the vendored dependencies of a chart differ from its Chart.lock
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChartLockReport {
+    /// Locked dependencies with a matching archive in `charts/`
+    pub verified: Vec<(LockedDependency, VendoredDependency)>,
+    /// Locked dependencies with no archive in `charts/`
+    pub missing: Vec<LockedDependency>,
+    /// Locked dependencies vendored in another version, with that version
+    pub mismatched: Vec<(LockedDependency, String)>,
+    /// Archives in `charts/` not pinned in Chart.lock
+    pub unexpected: Vec<PathBuf>,
+}
+
+impl ChartLockReport {
+    /// whether `charts/` holds exactly the locked dependencies
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty() && self.mismatched.is_empty() && self.unexpected.is_empty()
+    }
+}
+
+/// Checks the archives vendored in `charts/` of a chart directory against
+/// its Chart.lock, computing the digest of every matching archive.
+///
+/// Run after `helm dependency build`, before packaging or installing the
+/// chart. A chart without Chart.lock has nothing to verify.
+pub fn verify_chart_lock<P: AsRef<Path>>(chart_dir: P) -> Result<ChartLockReport, HelmError> {
+    let chart_dir = chart_dir.as_ref();
+    let io_error = |path: &Path| {
+        let path = path.to_path_buf();
+        move |source| HelmError::Io { path, source }
+    };
+
+    let lock_path = chart_dir.join("Chart.lock");
+    if !lock_path.is_file() {
+        return Ok(ChartLockReport::default());
+    }
+    let lock: ChartLock =
+        serde_yaml::from_slice(&fs::read(&lock_path).map_err(io_error(&lock_path))?)?;
+
+    let charts_dir = chart_dir.join("charts");
+    let mut archives: Vec<PathBuf> = if charts_dir.is_dir() {
+        fs::read_dir(&charts_dir)
+            .map_err(io_error(&charts_dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "tgz"))
+            .collect()
+    } else {
+        vec![]
+    };
+    archives.sort();
+
+    let mut report = ChartLockReport::default();
+    for dependency in lock.dependencies {
+        let vendored = archives.iter().position(|archive| {
+            archive_id(archive).is_some_and(|(name, _)| name == dependency.name)
+        });
+        let archive = match vendored {
+            Some(index) => archives.remove(index),
+            None => {
+                report.missing.push(dependency);
+                continue;
+            }
+        };
+        let version = archive_id(&archive).map(|(_, version)| version.to_string());
+        match version {
+            Some(version) if version == dependency.version => {
+                let digest = chart_digest(&archive)?;
+                report
+                    .verified
+                    .push((dependency, VendoredDependency { archive, digest }));
+            }
+            version => report
+                .mismatched
+                .push((dependency, version.unwrap_or_default())),
+        }
+    }
+    report.unexpected = archives;
+    Ok(report)
+}
+
+/// Name and version of an archive named `<name>-<version>.tgz`
+fn archive_id(archive: &Path) -> Option<(&str, &str)> {
+    archive
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(split_chart_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_verify_chart_lock() {
+        let dir = TempDir::new().unwrap();
+        let charts = dir.path().join("charts");
+        fs::create_dir_all(&charts).unwrap();
+        fs::write(
+            dir.path().join("Chart.lock"),
+            r#"dependencies:
+- name: postgresql
+  repository: https://charts.bitnami.com/bitnami
+  version: 12.1.2
+- name: redis
+  repository: https://charts.bitnami.com/bitnami
+  version: 17.3.1
+- name: fluvio-sys
+  repository: file://../fluvio-sys
+  version: 0.9.2
+digest: sha256:0f3c4b9d
+generated: "2023-01-10T10:00:00Z"
+"#,
+        )
+        .unwrap();
+        fs::write(charts.join("postgresql-12.1.2.tgz"), b"postgresql").unwrap();
+        fs::write(charts.join("redis-17.2.0.tgz"), b"redis").unwrap();
+        fs::write(charts.join("nginx-1.0.0.tgz"), b"nginx").unwrap();
+
+        let report = verify_chart_lock(dir.path()).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.verified.len(), 1);
+        assert_eq!(report.verified[0].0.name, "postgresql");
+        assert!(report.verified[0].1.digest.starts_with("sha256:"));
+        assert_eq!(report.mismatched[0].0.name, "redis");
+        assert_eq!(report.mismatched[0].1, "17.2.0");
+        assert_eq!(report.missing[0].name, "fluvio-sys");
+        assert_eq!(report.unexpected, vec![charts.join("nginx-1.0.0.tgz")]);
+    }
+}
@@ -0,0 +1,131 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HelmError, InstallResult};
+
+/// Step of a Fluvio installation, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallStep {
+    /// The chart repo was added if missing and its index updated
+    Repo,
+    /// The chart version was resolved
+    Version,
+    /// The sys chart was installed
+    SysChart,
+    /// The CRDs of the sys chart were verified
+    Crds,
+    /// The app chart was installed
+    AppChart,
+}
+
+/// Progress of an installation, written after every completed step so an
+/// interrupted run can resume after the last one
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallCheckpoint {
+    /// Identifies the installation, a checkpoint of another one isn't resumed
+    pub key: String,
+    /// Whether the repo had to be added, once the repo step completed
+    pub repo_added: Option<bool>,
+    pub version: Option<String>,
+    pub sys: Option<InstallResult>,
+    pub crds: Option<Vec<String>>,
+    pub app: Option<InstallResult>,
+}
+
+impl InstallCheckpoint {
+    pub fn new<K: Into<String>>(key: K) -> Self {
+        Self {
+            key: key.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Reads a checkpoint, `None` if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Option<Self>, HelmError> {
+        match fs::read(path) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(source) => Err(HelmError::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    /// Writes the checkpoint, replacing the file atomically so a crash
+    /// never leaves a partial checkpoint behind
+    pub fn save(&self, path: &Path) -> Result<(), HelmError> {
+        let json = serde_json::to_vec_pretty(self)?;
+        let partial = path.with_extension("partial");
+        fs::write(&partial, json)
+            .and_then(|_| fs::rename(&partial, path))
+            .map_err(|source| HelmError::Io {
+                path: path.to_path_buf(),
+                source,
+            })
+    }
+
+    /// Removes a checkpoint file, if any
+    pub fn remove(path: &Path) -> Result<(), HelmError> {
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(HelmError::Io {
+                path: path.to_path_buf(),
+                source: err,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The completed steps, in order
+    pub fn completed(&self) -> Vec<InstallStep> {
+        [
+            (InstallStep::Repo, self.repo_added.is_some()),
+            (InstallStep::Version, self.version.is_some()),
+            (InstallStep::SysChart, self.sys.is_some()),
+            (InstallStep::Crds, self.crds.is_some()),
+            (InstallStep::AppChart, self.app.is_some()),
+        ]
+        .iter()
+        .filter(|(_, done)| *done)
+        .map(|(step, _)| *step)
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_checkpoint_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("fluvio.checkpoint");
+        assert_eq!(InstallCheckpoint::load(&path).unwrap(), None);
+
+        let mut checkpoint = InstallCheckpoint::new("fluvio");
+        checkpoint.repo_added = Some(true);
+        checkpoint.version = Some("0.11.0".to_string());
+        checkpoint.sys = Some(InstallResult::default());
+        checkpoint.save(&path).unwrap();
+
+        let loaded = InstallCheckpoint::load(&path).unwrap().unwrap();
+        assert_eq!(
+            loaded.completed(),
+            vec![
+                InstallStep::Repo,
+                InstallStep::Version,
+                InstallStep::SysChart
+            ]
+        );
+        assert_eq!(loaded, checkpoint);
+
+        InstallCheckpoint::remove(&path).unwrap();
+        InstallCheckpoint::remove(&path).unwrap();
+        assert_eq!(InstallCheckpoint::load(&path).unwrap(), None);
+    }
+}
@@ -0,0 +1,209 @@
+use std::fmt;
+
+use semver::{Version, VersionReq};
+
+use crate::version::parse_version;
+use crate::ReleaseInventory;
+
+/// Requirements a chart has on the cluster it is installed into
+#[derive(Debug, Clone, Default)]
+pub struct ChartConstraints {
+    pub kube_versions: Option<VersionReq>,
+    pub required_crds: Vec<String>,
+    pub incompatible_charts: Vec<String>,
+}
+
+impl ChartConstraints {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// require a Kubernetes server version in the given range
+    pub fn kube_versions(mut self, versions: VersionReq) -> Self {
+        self.kube_versions = Some(versions);
+        self
+    }
+
+    /// require a CRD to be installed, e.g. `servicemonitors.monitoring.coreos.com`
+    pub fn required_crd<S: Into<String>>(mut self, crd: S) -> Self {
+        self.required_crds.push(crd.into());
+        self
+    }
+
+    /// refuse to install next to a release of the named chart
+    pub fn incompatible_chart<S: Into<String>>(mut self, chart: S) -> Self {
+        self.incompatible_charts.push(chart.into());
+        self
+    }
+
+    /// Checks the constraints against the state of the target cluster
+    pub(crate) fn evaluate(
+        &self,
+        chart: &str,
+        kube_version: Option<&str>,
+        crds: &[String],
+        inventory: &ReleaseInventory,
+    ) -> PreflightReport {
+        let mut violations = vec![];
+
+        if let Some(required) = &self.kube_versions {
+            match kube_version.and_then(parse_version) {
+                // vendor suffixes such as `-eks-2d98532` parse as pre-releases
+                Some(found)
+                    if required.matches(&Version::new(found.major, found.minor, found.patch)) => {}
+                _ => violations.push(ConstraintViolation::KubeVersion {
+                    found: kube_version.map(ToString::to_string),
+                    required: required.to_string(),
+                }),
+            }
+        }
+
+        for crd in &self.required_crds {
+            if !crds.contains(crd) {
+                violations.push(ConstraintViolation::MissingCrd(crd.clone()));
+            }
+        }
+
+        for incompatible in &self.incompatible_charts {
+            for release in inventory.find_releases_using_chart(incompatible) {
+                violations.push(ConstraintViolation::IncompatibleRelease {
+                    release: release.name.clone(),
+                    namespace: release.namespace.clone(),
+                    chart: incompatible.clone(),
+                });
+            }
+        }
+
+        PreflightReport {
+            chart: chart.to_string(),
+            violations,
+        }
+    }
+}
+
+/// A chart constraint not met by the target cluster
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConstraintViolation {
+    /// The Kubernetes version is outside the required range, or unknown
+    KubeVersion {
+        found: Option<String>,
+        required: String,
+    },
+    /// A required CRD is not installed
+    MissingCrd(String),
+    /// A release of an incompatible chart is installed
+    IncompatibleRelease {
+        release: String,
+        namespace: String,
+        chart: String,
+    },
+}
+
+impl fmt::Display for ConstraintViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::KubeVersion {
+                found: Some(found),
+                required,
+            } => write!(f, "kubernetes {} does not match {}", found, required),
+            Self::KubeVersion {
+                found: None,
+                required,
+            } => write!(f, "kubernetes version unknown, {} required", required),
+            Self::MissingCrd(crd) => write!(f, "CRD {} is not installed", crd),
+            Self::IncompatibleRelease {
+                release,
+                namespace,
+                chart,
+            } => write!(
+                f,
+                "release {} in {} uses incompatible chart {}",
+                release, namespace, chart
+            ),
+        }
+    }
+}
+
+/// Outcome of checking the constraints of a chart before installing it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreflightReport {
+    /// The chart name the constraints are registered for
+    pub chart: String,
+    pub violations: Vec<ConstraintViolation>,
+}
+
+impl PreflightReport {
+    /// whether every constraint is met
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "preflight checks for chart {} failed", self.chart)?;
+        for violation in &self.violations {
+            write!(f, "\n  - {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// The name a chart is registered under: the last path segment of the
+/// chart reference, without tag or digest
+pub(crate) fn chart_name(chart: &str) -> &str {
+    let name = chart.rsplit('/').next().unwrap_or(chart);
+    let name = name.split('@').next().unwrap_or(name);
+    name.split(':').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InstalledChart;
+
+    #[test]
+    fn test_evaluate_constraints() {
+        const JSON_RESPONSE: &str = r#"[{"name":"legacy","namespace":"kube-system","revision":"1","updated":"","status":"deployed","chart":"fluvio-legacy-0.6.0","app_version":"0.6.0"}]"#;
+        let releases: Vec<InstalledChart> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        let inventory = ReleaseInventory::new(releases);
+
+        let constraints = ChartConstraints::new()
+            .kube_versions(VersionReq::parse(">=1.25").unwrap())
+            .required_crd("topics.fluvio.infinyon.com")
+            .incompatible_chart("fluvio-legacy");
+        let crds = vec!["topics.fluvio.infinyon.com".to_string()];
+
+        let report = constraints.evaluate(
+            "fluvio-app",
+            Some("v1.27.3-eks-2d98532"),
+            &crds,
+            &ReleaseInventory::new(vec![]),
+        );
+        assert!(report.is_ok(), "{}", report);
+
+        let report = constraints.evaluate("fluvio-app", Some("v1.22.0"), &[], &inventory);
+        assert_eq!(
+            report.violations,
+            vec![
+                ConstraintViolation::KubeVersion {
+                    found: Some("v1.22.0".to_string()),
+                    required: ">=1.25".to_string(),
+                },
+                ConstraintViolation::MissingCrd("topics.fluvio.infinyon.com".to_string()),
+                ConstraintViolation::IncompatibleRelease {
+                    release: "legacy".to_string(),
+                    namespace: "kube-system".to_string(),
+                    chart: "fluvio-legacy".to_string(),
+                },
+            ]
+        );
+
+        assert_eq!(chart_name("fluvio/fluvio-app"), "fluvio-app");
+        assert_eq!(
+            chart_name("oci://ghcr.io/infinyon/fluvio-app:0.9.2"),
+            "fluvio-app"
+        );
+    }
+}
@@ -0,0 +1,234 @@
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{Transport, DEFAULT_HELM_IMAGE};
+
+/// Home directory of the user in the helm image
+const CONTAINER_HOME: &str = "/root";
+
+/// Container engine used to run helm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerRuntime {
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    fn program(self) -> &'static str {
+        match self {
+            Self::Docker => "docker",
+            Self::Podman => "podman",
+        }
+    }
+}
+
+/// Runs every command in a throwaway container of a helm image, so hosts
+/// without helm can use a pinned helm version.
+///
+/// The kubeconfig, the helm config and cache directories and the working
+/// directory are mounted into the container, as are the files passed as
+/// absolute paths, e.g. values files or `--set-file` files. Helpers
+/// running kubectl, such as `preflight`, need an image shipping kubectl
+/// as well.
+#[derive(Debug, Clone)]
+pub struct ContainerTransport {
+    pub runtime: ContainerRuntime,
+    pub image: String,
+    pub kubeconfig: Option<PathBuf>,
+    pub mounts: Vec<(PathBuf, PathBuf)>,
+    pub host_network: bool,
+}
+
+impl ContainerTransport {
+    /// Transport using docker and the given image, with the kubeconfig from
+    /// `KUBECONFIG` or `~/.kube/config` and the helm directories of the
+    /// user mounted
+    pub fn new<I: Into<String>>(image: I) -> Self {
+        let home = env::var_os("HOME").map(PathBuf::from);
+        let kubeconfig = env::var_os("KUBECONFIG")
+            .and_then(|paths| env::split_paths(&paths).next())
+            .or_else(|| home.as_ref().map(|home| home.join(".kube/config")));
+        let mounts = home
+            .map(|home| {
+                [".config/helm", ".cache/helm"]
+                    .iter()
+                    .map(|dir| (home.join(dir), Path::new(CONTAINER_HOME).join(dir)))
+                    .filter(|(host, _)| host.is_dir())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            runtime: ContainerRuntime::Docker,
+            image: image.into(),
+            kubeconfig,
+            mounts,
+            host_network: true,
+        }
+    }
+
+    /// set container runtime
+    pub fn runtime(mut self, runtime: ContainerRuntime) -> Self {
+        self.runtime = runtime;
+        self
+    }
+
+    /// set kubeconfig mounted into the container
+    pub fn kubeconfig<P: Into<PathBuf>>(mut self, kubeconfig: P) -> Self {
+        self.kubeconfig = Some(kubeconfig.into());
+        self
+    }
+
+    /// mount a host path into the container
+    pub fn mount<H: Into<PathBuf>, C: Into<PathBuf>>(mut self, host: H, container: C) -> Self {
+        self.mounts.push((host.into(), container.into()));
+        self
+    }
+
+    /// run the container on its own network, clusters on localhost are not
+    /// reachable then
+    pub fn isolated_network(mut self) -> Self {
+        self.host_network = false;
+        self
+    }
+}
+
+impl Default for ContainerTransport {
+    fn default() -> Self {
+        Self::new(DEFAULT_HELM_IMAGE)
+    }
+}
+
+fn volume(host: &Path, container: &Path, read_only: bool) -> String {
+    let mut volume = format!("{}:{}", host.display(), container.display());
+    if read_only {
+        volume.push_str(":ro");
+    }
+    volume
+}
+
+/// Paths an argument may refer to, the argument itself or the files of
+/// `key=path` lists as passed to `--set-file`
+fn arg_paths(arg: &OsStr) -> Vec<&Path> {
+    let mut paths = vec![Path::new(arg)];
+    if let Some(arg) = arg.to_str() {
+        paths.extend(
+            arg.split(',')
+                .filter_map(|opt| opt.split_once('='))
+                .map(|(_, path)| Path::new(path)),
+        );
+    }
+    paths
+}
+
+impl Transport for ContainerTransport {
+    fn wrap(&self, command: &Command) -> Command {
+        let mut run = Command::new(self.runtime.program());
+        run.args(["run", "--rm", "--interactive"])
+            .arg("--entrypoint")
+            .arg(command.get_program());
+
+        if self.host_network {
+            run.args(["--network", "host"]);
+        }
+
+        if let Some(kubeconfig) = &self.kubeconfig {
+            let target = Path::new(CONTAINER_HOME).join(".kube/config");
+            run.arg("--volume").arg(volume(kubeconfig, &target, true));
+        }
+
+        for (host, container) in &self.mounts {
+            run.arg("--volume").arg(volume(host, container, false));
+        }
+
+        // relative paths resolve against the same directory in the container
+        if let Ok(dir) = env::current_dir() {
+            run.arg("--volume").arg(volume(&dir, &dir, false));
+            run.arg("--workdir").arg(&dir);
+        }
+
+        for arg in command.get_args() {
+            for path in arg_paths(arg) {
+                if path.is_absolute() && path.exists() {
+                    run.arg("--volume").arg(volume(path, path, false));
+                }
+            }
+        }
+
+        for (key, value) in command.get_envs() {
+            if let Some(value) = value {
+                let mut env = key.to_os_string();
+                env.push("=");
+                env.push(value);
+                run.arg("--env").arg(env);
+            }
+        }
+
+        run.arg(&self.image).args(command.get_args());
+        run
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        let values = env::temp_dir();
+        let mut command = Command::new("helm");
+        command
+            .args(["upgrade", "--install", "fluvio", "fluvio/fluvio-app"])
+            .arg("--values")
+            .arg(&values)
+            .env("HELM_NAMESPACE", "fluvio");
+
+        let transport = ContainerTransport {
+            runtime: ContainerRuntime::Podman,
+            image: DEFAULT_HELM_IMAGE.to_string(),
+            kubeconfig: Some(PathBuf::from("/home/ops/.kube/config")),
+            mounts: vec![],
+            host_network: true,
+        };
+        let wrapped = transport.wrap(&command);
+        let args: Vec<String> = wrapped
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let dir = env::current_dir().unwrap().display().to_string();
+        let values = values.display().to_string();
+
+        assert_eq!(wrapped.get_program(), "podman");
+        assert_eq!(
+            args,
+            vec![
+                "run",
+                "--rm",
+                "--interactive",
+                "--entrypoint",
+                "helm",
+                "--network",
+                "host",
+                "--volume",
+                "/home/ops/.kube/config:/root/.kube/config:ro",
+                "--volume",
+                &format!("{}:{}", dir, dir),
+                "--workdir",
+                &dir,
+                "--volume",
+                &format!("{}:{}", values, values),
+                "--env",
+                "HELM_NAMESPACE=fluvio",
+                DEFAULT_HELM_IMAGE,
+                "upgrade",
+                "--install",
+                "fluvio",
+                "fluvio/fluvio-app",
+                "--values",
+                &values,
+            ]
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+
+use serde_yaml::Value;
+
+use crate::hooks::parse_yaml_documents;
+use crate::HelmError;
+
+/// How a CRD differs between two chart versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrdChangeKind {
+    /// The CRD is only bundled with the target version
+    Added,
+    /// The CRD definition differs in the target version
+    Changed,
+}
+
+/// A CRD which must be applied explicitly, since helm only installs CRDs
+/// on first install and never upgrades them
+#[derive(Debug, Clone)]
+pub struct CrdChange {
+    /// The CRD name, e.g. `topics.fluvio.infinyon.com`
+    pub name: String,
+    /// How the CRD differs
+    pub kind: CrdChangeKind,
+    /// The CRD manifest of the target version
+    pub manifest: Value,
+}
+
+/// Compares the CRDs of two `helm show crds` outputs
+pub(crate) fn crd_changes(current: &str, target: &str) -> Result<Vec<CrdChange>, HelmError> {
+    let current: HashMap<String, Value> = parse_yaml_documents(current)?
+        .into_iter()
+        .filter_map(|crd| crd_name(&crd).map(|name| (name, crd)))
+        .collect();
+
+    let changes = parse_yaml_documents(target)?
+        .into_iter()
+        .filter_map(|crd| {
+            let name = crd_name(&crd)?;
+            let kind = match current.get(&name) {
+                None => CrdChangeKind::Added,
+                Some(installed) if *installed != crd => CrdChangeKind::Changed,
+                Some(_) => return None,
+            };
+            Some(CrdChange {
+                name,
+                kind,
+                manifest: crd,
+            })
+        })
+        .collect();
+    Ok(changes)
+}
+
+/// Splits the output of `helm show crds` into its manifests, keeping
+/// their text untouched
+pub(crate) fn split_manifests(crds: &str) -> Vec<String> {
+    let mut manifests = vec![];
+    let mut current = String::new();
+    for line in crds.lines() {
+        if line.starts_with("---") {
+            manifests.push(std::mem::take(&mut current));
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    manifests.push(current);
+    manifests.retain(|manifest| {
+        manifest.lines().any(|line| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+    });
+    manifests
+}
+
+fn crd_name(crd: &Value) -> Option<String> {
+    crd["metadata"]["name"].as_str().map(ToString::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crd_changes() {
+        const CURRENT: &str = r#"---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: topics.fluvio.infinyon.com
+spec:
+  group: fluvio.infinyon.com
+---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: spus.fluvio.infinyon.com
+spec:
+  group: fluvio.infinyon.com
+"#;
+        const TARGET: &str = r#"---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: topics.fluvio.infinyon.com
+spec:
+  group: fluvio.infinyon.com
+  preserveUnknownFields: false
+---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: spus.fluvio.infinyon.com
+spec:
+  group: fluvio.infinyon.com
+---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: smartmodules.fluvio.infinyon.com
+spec:
+  group: fluvio.infinyon.com
+"#;
+        let changes = crd_changes(CURRENT, TARGET).expect("can not compare crds");
+        let changes: Vec<(&str, CrdChangeKind)> = changes
+            .iter()
+            .map(|change| (change.name.as_str(), change.kind))
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                ("topics.fluvio.infinyon.com", CrdChangeKind::Changed),
+                ("smartmodules.fluvio.infinyon.com", CrdChangeKind::Added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_manifests() {
+        const CRDS: &str = r#"---
+# Source: fluvio-sys/crds/topic.yaml
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: topics.fluvio.infinyon.com
+---
+# Source: fluvio-sys/crds/empty.yaml
+---
+apiVersion: apiextensions.k8s.io/v1
+kind: CustomResourceDefinition
+metadata:
+  name: spus.fluvio.infinyon.com
+"#;
+        let manifests = split_manifests(CRDS);
+        assert_eq!(manifests.len(), 2);
+        assert!(manifests[0].starts_with("# Source: fluvio-sys/crds/topic.yaml\n"));
+        assert!(manifests[0].ends_with("name: topics.fluvio.infinyon.com\n"));
+        assert!(manifests[1].contains("spus.fluvio.infinyon.com"));
+    }
+}
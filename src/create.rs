@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::HelmError;
+
+/// Create Argument, scaffolds a new chart directory
+#[derive(Debug)]
+pub struct CreateArg<'a> {
+    pub name: Cow<'a, str>,
+    pub directory: Option<PathBuf>,
+    pub starter: Option<Cow<'a, str>>,
+}
+
+impl<'a> CreateArg<'a> {
+    pub fn new<N: Into<Cow<'a, str>>>(name: N) -> Self {
+        Self {
+            name: name.into(),
+            directory: None,
+            starter: None,
+        }
+    }
+
+    /// set directory the chart is created in, defaults to the current directory
+    pub fn directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+
+    /// set starter scaffold, by name or absolute path
+    pub fn starter<S: Into<Cow<'a, str>>>(mut self, starter: S) -> Self {
+        self.starter = Some(starter.into());
+        self
+    }
+
+    /// The directory of the created chart
+    pub fn chart_path(&self) -> PathBuf {
+        match &self.directory {
+            Some(directory) => directory.join(&*self.name),
+            None => PathBuf::from(&*self.name),
+        }
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), HelmError> {
+        if self.name.is_empty() || self.name.contains(['/', '\\']) {
+            return Err(HelmError::InvalidArg(format!(
+                "invalid chart name \"{}\", set the directory separately",
+                self.name
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl From<CreateArg<'_>> for Command {
+    fn from(arg: CreateArg) -> Self {
+        let mut command = Command::new("helm");
+        command.arg("create").arg(arg.chart_path());
+
+        if let Some(starter) = &arg.starter {
+            command.args(["--starter", starter]);
+        }
+
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_command() {
+        let arg = CreateArg::new("fluvio-connector")
+            .directory("charts")
+            .starter("fluvio-starter");
+        assert!(arg.validate().is_ok());
+        assert_eq!(arg.chart_path(), PathBuf::from("charts/fluvio-connector"));
+
+        let command: Command = arg.into();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            args,
+            vec![
+                "create",
+                "charts/fluvio-connector",
+                "--starter",
+                "fluvio-starter"
+            ]
+        );
+
+        assert!(CreateArg::new("charts/fluvio").validate().is_err());
+    }
+}
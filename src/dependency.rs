@@ -0,0 +1,58 @@
+/// A dependency of a chart, as listed by `helm dependency list`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyEntry {
+    pub name: String,
+    /// The version constraint from Chart.yaml
+    pub version: String,
+    pub repository: String,
+    /// The state of the dependency in `charts/`, e.g. `ok`, `missing` or `wrong version`
+    pub status: String,
+}
+
+/// Parses the table printed by `helm dependency list`
+pub(crate) fn parse_dependency_list(output: &str) -> Vec<DependencyEntry> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("NAME"))
+        .skip(1)
+        .filter_map(|line| {
+            let mut columns = line.split('\t').map(str::trim);
+            let name = columns.next().filter(|name| !name.is_empty())?;
+            Some(DependencyEntry {
+                name: name.to_string(),
+                version: columns.next()?.to_string(),
+                repository: columns.next()?.to_string(),
+                status: columns.next()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dependency_list() {
+        const OUTPUT: &str = "NAME      \tVERSION\tREPOSITORY                        \tSTATUS\npostgresql\t12.1.2 \thttps://charts.bitnami.com/bitnami\tok    \nfluvio-sys\t~0.9.0 \tfile://../fluvio-sys              \twrong version\n\n";
+        let dependencies = parse_dependency_list(OUTPUT);
+        assert_eq!(
+            dependencies,
+            vec![
+                DependencyEntry {
+                    name: "postgresql".to_string(),
+                    version: "12.1.2".to_string(),
+                    repository: "https://charts.bitnami.com/bitnami".to_string(),
+                    status: "ok".to_string(),
+                },
+                DependencyEntry {
+                    name: "fluvio-sys".to_string(),
+                    version: "~0.9.0".to_string(),
+                    repository: "file://../fluvio-sys".to_string(),
+                    status: "wrong version".to_string(),
+                },
+            ]
+        );
+        assert!(parse_dependency_list("WARNING: no dependencies at ./charts\n").is_empty());
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Fields of Chart.yaml only understood by Helm 2
+const HELM2_FIELDS: &[&str] = &["engine", "tillerVersion"];
+
+/// Files of the Helm 2 chart layout, replaced by fields of Chart.yaml and
+/// Chart.lock in Helm 3
+const HELM2_FILES: &[&str] = &["requirements.yaml", "requirements.lock"];
+
+/// A deprecated chart format or layout, found when pulling or rendering
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChartWarning {
+    /// Chart.yaml declares `apiVersion: v1`, the Helm 2 chart format
+    ApiVersionV1,
+    /// Chart.yaml doesn't declare an `apiVersion`
+    MissingApiVersion,
+    /// Chart.yaml sets a field only used by Helm 2, e.g. `tillerVersion`
+    Helm2Field(String),
+    /// The chart ships a file of the Helm 2 layout, e.g. `requirements.yaml`
+    Helm2File(String),
+    /// The chart is marked deprecated by its maintainers
+    DeprecatedChart,
+}
+
+impl fmt::Display for ChartWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ApiVersionV1 => write!(f, "chart uses apiVersion v1, migrate it to v2"),
+            Self::MissingApiVersion => write!(f, "chart doesn't declare an apiVersion"),
+            Self::Helm2Field(field) => {
+                write!(f, "Chart.yaml field {} is only used by Helm 2", field)
+            }
+            Self::Helm2File(file) => {
+                write!(
+                    f,
+                    "{} is deprecated, move it into Chart.yaml or Chart.lock",
+                    file
+                )
+            }
+            Self::DeprecatedChart => write!(f, "chart is deprecated"),
+        }
+    }
+}
+
+/// Warnings about the metadata of a chart, from its Chart.yaml
+pub(crate) fn metadata_warnings(chart_yaml: &serde_yaml::Value) -> Vec<ChartWarning> {
+    let mut warnings = vec![];
+    match chart_yaml["apiVersion"].as_str() {
+        Some("v1") => warnings.push(ChartWarning::ApiVersionV1),
+        Some(_) => {}
+        None => warnings.push(ChartWarning::MissingApiVersion),
+    }
+    for field in HELM2_FIELDS {
+        if chart_yaml.get(field).is_some() {
+            warnings.push(ChartWarning::Helm2Field(field.to_string()));
+        }
+    }
+    if chart_yaml["deprecated"].as_bool() == Some(true) {
+        warnings.push(ChartWarning::DeprecatedChart);
+    }
+    warnings
+}
+
+/// Warnings about the files of an unpacked chart
+pub(crate) fn layout_warnings(chart_dir: &Path) -> Vec<ChartWarning> {
+    HELM2_FILES
+        .iter()
+        .filter(|file| chart_dir.join(file).is_file())
+        .map(|file| ChartWarning::Helm2File(file.to_string()))
+        .collect()
+}
+
+/// A chart downloaded by `HelmClient::pull_with_warnings`
+#[derive(Debug, Clone)]
+pub struct PulledChart {
+    /// The chart archive, or the chart directory when unpacked
+    pub path: PathBuf,
+    pub warnings: Vec<ChartWarning>,
+}
+
+/// A chart rendered by `HelmClient::template_with_warnings`
+#[derive(Debug, Clone)]
+pub struct RenderedChart {
+    /// The rendered manifests
+    pub manifest: String,
+    pub warnings: Vec<ChartWarning>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_metadata_warnings() {
+        let chart_yaml: serde_yaml::Value = serde_yaml::from_str(
+            "apiVersion: v1\nname: legacy\nversion: 0.1.0\ntillerVersion: '>=2.10'\ndeprecated: true\n",
+        )
+        .unwrap();
+        assert_eq!(
+            metadata_warnings(&chart_yaml),
+            vec![
+                ChartWarning::ApiVersionV1,
+                ChartWarning::Helm2Field("tillerVersion".to_string()),
+                ChartWarning::DeprecatedChart,
+            ]
+        );
+
+        let chart_yaml: serde_yaml::Value =
+            serde_yaml::from_str("apiVersion: v2\nname: fluvio-app\nversion: 0.11.0\n").unwrap();
+        assert!(metadata_warnings(&chart_yaml).is_empty());
+        let chart_yaml: serde_yaml::Value = serde_yaml::from_str("name: fluvio-app\n").unwrap();
+        assert_eq!(
+            metadata_warnings(&chart_yaml),
+            vec![ChartWarning::MissingApiVersion]
+        );
+    }
+
+    #[test]
+    fn test_layout_warnings() {
+        let dir = TempDir::new().unwrap();
+        assert!(layout_warnings(dir.path()).is_empty());
+        std::fs::write(dir.path().join("requirements.yaml"), "dependencies: []\n").unwrap();
+        assert_eq!(
+            layout_warnings(dir.path()),
+            vec![ChartWarning::Helm2File("requirements.yaml".to_string())]
+        );
+    }
+}
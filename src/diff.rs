@@ -0,0 +1,61 @@
+/// Line based diff of two texts.
+///
+/// Lines are prefixed with `-` when only in `old`, `+` when only in `new`
+/// and a space when in both, computed from their longest common subsequence.
+pub(crate) fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // lcs[i][j] is the common subsequence length of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = String::new();
+    let mut push = |prefix: char, line: &str| {
+        diff.push(prefix);
+        diff.push_str(line);
+        diff.push('\n');
+    };
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            push(' ', old[i]);
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            push('-', old[i]);
+            i += 1;
+        } else {
+            push('+', new[j]);
+            j += 1;
+        }
+    }
+    old[i..].iter().for_each(|line| push('-', line));
+    new[j..].iter().for_each(|line| push('+', line));
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let old = "Fluvio is installed\nRun: fluvio cluster check\nDocs: fluvio.io\n";
+        let new = "Fluvio is installed\nRun: fluvio cluster status\nDocs: fluvio.io\nEnjoy\n";
+        assert_eq!(
+            line_diff(old, new),
+            " Fluvio is installed\n-Run: fluvio cluster check\n+Run: fluvio cluster status\n Docs: fluvio.io\n+Enjoy\n"
+        );
+        assert_eq!(line_diff("same\n", "same\n"), " same\n");
+        assert_eq!(line_diff("", "new"), "+new\n");
+    }
+}
@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Helm settings, as reported by `helm env`
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct HelmEnv {
+    /// Base directory of cached files, e.g. repo indexes
+    pub cache_home: PathBuf,
+    /// Base directory of configuration files
+    pub config_home: PathBuf,
+    /// Base directory of data, e.g. plugins
+    pub data_home: PathBuf,
+    /// File holding the OCI registry credentials
+    pub registry_config: PathBuf,
+    /// Directory of the cached repo indexes
+    pub repository_cache: PathBuf,
+    /// File listing the configured repos
+    pub repository_config: PathBuf,
+    /// The namespace used by default
+    pub namespace: String,
+    /// The kubeconfig context, empty for the current context
+    pub kube_context: String,
+    /// Every variable printed by helm, including those above
+    pub vars: BTreeMap<String, String>,
+}
+
+impl HelmEnv {
+    /// Parses the `KEY="value"` lines printed by `helm env`
+    pub(crate) fn parse(output: &str) -> Self {
+        let vars: BTreeMap<String, String> = output
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                let value = value.trim();
+                let value = value
+                    .strip_prefix('"')
+                    .and_then(|value| value.strip_suffix('"'))
+                    .unwrap_or(value);
+                Some((key.trim().to_string(), value.to_string()))
+            })
+            .collect();
+
+        let var = |key: &str| vars.get(key).cloned().unwrap_or_default();
+        Self {
+            cache_home: var("HELM_CACHE_HOME").into(),
+            config_home: var("HELM_CONFIG_HOME").into(),
+            data_home: var("HELM_DATA_HOME").into(),
+            registry_config: var("HELM_REGISTRY_CONFIG").into(),
+            repository_cache: var("HELM_REPOSITORY_CACHE").into(),
+            repository_config: var("HELM_REPOSITORY_CONFIG").into(),
+            namespace: var("HELM_NAMESPACE"),
+            kube_context: var("HELM_KUBECONTEXT"),
+            vars,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_helm_env() {
+        const OUTPUT: &str = r#"HELM_BIN="helm"
+HELM_CACHE_HOME="/home/fluvio/.cache/helm"
+HELM_CONFIG_HOME="/home/fluvio/.config/helm"
+HELM_DATA_HOME="/home/fluvio/.local/share/helm"
+HELM_DEBUG="false"
+HELM_KUBECONTEXT=""
+HELM_NAMESPACE="default"
+HELM_REGISTRY_CONFIG="/home/fluvio/.config/helm/registry/config.json"
+HELM_REPOSITORY_CACHE="/home/fluvio/.cache/helm/repository"
+HELM_REPOSITORY_CONFIG="/home/fluvio/.config/helm/repositories.yaml"
+"#;
+        let env = HelmEnv::parse(OUTPUT);
+        assert_eq!(env.cache_home, PathBuf::from("/home/fluvio/.cache/helm"));
+        assert_eq!(
+            env.repository_config,
+            PathBuf::from("/home/fluvio/.config/helm/repositories.yaml")
+        );
+        assert_eq!(env.namespace, "default");
+        assert_eq!(env.kube_context, "");
+        assert_eq!(env.vars["HELM_DEBUG"], "false");
+    }
+}
@@ -1,7 +1,11 @@
-use fluvio_command::CommandError;
-use std::io::Error as IoError;
+use fluvio_command::{CommandError, CommandErrorKind};
+use std::io::{Error as IoError, ErrorKind};
+use std::path::PathBuf;
+use std::process::Output;
 use std::string::FromUtf8Error;
 
+use crate::{HelmFailureKind, PreflightReport, RepoAddFailure};
+
 #[derive(thiserror::Error, Debug)]
 pub enum HelmError {
     #[error(
@@ -12,12 +16,116 @@ pub enum HelmError {
     HelmNotInstalled(IoError),
     #[error("Failed to read helm client version: {0}")]
     HelmVersionNotFound(String),
+    #[error(
+        r#"Unsupported helm version {found}, version {required} is required
+  Please upgrade helm and make sure the new version is first in your PATH.
+  See https://helm.sh/docs/intro/install/ for more help"#
+    )]
+    UnsupportedHelmVersion { found: String, required: String },
+    #[error("Invalid repository url \"{url}\": {reason}")]
+    InvalidRepoUrl { url: String, reason: String },
+    #[error("Invalid argument: {0}")]
+    InvalidArg(String),
+    #[error("Failed to add repository {name}: {reason}")]
+    RepoAddFailed {
+        name: String,
+        reason: RepoAddFailure,
+        #[source]
+        source: CommandError,
+    },
+    #[error(
+        r#"Timed out waiting for the repo config lock {}
+  Another process is changing helm repositories"#,
+        .0.display()
+    )]
+    RepoConfigLocked(PathBuf),
+    #[error("Chart {chart} not found in any of the repos: {}", repos.join(", "))]
+    ChartNotFound { chart: String, repos: Vec<String> },
+    #[error(
+        r#"Helm plugin '{0}' is not installed
+  Install it with 'helm plugin install <url>', see the plugin documentation"#
+    )]
+    PluginNotInstalled(String),
+    #[error("{0}")]
+    PreflightFailed(PreflightReport),
+    #[error("CRDs not installed: {}", .0.join(", "))]
+    MissingCrds(Vec<String>),
+    #[error("Failed to read the revision release {0} was rolled back to")]
+    RollbackNotFound(String),
+    #[error("Release {release} of the batch panicked: {message}")]
+    BatchPanicked { release: String, message: String },
+    #[error("Registry request to {url} failed: {reason}")]
+    RegistryRequest { url: String, reason: String },
+    #[error("Helm reported unexpected output on stderr:\n{0}")]
+    UnexpectedStderr(String),
     #[error("Failed to connect to Kubernetes")]
     FailedToConnect,
+    #[error("I/O error accessing {}", path.display())]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: IoError,
+    },
     #[error("Failed to parse helm output as UTF8")]
     Utf8Error(#[from] FromUtf8Error),
     #[error("Failed to parse JSON from helm output")]
     Serde(#[from] serde_json::Error),
+    #[error("Failed to parse YAML from helm output")]
+    Yaml(#[from] serde_yaml::Error),
     #[error("Failed to execute a command")]
-    Command(#[from] CommandError),
+    Command(#[source] CommandError),
+    #[error("Helm command failed: {kind}")]
+    CommandFailed {
+        kind: HelmFailureKind,
+        #[source]
+        source: CommandError,
+    },
+}
+
+impl HelmError {
+    /// The classified failure, if helm ran and exited with an error
+    pub fn failure_kind(&self) -> Option<HelmFailureKind> {
+        match self {
+            Self::CommandFailed { kind, .. } => Some(*kind),
+            _ => None,
+        }
+    }
+
+    /// The captured output of helm, if it ran and exited with an error
+    pub fn output(&self) -> Option<&Output> {
+        match self {
+            Self::CommandFailed { source, .. } | Self::RepoAddFailed { source, .. } => {
+                match &source.source {
+                    CommandErrorKind::ExitError(_, output) => Some(output),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the error of a command checking helm, reporting a helm
+    /// executable that could not be found as `HelmNotInstalled`
+    pub(crate) fn from_helm_check(source: CommandError) -> Self {
+        match source {
+            CommandError {
+                source: CommandErrorKind::IoError(err),
+                ..
+            } if err.kind() == ErrorKind::NotFound => Self::HelmNotInstalled(err),
+            source => source.into(),
+        }
+    }
+}
+
+impl From<CommandError> for HelmError {
+    fn from(source: CommandError) -> Self {
+        match &source.source {
+            CommandErrorKind::ExitError(code, output) => {
+                let kind =
+                    HelmFailureKind::classify(*code, &String::from_utf8_lossy(&output.stderr));
+                Self::CommandFailed { kind, source }
+            }
+            _ => Self::Command(source),
+        }
+    }
 }
@@ -0,0 +1,122 @@
+use std::process::Command;
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Effective configuration of a planned install, as returned by
+/// `HelmClient::explain`
+#[derive(Debug, Clone)]
+pub struct InstallExplanation {
+    /// The chart version helm resolved
+    pub chart_version: String,
+    /// The repositories the chart is looked up in
+    pub repos: Vec<String>,
+    /// The chart defaults merged with the values files and `--set` options
+    pub values: Value,
+    /// The helm command line, with passwords masked
+    pub argv: Vec<String>,
+    /// The helm client version
+    pub helm_version: String,
+    /// The Kubernetes server version, if the cluster could be reached
+    pub kube_version: Option<String>,
+}
+
+/// Release rendered by `helm install --dry-run --output json`
+#[derive(Debug, Deserialize)]
+pub(crate) struct DryRunRelease {
+    pub chart: DryRunChart,
+    /// The user supplied values
+    #[serde(default)]
+    pub config: Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DryRunChart {
+    pub metadata: DryRunChartMetadata,
+    /// The chart defaults
+    #[serde(default)]
+    pub values: Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct DryRunChartMetadata {
+    pub version: String,
+}
+
+/// Merges `overlay` into `base` the way helm coalesces values: maps are
+/// merged recursively, other values replace the base and `null` removes it
+pub(crate) fn merge_values(base: &mut Value, overlay: Value) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                if value.is_null() {
+                    base.remove(&key);
+                } else if let Some(existing) = base.get_mut(&key) {
+                    merge_values(existing, value);
+                } else {
+                    base.insert(key, value);
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// The program and arguments of a command, with passwords masked
+pub(crate) fn command_argv(command: &Command) -> Vec<String> {
+    let mut argv = vec![command.get_program().to_string_lossy().into_owned()];
+    let mut mask_next = false;
+    for arg in command.get_args() {
+        let arg = arg.to_string_lossy();
+        if mask_next {
+            argv.push("********".to_string());
+        } else {
+            argv.push(arg.to_string());
+        }
+        mask_next = arg == "--password";
+    }
+    argv
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_merge_values() {
+        let mut values = json!({
+            "image": {"registry": "infinyon", "tag": "0.9.0"},
+            "replicas": 1,
+            "debug": true,
+        });
+        merge_values(
+            &mut values,
+            json!({"image": {"tag": "0.9.2"}, "replicas": 3, "debug": null}),
+        );
+        assert_eq!(
+            values,
+            json!({"image": {"registry": "infinyon", "tag": "0.9.2"}, "replicas": 3})
+        );
+    }
+
+    #[test]
+    fn test_command_argv() {
+        let mut command = Command::new("helm");
+        command.args(["pull", "oci://ghcr.io/infinyon/fluvio-app"]);
+        command.args(["--username", "fluvio", "--password", "secret"]);
+        assert_eq!(
+            command_argv(&command),
+            vec![
+                "helm",
+                "pull",
+                "oci://ghcr.io/infinyon/fluvio-app",
+                "--username",
+                "fluvio",
+                "--password",
+                "********"
+            ]
+        );
+    }
+}
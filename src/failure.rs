@@ -0,0 +1,234 @@
+use std::fmt;
+
+/// Classification of a failed helm invocation.
+///
+/// Helm exits with code `1` for every error it reports itself, so the kind
+/// is mostly derived from the error message on stderr, checking the
+/// patterns of [`FAILURE_PATTERNS`] in order. Exit code `2` is the Go
+/// runtime's code for a panic and maps to `Crashed` when no message matches.
+/// Any other code comes from a plugin and maps to `Other`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HelmFailureKind {
+    /// The Kubernetes API server could not be reached
+    ClusterUnreachable,
+    /// The release does not exist
+    ReleaseNotFound,
+    /// A release with the same name is still installed
+    ReleaseNameInUse,
+    /// Another install, upgrade or rollback of the release is running
+    OperationInProgress,
+    /// The chart, or the requested chart version, could not be found
+    ChartNotFound,
+    /// The named repository is not configured
+    RepoNotFound,
+    /// Credentials were missing or rejected by the repository or cluster
+    Unauthorized,
+    /// The operation did not complete within its timeout
+    Timeout,
+    /// The rendered manifests were rejected by validation, or the chart
+    /// does not support the target Kubernetes version
+    ValidationFailed,
+    /// The chart signature or checksum did not match its provenance file
+    VerificationFailed,
+    /// Helm rejected a flag, usually because the binary is too old
+    UnknownFlag,
+    /// Helm panicked (exit code 2)
+    Crashed,
+    /// No known pattern matched
+    Other,
+}
+
+/// Exit code of a Go program terminated by a panic
+const GO_PANIC_EXIT_CODE: i32 = 2;
+
+/// Error message fragments mapped to failure kinds, checked in order.
+///
+/// An entry matches if stderr contains all of its fragments.
+pub const FAILURE_PATTERNS: &[(&[&str], HelmFailureKind)] = &[
+    (
+        &["Kubernetes cluster unreachable"],
+        HelmFailureKind::ClusterUnreachable,
+    ),
+    (&["connection refused"], HelmFailureKind::ClusterUnreachable),
+    (
+        &["the server is currently unable to handle the request"],
+        HelmFailureKind::ClusterUnreachable,
+    ),
+    (&["release: not found"], HelmFailureKind::ReleaseNotFound),
+    (
+        &["cannot re-use a name that is still in use"],
+        HelmFailureKind::ReleaseNameInUse,
+    ),
+    (
+        &["another operation (install/upgrade/rollback) is in progress"],
+        HelmFailureKind::OperationInProgress,
+    ),
+    (&["401 Unauthorized"], HelmFailureKind::Unauthorized),
+    (&["403 Forbidden"], HelmFailureKind::Unauthorized),
+    (&["unauthorized"], HelmFailureKind::Unauthorized),
+    (&["not found in", "index"], HelmFailureKind::ChartNotFound),
+    (&["no repo named"], HelmFailureKind::RepoNotFound),
+    (&["repo ", " not found"], HelmFailureKind::RepoNotFound),
+    (&["failed to download"], HelmFailureKind::ChartNotFound),
+    (&["chart not found"], HelmFailureKind::ChartNotFound),
+    (
+        &["timed out waiting for the condition"],
+        HelmFailureKind::Timeout,
+    ),
+    (&["context deadline exceeded"], HelmFailureKind::Timeout),
+    (
+        &["unable to build kubernetes objects"],
+        HelmFailureKind::ValidationFailed,
+    ),
+    (&["error validating"], HelmFailureKind::ValidationFailed),
+    (
+        &["chart requires kubeVersion"],
+        HelmFailureKind::ValidationFailed,
+    ),
+    (&["openpgp:"], HelmFailureKind::VerificationFailed),
+    (
+        &["sha256 sum does not match"],
+        HelmFailureKind::VerificationFailed,
+    ),
+    (&["unknown flag"], HelmFailureKind::UnknownFlag),
+    (&["unknown shorthand flag"], HelmFailureKind::UnknownFlag),
+];
+
+/// Fragments of stderr lines helm prints on success, accepted by the
+/// strict stderr mode of the client
+pub const ALLOWED_STDERR: &[&str] = &[
+    "Kubernetes configuration file is group-readable",
+    "Kubernetes configuration file is world-readable",
+    "Pulled: ",
+    "Pushed: ",
+    "Digest: ",
+];
+
+/// The stderr lines not containing any of the allowed fragments, `None` if
+/// every line is allowed
+pub(crate) fn unexpected_stderr(stderr: &str, allowed: &[String]) -> Option<String> {
+    let unexpected: Vec<&str> = stderr
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| {
+            !ALLOWED_STDERR
+                .iter()
+                .any(|fragment| line.contains(fragment))
+                && !allowed
+                    .iter()
+                    .any(|fragment| line.contains(fragment.as_str()))
+        })
+        .collect();
+    if unexpected.is_empty() {
+        None
+    } else {
+        Some(unexpected.join("\n"))
+    }
+}
+
+impl HelmFailureKind {
+    /// Classifies a failure from helm's exit code and stderr
+    pub fn classify(code: i32, stderr: &str) -> Self {
+        match Self::from_stderr(stderr) {
+            Self::Other if code == GO_PANIC_EXIT_CODE => Self::Crashed,
+            kind => kind,
+        }
+    }
+
+    /// Classifies a failure from helm's stderr alone
+    pub fn from_stderr(stderr: &str) -> Self {
+        FAILURE_PATTERNS
+            .iter()
+            .find(|(fragments, _)| fragments.iter().all(|f| stderr.contains(f)))
+            .map(|(_, kind)| *kind)
+            .unwrap_or(Self::Other)
+    }
+}
+
+impl fmt::Display for HelmFailureKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::ClusterUnreachable => "cluster unreachable",
+            Self::ReleaseNotFound => "release not found",
+            Self::ReleaseNameInUse => "release name in use",
+            Self::OperationInProgress => "another operation in progress",
+            Self::ChartNotFound => "chart not found",
+            Self::RepoNotFound => "repo not found",
+            Self::Unauthorized => "unauthorized",
+            Self::Timeout => "timed out",
+            Self::ValidationFailed => "validation failed",
+            Self::VerificationFailed => "verification failed",
+            Self::UnknownFlag => "unknown flag",
+            Self::Crashed => "helm crashed",
+            Self::Other => "unclassified error",
+        };
+        f.write_str(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_failures() {
+        let cases = [
+            (
+                "Error: uninstall: Release not loaded: fluvio: release: not found",
+                HelmFailureKind::ReleaseNotFound,
+            ),
+            (
+                "Error: INSTALLATION FAILED: cannot re-use a name that is still in use",
+                HelmFailureKind::ReleaseNameInUse,
+            ),
+            (
+                "Error: INSTALLATION FAILED: repo fluvio not found",
+                HelmFailureKind::RepoNotFound,
+            ),
+            (
+                "Error: chart \"fluvio-app\" matching 9.9.9 not found in fluvio index. (try 'helm repo update')",
+                HelmFailureKind::ChartNotFound,
+            ),
+            (
+                "Error: Kubernetes cluster unreachable: Get \"https://127.0.0.1:6443/version\": dial tcp 127.0.0.1:6443: connect: connection refused",
+                HelmFailureKind::ClusterUnreachable,
+            ),
+            (
+                "Error: chart requires kubeVersion: >=1.25.0-0 which is incompatible with Kubernetes v1.22.0",
+                HelmFailureKind::ValidationFailed,
+            ),
+            (
+                "Error: unknown flag: --wait-for-jobs",
+                HelmFailureKind::UnknownFlag,
+            ),
+            (
+                "Error: openpgp: signature made by unknown entity",
+                HelmFailureKind::VerificationFailed,
+            ),
+            ("Error: something else", HelmFailureKind::Other),
+        ];
+        for (stderr, kind) in cases {
+            assert_eq!(HelmFailureKind::classify(1, stderr), kind, "{}", stderr);
+        }
+        assert_eq!(
+            HelmFailureKind::classify(2, "panic: runtime error"),
+            HelmFailureKind::Crashed
+        );
+    }
+
+    #[test]
+    fn test_unexpected_stderr() {
+        let stderr = "WARNING: Kubernetes configuration file is group-readable. This is insecure. Location: /root/.kube/config\nPulled: ghcr.io/infinyon/fluvio-app:0.11.0\nDigest: sha256:abc\n";
+        assert_eq!(unexpected_stderr(stderr, &[]), None);
+
+        let stderr = "Pulled: ghcr.io/infinyon/fluvio-app:0.11.0\ncoalesce.go:237: warning: skipped value for resources: Not a table.\n";
+        assert_eq!(
+            unexpected_stderr(stderr, &[]).as_deref(),
+            Some("coalesce.go:237: warning: skipped value for resources: Not a table.")
+        );
+        assert_eq!(
+            unexpected_stderr(stderr, &["skipped value for".to_string()]),
+            None
+        );
+    }
+}
@@ -0,0 +1,388 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use semver::VersionReq;
+use tracing::{debug, info, instrument};
+
+use crate::{
+    ChartRef, HelmClient, HelmError, InstallArg, InstallCheckpoint, InstallResult, InstallStep,
+};
+
+/// Name of the Fluvio chart repository
+pub const FLUVIO_REPO_NAME: &str = "fluvio";
+
+/// Location of the Fluvio chart repository
+pub const FLUVIO_REPO_URL: &str = "https://charts.fluvio.io";
+
+/// Chart installing the Fluvio CRDs and cluster wide resources
+pub const FLUVIO_SYS_CHART: &str = "fluvio-sys";
+
+/// Chart installing the Fluvio cluster
+pub const FLUVIO_APP_CHART: &str = "fluvio-app";
+
+/// How long to wait for the CRDs of the sys chart by default
+const DEFAULT_CRD_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long to wait for the resources of each chart by default
+const DEFAULT_INSTALL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Interval at which the installed CRDs are checked
+const CRD_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Installs or upgrades Fluvio with the sys and app charts.
+///
+/// Adds the Fluvio repo when missing, resolves the newest chart version
+/// matching the requirement, or the version locked in the lockfile of the
+/// client, installs the sys chart, waits for its CRDs
+/// and then installs the app chart. Each install waits until the resources
+/// of the chart are ready.
+///
+/// The resolved version is locked in the lockfile of the client. The chart
+/// digest is only locked when helm reports one, as for OCI charts; helm
+/// reports none for charts from a repo, so none is recorded for them.
+///
+/// With a checkpoint file, progress is recorded after every step and
+/// [`FluvioChartInstaller::resume`] continues a cancelled or failed run
+/// after its last completed step.
+#[derive(Debug)]
+pub struct FluvioChartInstaller<'a> {
+    client: &'a HelmClient,
+    pub repo_name: String,
+    pub repo_url: String,
+    pub version: VersionReq,
+    pub develop: bool,
+    pub namespace: Option<String>,
+    pub sys_release: String,
+    pub app_release: String,
+    pub opts: Vec<(String, String)>,
+    pub values: Vec<PathBuf>,
+    pub crd_timeout: Duration,
+    pub timeout: Duration,
+    pub checkpoint: Option<PathBuf>,
+}
+
+impl<'a> FluvioChartInstaller<'a> {
+    pub fn new(client: &'a HelmClient) -> Self {
+        Self {
+            client,
+            repo_name: FLUVIO_REPO_NAME.to_string(),
+            repo_url: FLUVIO_REPO_URL.to_string(),
+            version: VersionReq::STAR,
+            develop: false,
+            namespace: None,
+            sys_release: FLUVIO_SYS_CHART.to_string(),
+            app_release: "fluvio".to_string(),
+            opts: vec![],
+            values: vec![],
+            crd_timeout: DEFAULT_CRD_TIMEOUT,
+            timeout: DEFAULT_INSTALL_TIMEOUT,
+            checkpoint: None,
+        }
+    }
+
+    /// set chart repository, defaults to the public Fluvio repo
+    pub fn repo<N: Into<String>, U: Into<String>>(mut self, name: N, url: U) -> Self {
+        self.repo_name = name.into();
+        self.repo_url = url.into();
+        self
+    }
+
+    /// set requirement on the chart version, the newest match is installed
+    pub fn version(mut self, version: VersionReq) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// consider development versions of the charts
+    pub fn develop(mut self) -> Self {
+        self.develop = true;
+        self
+    }
+
+    /// set namespace
+    pub fn namespace<S: Into<String>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set release names of the sys and app charts
+    pub fn release_names<S: Into<String>, A: Into<String>>(mut self, sys: S, app: A) -> Self {
+        self.sys_release = sys.into();
+        self.app_release = app.into();
+        self
+    }
+
+    /// set a single option of the app chart
+    pub fn opt<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.opts.push((key.into(), value.into()));
+        self
+    }
+
+    /// add a values file of the app chart
+    pub fn value(mut self, value: PathBuf) -> Self {
+        self.values.push(value);
+        self
+    }
+
+    /// set how long to wait for the CRDs of the sys chart
+    pub fn crd_timeout(mut self, timeout: Duration) -> Self {
+        self.crd_timeout = timeout;
+        self
+    }
+
+    /// set how long to wait for the resources of each chart to be ready
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// record progress in the given file, removed once the installation completes
+    pub fn checkpoint<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    /// Runs the installation from the start, discarding any checkpoint
+    #[instrument(
+        skip(self),
+        fields(
+            release = %self.app_release,
+            namespace = self.namespace.as_deref().unwrap_or_default(),
+            repo = %self.repo_name,
+            version = %self.version,
+        )
+    )]
+    pub fn install(&self) -> Result<FluvioInstallReport, HelmError> {
+        self.run(InstallCheckpoint::new(self.checkpoint_key()))
+    }
+
+    /// Runs the installation, skipping the steps completed by a previous run
+    /// according to the checkpoint.
+    ///
+    /// Starts from the beginning when no checkpoint is set or found, or when
+    /// it was recorded for another installation.
+    #[instrument(
+        skip(self),
+        fields(
+            release = %self.app_release,
+            namespace = self.namespace.as_deref().unwrap_or_default(),
+            checkpoint = ?self.checkpoint,
+        )
+    )]
+    pub fn resume(&self) -> Result<FluvioInstallReport, HelmError> {
+        let key = self.checkpoint_key();
+        let checkpoint = match &self.checkpoint {
+            Some(path) => InstallCheckpoint::load(path)?,
+            None => None,
+        };
+        match checkpoint {
+            Some(checkpoint) if checkpoint.key == key => {
+                info!(completed = ?checkpoint.completed(), "resuming fluvio installation");
+                self.run(checkpoint)
+            }
+            Some(_) => {
+                debug!("checkpoint recorded for another installation, starting over");
+                self.run(InstallCheckpoint::new(key))
+            }
+            None => self.run(InstallCheckpoint::new(key)),
+        }
+    }
+
+    fn run(&self, mut checkpoint: InstallCheckpoint) -> Result<FluvioInstallReport, HelmError> {
+        let resumed = checkpoint.completed();
+
+        let repo_added = match checkpoint.repo_added {
+            Some(added) => added,
+            None => {
+                let added = self.ensure_repo()?;
+                checkpoint.repo_added = Some(added);
+                self.save_checkpoint(&checkpoint)?;
+                added
+            }
+        };
+
+        let version = match &checkpoint.version {
+            Some(version) => version.clone(),
+            None => {
+                let version = self.resolve_version()?;
+                checkpoint.version = Some(version.clone());
+                self.save_checkpoint(&checkpoint)?;
+                version
+            }
+        };
+        info!(%version, "installing fluvio charts");
+
+        let sys = match &checkpoint.sys {
+            Some(sys) => sys.clone(),
+            None => {
+                let sys = self.client.upgrade(&self.install_arg(
+                    &self.sys_release,
+                    FLUVIO_SYS_CHART,
+                    &version,
+                ))?;
+                checkpoint.sys = Some(sys.clone());
+                self.save_checkpoint(&checkpoint)?;
+                sys
+            }
+        };
+
+        let crds = match &checkpoint.crds {
+            Some(crds) => crds.clone(),
+            None => {
+                let crds = self.wait_for_crds(&version)?;
+                checkpoint.crds = Some(crds.clone());
+                self.save_checkpoint(&checkpoint)?;
+                crds
+            }
+        };
+
+        let app = match &checkpoint.app {
+            Some(app) => app.clone(),
+            None => {
+                let mut app_arg = self.install_arg(&self.app_release, FLUVIO_APP_CHART, &version);
+                app_arg.opts.extend(
+                    self.opts
+                        .iter()
+                        .map(|(key, value)| (Cow::from(key), Cow::from(value))),
+                );
+                app_arg.values.extend(self.values.iter().cloned());
+                let app = self.client.upgrade(&app_arg)?;
+                let app_chart = format!("{}/{}", self.repo_name, FLUVIO_APP_CHART);
+                if let Some(digest) = app.chart_digest.as_ref().or(app.oci_digest.as_ref()) {
+                    self.client.record_chart_digest(&app_chart, digest)?;
+                }
+                app
+            }
+        };
+
+        if let Some(path) = &self.checkpoint {
+            InstallCheckpoint::remove(path)?;
+        }
+        Ok(FluvioInstallReport {
+            version,
+            repo_added,
+            sys,
+            app,
+            crds,
+            resumed,
+        })
+    }
+
+    /// Identifies this installation in checkpoints
+    fn checkpoint_key(&self) -> String {
+        format!(
+            "{}/{}/{}/{}@{}{}",
+            self.namespace.as_deref().unwrap_or_default(),
+            self.sys_release,
+            self.app_release,
+            self.repo_url,
+            self.version,
+            if self.develop { "-devel" } else { "" }
+        )
+    }
+
+    fn save_checkpoint(&self, checkpoint: &InstallCheckpoint) -> Result<(), HelmError> {
+        match &self.checkpoint {
+            Some(path) => checkpoint.save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Adds the repo unless configured, then updates the repo indexes.
+    /// Returns whether the repo was added.
+    fn ensure_repo(&self) -> Result<bool, HelmError> {
+        let added = !self
+            .client
+            .repo_list()?
+            .iter()
+            .any(|repo| repo.name == self.repo_name);
+        if added {
+            self.client.repo_add(&self.repo_name, &self.repo_url)?;
+        }
+        self.client.repo_update_only(&[&self.repo_name])?;
+        Ok(added)
+    }
+
+    fn resolve_version(&self) -> Result<String, HelmError> {
+        let chart = format!("{}/{}", self.repo_name, FLUVIO_APP_CHART);
+        self.client
+            .resolve_chart_version(&chart, &self.version, self.develop)
+    }
+
+    fn install_arg<'b>(
+        &'b self,
+        release: &'b str,
+        chart: &str,
+        version: &'b str,
+    ) -> InstallArg<'b> {
+        let chart = ChartRef::Name(format!("{}/{}", self.repo_name, chart));
+        let mut arg = InstallArg::new(release, chart)
+            .version(version)
+            .wait()
+            .timeout(self.timeout);
+        arg.namespace = self.namespace.as_deref().map(Cow::Borrowed);
+        if self.develop {
+            arg = arg.develop();
+        }
+        arg
+    }
+
+    /// Waits until the CRDs shipped by the sys chart are installed,
+    /// returning their names
+    fn wait_for_crds(&self, version: &str) -> Result<Vec<String>, HelmError> {
+        let mut expected = self
+            .client
+            .resource_inventory(&self.sys_release, self.namespace.as_deref())?
+            .of_kind("CustomResourceDefinition")
+            .into_iter()
+            .map(|crd| crd.name.clone())
+            .collect::<Vec<_>>();
+        let chart = ChartRef::Name(format!("{}/{}", self.repo_name, FLUVIO_SYS_CHART));
+        for manifest in self.client.show_crds(&chart, Some(version))? {
+            let crd: serde_yaml::Value = serde_yaml::from_str(&manifest)?;
+            if let Some(name) = crd["metadata"]["name"].as_str() {
+                expected.push(name.to_string());
+            }
+        }
+        expected.sort();
+        expected.dedup();
+
+        let started = Instant::now();
+        loop {
+            let installed = self.client.installed_crds(None, None)?;
+            let missing: Vec<String> = expected
+                .iter()
+                .filter(|crd| !installed.contains(crd))
+                .cloned()
+                .collect();
+            if missing.is_empty() {
+                return Ok(expected);
+            }
+            if started.elapsed() >= self.crd_timeout {
+                return Err(HelmError::MissingCrds(missing));
+            }
+            debug!(?missing, "waiting for fluvio crds");
+            thread::sleep(CRD_POLL_INTERVAL);
+        }
+    }
+}
+
+/// Outcome of a Fluvio installation
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct FluvioInstallReport {
+    /// The chart version installed
+    pub version: String,
+    /// Whether the Fluvio repo had to be added
+    pub repo_added: bool,
+    /// Result of installing the sys chart
+    pub sys: InstallResult,
+    /// Result of installing the app chart
+    pub app: InstallResult,
+    /// The CRDs verified after installing the sys chart
+    pub crds: Vec<String>,
+    /// The steps completed by a previous run and skipped
+    pub resumed: Vec<InstallStep>,
+}
@@ -0,0 +1,289 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, SystemTime};
+
+use serde::Deserialize;
+
+use crate::time::parse_helm_timestamp;
+use crate::HelmError;
+
+const HOOK_ANNOTATION: &str = "helm.sh/hook";
+const HOOK_WEIGHT_ANNOTATION: &str = "helm.sh/hook-weight";
+
+/// A hook resource of a release, as returned by `helm get hooks`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookManifest {
+    /// The resource kind, e.g. `Job`
+    pub kind: String,
+    /// The resource name
+    pub name: String,
+    /// The hook events, e.g. `pre-install` or `post-upgrade`
+    pub events: Vec<String>,
+    /// The hook weight, hooks run in ascending weight order
+    pub weight: i32,
+}
+
+#[derive(Deserialize)]
+struct Resource {
+    kind: String,
+    metadata: Metadata,
+}
+
+#[derive(Deserialize)]
+struct Metadata {
+    name: String,
+    #[serde(default)]
+    annotations: BTreeMap<String, String>,
+}
+
+/// Phase of the last run of a hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookPhase {
+    /// The hook didn't run for the revision, e.g. its events didn't fire
+    NotRun,
+    Running,
+    Succeeded,
+    Failed,
+    Unknown,
+}
+
+impl HookPhase {
+    fn parse(phase: &str) -> Self {
+        match phase {
+            "" => Self::NotRun,
+            "Running" => Self::Running,
+            "Succeeded" => Self::Succeeded,
+            "Failed" => Self::Failed,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Outcome of the last run of a hook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookOutcome {
+    /// The resource kind, e.g. `Job`
+    pub kind: String,
+    /// The resource name
+    pub name: String,
+    /// The hook events, e.g. `pre-upgrade`
+    pub events: Vec<String>,
+    pub phase: HookPhase,
+    pub started_at: Option<SystemTime>,
+    pub completed_at: Option<SystemTime>,
+}
+
+impl HookOutcome {
+    /// How long the hook ran, if it completed
+    pub fn duration(&self) -> Option<Duration> {
+        self.completed_at?.duration_since(self.started_at?).ok()
+    }
+}
+
+/// The hooks of a release revision and how their last run went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookReport {
+    pub release: String,
+    pub revision: u32,
+    /// Hooks in execution order
+    pub hooks: Vec<HookOutcome>,
+}
+
+impl HookReport {
+    /// hooks that ran, or are still running
+    pub fn ran(&self) -> Vec<&HookOutcome> {
+        self.hooks
+            .iter()
+            .filter(|hook| hook.phase != HookPhase::NotRun)
+            .collect()
+    }
+
+    /// hooks whose last run failed
+    pub fn failed(&self) -> Vec<&HookOutcome> {
+        self.hooks
+            .iter()
+            .filter(|hook| hook.phase == HookPhase::Failed)
+            .collect()
+    }
+
+    /// whether no hook failed
+    pub fn is_success(&self) -> bool {
+        self.failed().is_empty()
+    }
+
+    /// Builds the report from the output of `helm status --output json`
+    pub(crate) fn parse(status_json: &[u8]) -> Result<Self, HelmError> {
+        #[derive(Deserialize)]
+        struct Status {
+            name: String,
+            version: u32,
+            #[serde(default)]
+            hooks: Vec<Hook>,
+        }
+
+        #[derive(Deserialize)]
+        struct Hook {
+            name: String,
+            kind: String,
+            #[serde(default)]
+            events: Vec<String>,
+            #[serde(default)]
+            weight: i32,
+            #[serde(default)]
+            last_run: LastRun,
+        }
+
+        #[derive(Default, Deserialize)]
+        struct LastRun {
+            #[serde(default)]
+            started_at: String,
+            #[serde(default)]
+            completed_at: String,
+            #[serde(default)]
+            phase: String,
+        }
+
+        let status: Status = serde_json::from_slice(status_json)?;
+        let mut hooks = status.hooks;
+        // helm runs hooks by ascending weight, then by name
+        hooks.sort_by(|a, b| (a.weight, &a.name).cmp(&(b.weight, &b.name)));
+        Ok(Self {
+            release: status.name,
+            revision: status.version,
+            hooks: hooks
+                .into_iter()
+                .map(|hook| HookOutcome {
+                    kind: hook.kind,
+                    name: hook.name,
+                    events: hook.events,
+                    phase: HookPhase::parse(&hook.last_run.phase),
+                    started_at: parse_helm_timestamp(&hook.last_run.started_at),
+                    completed_at: parse_helm_timestamp(&hook.last_run.completed_at),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Splits a multi document YAML stream into its non-empty documents
+pub(crate) fn parse_yaml_documents(yaml: &str) -> Result<Vec<serde_yaml::Value>, HelmError> {
+    let mut documents = vec![];
+    for document in serde_yaml::Deserializer::from_str(yaml) {
+        let value = serde_yaml::Value::deserialize(document)?;
+        if !value.is_null() {
+            documents.push(value);
+        }
+    }
+    Ok(documents)
+}
+
+/// Parses the output of `helm get hooks`
+pub(crate) fn parse_hooks(yaml: &str) -> Result<Vec<HookManifest>, HelmError> {
+    parse_yaml_documents(yaml)?
+        .into_iter()
+        .map(|document| {
+            let resource: Resource = serde_yaml::from_value(document)?;
+            let annotations = resource.metadata.annotations;
+            let events = annotations
+                .get(HOOK_ANNOTATION)
+                .map(|events| events.split(',').map(|e| e.trim().to_string()).collect())
+                .unwrap_or_default();
+            let weight = annotations
+                .get(HOOK_WEIGHT_ANNOTATION)
+                .and_then(|weight| weight.trim().parse().ok())
+                .unwrap_or_default();
+            Ok(HookManifest {
+                kind: resource.kind,
+                name: resource.metadata.name,
+                events,
+                weight,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hooks() {
+        const HOOKS: &str = r#"---
+# Source: fluvio-app/templates/pre-install.yaml
+apiVersion: batch/v1
+kind: Job
+metadata:
+  name: fluvio-pre-install
+  annotations:
+    "helm.sh/hook": pre-install,pre-upgrade
+    "helm.sh/hook-weight": "-5"
+---
+# Source: fluvio-app/templates/tests/test-connection.yaml
+apiVersion: v1
+kind: Pod
+metadata:
+  name: fluvio-test-connection
+  annotations:
+    "helm.sh/hook": test
+"#;
+        let hooks = parse_hooks(HOOKS).expect("can not parse hooks");
+        assert_eq!(
+            hooks,
+            vec![
+                HookManifest {
+                    kind: "Job".to_string(),
+                    name: "fluvio-pre-install".to_string(),
+                    events: vec!["pre-install".to_string(), "pre-upgrade".to_string()],
+                    weight: -5,
+                },
+                HookManifest {
+                    kind: "Pod".to_string(),
+                    name: "fluvio-test-connection".to_string(),
+                    events: vec!["test".to_string()],
+                    weight: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_hook_report() {
+        const STATUS: &str = r#"{"name":"fluvio","version":3,"info":{"status":"failed"},"hooks":[{"name":"fluvio-migrate","kind":"Job","path":"fluvio-app/templates/migrate.yaml","events":["pre-upgrade"],"last_run":{"started_at":"2021-03-17T08:42:54Z","completed_at":"2021-03-17T08:43:04.5Z","phase":"Failed"},"weight":5},{"name":"fluvio-check","kind":"Job","events":["pre-upgrade"],"last_run":{"started_at":"2021-03-17T08:42:50Z","completed_at":"2021-03-17T08:42:52Z","phase":"Succeeded"},"weight":-1},{"name":"fluvio-test-connection","kind":"Pod","events":["test"],"last_run":{"started_at":"","completed_at":"","phase":""}}]}"#;
+        let report = HookReport::parse(STATUS.as_bytes()).expect("can not parse status");
+        assert_eq!(report.revision, 3);
+        let names: Vec<&str> = report.hooks.iter().map(|hook| hook.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["fluvio-check", "fluvio-test-connection", "fluvio-migrate"]
+        );
+        assert_eq!(report.ran().len(), 2);
+        assert!(!report.is_success());
+        let failed = report.failed();
+        assert_eq!(failed[0].name, "fluvio-migrate");
+        assert_eq!(failed[0].duration(), Some(Duration::from_millis(10_500)));
+        assert_eq!(report.hooks[1].phase, HookPhase::NotRun);
+        assert_eq!(report.hooks[1].duration(), None);
+    }
+
+    #[test]
+    fn test_parse_yaml_documents() {
+        const MANIFESTS: &str = r#"---
+# Source: fluvio-app/templates/disabled.yaml
+---
+# Source: fluvio-app/templates/service.yaml
+apiVersion: v1
+kind: Service
+metadata:
+  name: fluvio-sc
+---
+# Source: fluvio-app/templates/deployment.yaml
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: fluvio-sc
+"#;
+        let documents = parse_yaml_documents(MANIFESTS).expect("can not parse manifests");
+        assert_eq!(documents.len(), 2);
+        assert_eq!(documents[0]["kind"].as_str(), Some("Service"));
+        assert_eq!(documents[1]["kind"].as_str(), Some("Deployment"));
+    }
+}
@@ -0,0 +1,245 @@
+use std::time::{Duration, Instant};
+
+use crate::{InstalledChart, VersionDelta};
+
+/// Snapshot of the releases in every namespace, taken with a single `helm list`.
+///
+/// Lookups against a snapshot don't spawn helm, so helpers checking many
+/// releases can share one listing while it is fresh enough.
+#[derive(Debug, Clone)]
+pub struct ReleaseInventory {
+    releases: Vec<InstalledChart>,
+    taken_at: Instant,
+}
+
+impl ReleaseInventory {
+    /// snapshot of releases listed by `helm list --all-namespaces --output json`
+    pub fn new(releases: Vec<InstalledChart>) -> Self {
+        Self {
+            releases,
+            taken_at: Instant::now(),
+        }
+    }
+
+    /// all releases in the snapshot
+    pub fn releases(&self) -> &[InstalledChart] {
+        &self.releases
+    }
+
+    /// time elapsed since the snapshot was taken
+    pub fn age(&self) -> Duration {
+        self.taken_at.elapsed()
+    }
+
+    /// whether the snapshot is younger than `max_age`
+    pub fn is_fresh(&self, max_age: Duration) -> bool {
+        self.age() < max_age
+    }
+
+    /// finds a release by name, in any namespace
+    pub fn find_release(&self, name: &str) -> Option<&InstalledChart> {
+        self.releases.iter().find(|release| release.name == name)
+    }
+
+    /// finds a release by namespace and name
+    pub fn find_release_in(&self, namespace: &str, name: &str) -> Option<&InstalledChart> {
+        self.releases
+            .iter()
+            .find(|release| release.namespace == namespace && release.name == name)
+    }
+
+    /// releases installed in the given namespace
+    pub fn releases_in_namespace(&self, namespace: &str) -> Vec<&InstalledChart> {
+        self.releases
+            .iter()
+            .filter(|release| release.namespace == namespace)
+            .collect()
+    }
+
+    /// the namespaces holding at least one release, sorted
+    pub fn namespaces(&self) -> Vec<&str> {
+        let mut namespaces: Vec<&str> = self
+            .releases
+            .iter()
+            .map(|release| release.namespace.as_str())
+            .collect();
+        namespaces.sort_unstable();
+        namespaces.dedup();
+        namespaces
+    }
+
+    /// whether a release with the given name exists
+    pub fn release_exists(&self, name: &str) -> bool {
+        self.find_release(name).is_some()
+    }
+
+    /// releases whose name approximately matches `pattern`, best matches first.
+    ///
+    /// Names are compared case insensitively, matching exactly, by prefix,
+    /// by substring or as a glob with `*` and `?` wildcards.
+    pub fn find_release_fuzzy(&self, pattern: &str) -> Vec<ReleaseMatch> {
+        let pattern = pattern.to_lowercase();
+        let mut matches: Vec<ReleaseMatch> = self
+            .releases
+            .iter()
+            .filter_map(|release| {
+                let kind = MatchKind::of(&release.name, &pattern)?;
+                Some(ReleaseMatch {
+                    release: release.clone(),
+                    kind,
+                })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            (a.kind, &a.release.name, &a.release.namespace).cmp(&(
+                b.kind,
+                &b.release.name,
+                &b.release.namespace,
+            ))
+        });
+        matches
+    }
+
+    /// releases installed from the chart with the given name, in any version
+    pub fn find_releases_using_chart(&self, chart: &str) -> Vec<&InstalledChart> {
+        self.releases
+            .iter()
+            .filter(|release| release.chart_name() == chart)
+            .collect()
+    }
+}
+
+/// How a release name matched a fuzzy lookup, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    /// The name equals the pattern
+    Exact,
+    /// The name equals the pattern ignoring case
+    CaseInsensitive,
+    /// The name starts with the pattern
+    Prefix,
+    /// The name contains the pattern
+    Substring,
+    /// The name matches the pattern as a glob
+    Glob,
+}
+
+impl MatchKind {
+    /// Matches a name against a lowercase pattern
+    fn of(name: &str, pattern: &str) -> Option<Self> {
+        let lower = name.to_lowercase();
+        if name == pattern {
+            Some(Self::Exact)
+        } else if lower == pattern {
+            Some(Self::CaseInsensitive)
+        } else if lower.starts_with(pattern) {
+            Some(Self::Prefix)
+        } else if lower.contains(pattern) {
+            Some(Self::Substring)
+        } else if glob_match(pattern.as_bytes(), lower.as_bytes()) {
+            Some(Self::Glob)
+        } else {
+            None
+        }
+    }
+}
+
+/// A release found by a fuzzy lookup
+#[derive(Debug, Clone)]
+pub struct ReleaseMatch {
+    pub release: InstalledChart,
+    pub kind: MatchKind,
+}
+
+/// Matches `text` against a glob where `*` matches any run of characters
+/// and `?` a single one
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match (pattern.split_first(), text.split_first()) {
+        (None, None) => true,
+        (Some((b'*', rest)), _) => {
+            glob_match(rest, text) || (!text.is_empty() && glob_match(pattern, &text[1..]))
+        }
+        (Some((b'?', rest)), Some((_, text))) => glob_match(rest, text),
+        (Some((p, rest)), Some((t, text))) => p == t && glob_match(rest, text),
+        _ => false,
+    }
+}
+
+/// A release not upgraded within the requested window
+#[derive(Debug, Clone)]
+pub struct StaleRelease {
+    /// The installed release
+    pub release: InstalledChart,
+    /// Time since the release was last updated
+    pub age: Duration,
+    /// The newest chart version available in the configured repos, if found
+    pub latest_version: Option<String>,
+}
+
+impl StaleRelease {
+    /// The installed chart version
+    pub fn current_version(&self) -> &str {
+        self.release.chart_version()
+    }
+}
+
+/// A release for which a newer chart version is available
+#[derive(Debug, Clone)]
+pub struct OutdatedRelease {
+    /// The installed release
+    pub release: InstalledChart,
+    /// The installed chart version
+    pub current: String,
+    /// The newest chart version available in the configured repos
+    pub latest: String,
+    /// How far behind the installed version is
+    pub delta: VersionDelta,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_namespace_lookups() {
+        const JSON_RESPONSE: &str = r#"[{"name":"fluvio","namespace":"fluvio","revision":"1","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"fluvio-app-0.9.2","app_version":"0.9.2"},{"name":"fluvio","namespace":"staging","revision":"4","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"fluvio-app-0.9.3","app_version":"0.9.3"},{"name":"fluvio-sys","namespace":"fluvio","revision":"1","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"fluvio-sys-0.9.2","app_version":"0.9.2"}]"#;
+        let releases: Vec<InstalledChart> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        let inventory = ReleaseInventory::new(releases);
+
+        assert_eq!(inventory.namespaces(), vec!["fluvio", "staging"]);
+        assert_eq!(inventory.releases_in_namespace("fluvio").len(), 2);
+        let staging = inventory
+            .find_release_in("staging", "fluvio")
+            .expect("release not found");
+        assert_eq!(staging.chart_version(), "0.9.3");
+        assert!(inventory.find_release_in("default", "fluvio").is_none());
+    }
+
+    #[test]
+    fn test_find_release_fuzzy() {
+        const JSON_RESPONSE: &str = r#"[{"name":"fluvio-sys","namespace":"default","revision":"1","updated":"","status":"deployed","chart":"fluvio-sys-0.9.2","app_version":"0.9.2"},{"name":"Fluvio","namespace":"default","revision":"1","updated":"","status":"deployed","chart":"fluvio-app-0.9.2","app_version":"0.9.2"},{"name":"my-fluvio","namespace":"default","revision":"1","updated":"","status":"deployed","chart":"fluvio-app-0.9.2","app_version":"0.9.2"},{"name":"flv-app","namespace":"default","revision":"1","updated":"","status":"deployed","chart":"fluvio-app-0.9.2","app_version":"0.9.2"}]"#;
+        let releases: Vec<InstalledChart> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        let inventory = ReleaseInventory::new(releases);
+
+        let found = inventory.find_release_fuzzy("fluvio");
+        let found: Vec<(&str, MatchKind)> = found
+            .iter()
+            .map(|found| (found.release.name.as_str(), found.kind))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("Fluvio", MatchKind::CaseInsensitive),
+                ("fluvio-sys", MatchKind::Prefix),
+                ("my-fluvio", MatchKind::Substring),
+            ]
+        );
+
+        let found = inventory.find_release_fuzzy("f*-APP");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].release.name, "flv-app");
+        assert_eq!(found[0].kind, MatchKind::Glob);
+    }
+}
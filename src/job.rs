@@ -0,0 +1,378 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use base64::Engine;
+use serde_json::{json, Map, Value};
+
+use crate::HelmError;
+
+/// Default image running helm, in containers or Kubernetes Jobs
+pub const DEFAULT_HELM_IMAGE: &str = "alpine/helm:3.15.4";
+
+/// Directory the files of a command are mounted at in the Job
+const FILES_MOUNT_PATH: &str = "/helm-files";
+
+/// Label marking the resources generated by this crate
+const MANAGED_BY_LABEL: &str = "app.kubernetes.io/managed-by";
+
+/// Generates a Kubernetes Job running a helm command in the cluster, with
+/// the permissions of a service account instead of the operator's
+/// kubeconfig.
+///
+/// Takes the command of any arg builder, e.g. `InstallArg::upgrade()`.
+/// Local files passed to the command, such as values files or
+/// `--set-file` files, are shipped in a ConfigMap, or a Secret, mounted
+/// into the Job. Files which aren't UTF-8, such as packaged charts, are
+/// shipped base64 encoded.
+#[derive(Debug, Clone)]
+pub struct JobSpec {
+    pub name: String,
+    pub namespace: Option<String>,
+    pub image: String,
+    pub service_account: Option<String>,
+    /// Ship the files in a Secret rather than a ConfigMap
+    pub secret_files: bool,
+    pub backoff_limit: u32,
+    pub ttl_seconds_after_finished: Option<u32>,
+}
+
+impl JobSpec {
+    pub fn new<N: Into<String>>(name: N) -> Self {
+        Self {
+            name: name.into(),
+            namespace: None,
+            image: DEFAULT_HELM_IMAGE.to_string(),
+            service_account: None,
+            secret_files: false,
+            backoff_limit: 0,
+            ttl_seconds_after_finished: None,
+        }
+    }
+
+    /// set namespace of the Job
+    pub fn namespace<S: Into<String>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set image running helm, defaults to [`DEFAULT_HELM_IMAGE`]
+    pub fn image<S: Into<String>>(mut self, image: S) -> Self {
+        self.image = image.into();
+        self
+    }
+
+    /// set service account the Job runs as
+    pub fn service_account<S: Into<String>>(mut self, account: S) -> Self {
+        self.service_account = Some(account.into());
+        self
+    }
+
+    /// ship the files of the command in a Secret, e.g. values with credentials
+    pub fn secret_files(mut self) -> Self {
+        self.secret_files = true;
+        self
+    }
+
+    /// set how many times a failed Job is retried, defaults to none
+    pub fn backoff_limit(mut self, limit: u32) -> Self {
+        self.backoff_limit = limit;
+        self
+    }
+
+    /// delete the Job the given number of seconds after it finished
+    pub fn ttl_seconds_after_finished(mut self, seconds: u32) -> Self {
+        self.ttl_seconds_after_finished = Some(seconds);
+        self
+    }
+
+    /// The manifests running `command`, the ConfigMap or Secret holding its
+    /// files, if any, then the Job
+    pub fn manifests(&self, command: &Command) -> Result<Vec<Value>, HelmError> {
+        let files_name = format!("{}-files", self.name);
+        let mut text_files = Map::new();
+        let mut binary_files = Map::new();
+        let mut args = vec![];
+        for arg in command.get_args() {
+            let arg = arg.to_string_lossy();
+            args.push(rewrite_paths(&arg, |path| {
+                let index = text_files.len() + binary_files.len();
+                let key = format!("{}-{}", index, file_key(path));
+                let contents = fs::read(path).map_err(|source| HelmError::Io {
+                    path: path.to_path_buf(),
+                    source,
+                })?;
+                match String::from_utf8(contents) {
+                    Ok(text) => text_files.insert(key.clone(), Value::String(text)),
+                    Err(err) => {
+                        let encoded =
+                            base64::engine::general_purpose::STANDARD.encode(err.into_bytes());
+                        binary_files.insert(key.clone(), Value::String(encoded))
+                    }
+                };
+                Ok(format!("{}/{}", FILES_MOUNT_PATH, key))
+            })?);
+        }
+
+        let env: Vec<Value> = command
+            .get_envs()
+            .filter_map(|(key, value)| {
+                Some(json!({
+                    "name": key.to_string_lossy(),
+                    "value": value?.to_string_lossy(),
+                }))
+            })
+            .collect();
+
+        let mut container = json!({
+            "name": "helm",
+            "image": self.image,
+            "command": [command.get_program().to_string_lossy()],
+            "args": args,
+        });
+        if !env.is_empty() {
+            container["env"] = Value::Array(env);
+        }
+
+        let mut pod_spec = json!({
+            "restartPolicy": "Never",
+            "containers": [],
+        });
+        if let Some(account) = &self.service_account {
+            pod_spec["serviceAccountName"] = json!(account);
+        }
+
+        let mut manifests = vec![];
+        if !text_files.is_empty() || !binary_files.is_empty() {
+            container["volumeMounts"] = json!([{
+                "name": "files",
+                "mountPath": FILES_MOUNT_PATH,
+                "readOnly": true,
+            }]);
+            // the base64 encoded files go where Kubernetes decodes them
+            let (kind, text_field, binary_field, mut volume) = if self.secret_files {
+                (
+                    "Secret",
+                    "stringData",
+                    "data",
+                    json!({ "secret": { "secretName": files_name } }),
+                )
+            } else {
+                (
+                    "ConfigMap",
+                    "data",
+                    "binaryData",
+                    json!({ "configMap": { "name": files_name } }),
+                )
+            };
+            volume["name"] = json!("files");
+            pod_spec["volumes"] = json!([volume]);
+            let mut files = json!({
+                "apiVersion": "v1",
+                "kind": kind,
+                "metadata": self.metadata(&files_name),
+            });
+            if !text_files.is_empty() {
+                files[text_field] = Value::Object(text_files);
+            }
+            if !binary_files.is_empty() {
+                files[binary_field] = Value::Object(binary_files);
+            }
+            manifests.push(files);
+        }
+        pod_spec["containers"] = json!([container]);
+
+        let mut job_spec = json!({
+            "backoffLimit": self.backoff_limit,
+            "template": { "spec": pod_spec },
+        });
+        if let Some(ttl) = self.ttl_seconds_after_finished {
+            job_spec["ttlSecondsAfterFinished"] = json!(ttl);
+        }
+        manifests.push(json!({
+            "apiVersion": "batch/v1",
+            "kind": "Job",
+            "metadata": self.metadata(&self.name),
+            "spec": job_spec,
+        }));
+        Ok(manifests)
+    }
+
+    /// The manifests running `command` as a YAML stream, ready for
+    /// `kubectl apply -f -`
+    pub fn to_yaml(&self, command: &Command) -> Result<String, HelmError> {
+        let mut yaml = String::new();
+        for manifest in self.manifests(command)? {
+            yaml.push_str("---\n");
+            yaml.push_str(&serde_yaml::to_string(&manifest)?);
+        }
+        Ok(yaml)
+    }
+
+    fn metadata(&self, name: &str) -> Value {
+        let mut metadata = json!({
+            "name": name,
+            "labels": { MANAGED_BY_LABEL: env!("CARGO_PKG_NAME") },
+        });
+        if let Some(namespace) = &self.namespace {
+            metadata["namespace"] = json!(namespace);
+        }
+        metadata
+    }
+}
+
+/// Replaces the local files an argument refers to, either the argument
+/// itself or the files of `key=path` lists as passed to `--set-file`
+fn rewrite_paths<F>(arg: &str, mut rewrite: F) -> Result<String, HelmError>
+where
+    F: FnMut(&Path) -> Result<String, HelmError>,
+{
+    if Path::new(arg).is_file() {
+        return rewrite(Path::new(arg));
+    }
+    if !arg.contains('=') {
+        return Ok(arg.to_string());
+    }
+    let opts: Result<Vec<String>, HelmError> = arg
+        .split(',')
+        .map(|opt| match opt.split_once('=') {
+            Some((key, path)) if Path::new(path).is_file() => {
+                Ok(format!("{}={}", key, rewrite(Path::new(path))?))
+            }
+            _ => Ok(opt.to_string()),
+        })
+        .collect();
+    Ok(opts?.join(","))
+}
+
+/// A ConfigMap key for a file, keeping only the characters keys allow
+fn file_key(path: &Path) -> String {
+    path.file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || "-._".contains(c) {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_job_manifests() {
+        let dir = TempDir::new().unwrap();
+        let values = dir.path().join("values prod.yaml");
+        let cert = dir.path().join("server.crt");
+        fs::write(&values, "replicas: 3\n").unwrap();
+        fs::write(&cert, "CERT\n").unwrap();
+
+        let mut command = Command::new("helm");
+        command
+            .args(["upgrade", "--install", "fluvio", "fluvio/fluvio-app"])
+            .arg("--values")
+            .arg(&values)
+            .arg("--set-file")
+            .arg(format!("tls.cert={}", cert.display()))
+            .env("HELM_NAMESPACE", "fluvio");
+
+        let job = JobSpec::new("fluvio-upgrade")
+            .namespace("fluvio")
+            .service_account("helm-operator");
+        let manifests = job.manifests(&command).unwrap();
+        assert_eq!(manifests.len(), 2);
+
+        let config_map = &manifests[0];
+        assert_eq!(config_map["kind"], "ConfigMap");
+        assert_eq!(config_map["metadata"]["name"], "fluvio-upgrade-files");
+        assert_eq!(config_map["data"]["0-values_prod.yaml"], "replicas: 3\n");
+        assert_eq!(config_map["data"]["1-server.crt"], "CERT\n");
+
+        let job = &manifests[1];
+        assert_eq!(job["kind"], "Job");
+        assert_eq!(job["metadata"]["namespace"], "fluvio");
+        let pod = &job["spec"]["template"]["spec"];
+        assert_eq!(pod["serviceAccountName"], "helm-operator");
+        assert_eq!(
+            pod["volumes"][0]["configMap"]["name"],
+            "fluvio-upgrade-files"
+        );
+        let container = &pod["containers"][0];
+        assert_eq!(container["image"], DEFAULT_HELM_IMAGE);
+        assert_eq!(container["command"], json!(["helm"]));
+        assert_eq!(
+            container["args"],
+            json!([
+                "upgrade",
+                "--install",
+                "fluvio",
+                "fluvio/fluvio-app",
+                "--values",
+                "/helm-files/0-values_prod.yaml",
+                "--set-file",
+                "tls.cert=/helm-files/1-server.crt"
+            ])
+        );
+        assert_eq!(container["env"][0]["name"], "HELM_NAMESPACE");
+    }
+
+    #[test]
+    fn test_job_binary_files() {
+        let dir = TempDir::new().unwrap();
+        let chart = dir.path().join("fluvio-app-0.9.2.tgz");
+        let values = dir.path().join("values.yaml");
+        fs::write(&chart, [0x1f, 0x8b, 0x08, 0x00, 0xff]).unwrap();
+        fs::write(&values, "replicas: 3\n").unwrap();
+
+        let mut command = Command::new("helm");
+        command
+            .args(["install", "fluvio"])
+            .arg(&chart)
+            .arg("--values")
+            .arg(&values);
+
+        let manifests = JobSpec::new("fluvio-install").manifests(&command).unwrap();
+        let config_map = &manifests[0];
+        assert_eq!(
+            config_map["binaryData"]["0-fluvio-app-0.9.2.tgz"],
+            "H4sIAP8="
+        );
+        assert_eq!(config_map["data"]["1-values.yaml"], "replicas: 3\n");
+
+        let manifests = JobSpec::new("fluvio-install")
+            .secret_files()
+            .manifests(&command)
+            .unwrap();
+        let secret = &manifests[0];
+        assert_eq!(secret["data"]["0-fluvio-app-0.9.2.tgz"], "H4sIAP8=");
+        assert_eq!(secret["stringData"]["1-values.yaml"], "replicas: 3\n");
+        assert_eq!(
+            manifests[1]["spec"]["template"]["spec"]["containers"][0]["args"][2],
+            "/helm-files/0-fluvio-app-0.9.2.tgz"
+        );
+    }
+
+    #[test]
+    fn test_job_without_files() {
+        let mut command = Command::new("helm");
+        command.args(["uninstall", "fluvio"]);
+        let job = JobSpec::new("fluvio-uninstall")
+            .secret_files()
+            .ttl_seconds_after_finished(600);
+        let yaml = job.to_yaml(&command).unwrap();
+        let documents: Vec<serde_yaml::Value> = crate::hooks::parse_yaml_documents(&yaml).unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(
+            documents[0]["spec"]["ttlSecondsAfterFinished"].as_u64(),
+            Some(600)
+        );
+        assert!(documents[0]["spec"]["template"]["spec"]["volumes"].is_null());
+    }
+}
@@ -1,68 +1,423 @@
-use std::path::PathBuf;
-use std::process::Command;
-
-use serde::Deserialize;
-use tracing::{instrument, warn};
-
+// without the client most parsers are only reachable from tests
+#![cfg_attr(not(feature = "client"), allow(dead_code, unused_imports))]
+
+use std::any::Any;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use fluvio_command::CommandErrorKind;
+use semver::{Version, VersionReq};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument, warn};
+
+mod batch;
+#[cfg(feature = "client")]
+mod builder;
+#[cfg(feature = "client")]
+mod bundle;
+mod chart;
+mod chart_lock;
+mod checkpoint;
+mod compat;
+#[cfg(feature = "container")]
+mod container;
+mod crd;
+mod create;
+mod dependency;
+mod deprecation;
+mod diff;
+mod env;
 mod error;
+mod explain;
+mod failure;
+#[cfg(feature = "client")]
+mod fluvio;
+mod hooks;
+mod inventory;
+mod job;
+mod lint;
+mod local;
+mod lockfile;
+mod migrate;
+mod oci;
+mod package;
+mod plan;
+#[cfg(feature = "client")]
+mod process;
+mod pull;
+mod push;
+mod receipts;
+#[cfg(feature = "repo-refresh")]
+mod refresh;
+mod registry;
+mod release;
+mod repo;
+#[cfg(feature = "client")]
+mod repo_lock;
+mod resources;
+mod search;
+mod temp;
+mod template;
+mod time;
+#[cfg(feature = "client")]
+mod transport;
+mod values_lint;
+mod verify;
+mod version;
+use crate::batch::Scheduler;
+pub use crate::batch::{BatchArg, BatchEntry, BatchOutcome, BatchReport};
+#[cfg(feature = "client")]
+pub use crate::builder::{HelmClientBuilder, DEFAULT_SUPPORTED_HELM_VERSIONS};
+use crate::chart::parse_oci_digest;
+pub use crate::chart::{chart_digest, ChartDocs, ChartRef};
+pub use crate::chart_lock::{
+    verify_chart_lock, ChartLockReport, LockedDependency, VendoredDependency,
+};
+pub use crate::checkpoint::{InstallCheckpoint, InstallStep};
+pub use crate::compat::{ChartConstraints, ConstraintViolation, PreflightReport};
+#[cfg(feature = "container")]
+pub use crate::container::{ContainerRuntime, ContainerTransport};
+pub use crate::crd::{CrdChange, CrdChangeKind};
+pub use crate::create::CreateArg;
+pub use crate::dependency::DependencyEntry;
+use crate::deprecation::{layout_warnings, metadata_warnings};
+pub use crate::deprecation::{ChartWarning, PulledChart, RenderedChart};
+pub use crate::env::HelmEnv;
 pub use crate::error::HelmError;
-use fluvio_command::CommandExt;
+use crate::explain::DryRunRelease;
+pub use crate::explain::InstallExplanation;
+pub use crate::failure::{HelmFailureKind, ALLOWED_STDERR, FAILURE_PATTERNS};
+#[cfg(feature = "client")]
+pub use crate::fluvio::{
+    FluvioChartInstaller, FluvioInstallReport, FLUVIO_APP_CHART, FLUVIO_REPO_NAME, FLUVIO_REPO_URL,
+    FLUVIO_SYS_CHART,
+};
+use crate::hooks::parse_yaml_documents;
+pub use crate::hooks::{HookManifest, HookOutcome, HookPhase, HookReport};
+pub use crate::inventory::{
+    MatchKind, OutdatedRelease, ReleaseInventory, ReleaseMatch, StaleRelease,
+};
+pub use crate::job::{JobSpec, DEFAULT_HELM_IMAGE};
+pub use crate::lint::{LintArg, LintMessage, LintSeverity};
+pub use crate::local::{is_local_registry, LocalCluster};
+pub use crate::lockfile::{LockedChart, Lockfile, LOCKFILE_NAME};
+pub use crate::migrate::{CleanupV2Arg, ConvertReleaseArg, MIGRATE_PLUGIN};
+pub use crate::package::{PackageArg, PackageResult};
+pub use crate::plan::{
+    AbsentSpec, ActionOutcome, ActionReport, ApplyReport, FailurePolicy, Plan, PlannedAction,
+    ReleaseSet, ReleaseSpec, RepoSpec,
+};
+#[cfg(feature = "signals")]
+pub use crate::process::install_signal_handler;
+#[cfg(feature = "client")]
+pub use crate::process::{recent_commands, terminate_children, CommandRecord};
+pub use crate::pull::PullArg;
+pub use crate::push::PushArg;
+use crate::receipts::ReceiptStore;
+pub use crate::receipts::{Receipt, ReceiptOperation};
+#[cfg(feature = "repo-refresh")]
+pub use crate::refresh::RepoRefresher;
+pub use crate::registry::{RegistryLoginArg, RegistryLogoutArg};
+pub use crate::release::{
+    HistoryArg, HistoryEntry, ReleaseDump, ReleaseInfo, ReleaseMetadata, ReleaseStatus,
+    RollbackArg, RollbackResult, StatusArg, TestArg, TestResult, TestSuite,
+};
+pub use crate::repo::{HelmRepo, RepoAddArg, RepoAddFailure, RepoCredentials, RepoUrl};
+#[cfg(feature = "client")]
+use crate::repo_lock::{lock_repo_config, RepoConfigGuard};
+pub use crate::resources::{ResourceInventory, ResourceRef};
+pub use crate::search::{HubRepository, HubResult, SearchRepoArg};
+use crate::temp::TempDir;
+pub use crate::template::TemplateArg;
+use crate::time::{go_duration, parse_helm_timestamp};
+#[cfg(feature = "client")]
+pub use crate::transport::{KubectlExecTransport, SshTransport, Transport};
+pub use crate::values_lint::UnknownValueKey;
+pub use crate::verify::VerifyArg;
+pub use crate::version::VersionDelta;
+use crate::version::{compare_versions, newest_matching};
 
 /// Installer Argument
-#[derive(Debug)]
-pub struct InstallArg {
-    pub name: String,
-    pub chart: String,
-    pub version: Option<String>,
-    pub namespace: Option<String>,
-    pub opts: Vec<(String, String)>,
+#[derive(Debug, Clone)]
+pub struct InstallArg<'a> {
+    pub name: Cow<'a, str>,
+    pub chart: ChartRef,
+    pub version: Option<Cow<'a, str>>,
+    pub namespace: Option<Cow<'a, str>>,
+    pub kubeconfig: Option<PathBuf>,
+    pub kube_context: Option<Cow<'a, str>>,
+    pub create_namespace: bool,
+    pub opts: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub string_opts: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub json_opts: Vec<(Cow<'a, str>, serde_json::Value)>,
+    pub literal_opts: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub file_opts: Vec<(Cow<'a, str>, PathBuf)>,
     pub values: Vec<PathBuf>,
     pub develop: bool,
+    pub atomic: bool,
+    pub wait: bool,
+    pub wait_for_jobs: bool,
+    pub timeout: Option<Duration>,
+    pub dry_run: bool,
+    pub skip_crds: bool,
+    pub no_hooks: bool,
+    pub description: Option<Cow<'a, str>>,
+    pub generate_name: bool,
+    pub render_subchart_notes: bool,
+    pub replace: bool,
+    pub labels: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub verify: bool,
+    pub keyring: Option<PathBuf>,
 }
 
-impl InstallArg {
-    pub fn new<N: Into<String>, C: Into<String>>(name: N, chart: C) -> Self {
+impl<'a> InstallArg<'a> {
+    pub fn new<N: Into<Cow<'a, str>>, C: Into<ChartRef>>(name: N, chart: C) -> Self {
         Self {
             name: name.into(),
             chart: chart.into(),
             version: None,
             namespace: None,
+            kubeconfig: None,
+            kube_context: None,
+            create_namespace: false,
             opts: vec![],
+            string_opts: vec![],
+            json_opts: vec![],
+            literal_opts: vec![],
+            file_opts: vec![],
             values: vec![],
             develop: false,
+            atomic: false,
+            wait: false,
+            wait_for_jobs: false,
+            timeout: None,
+            dry_run: false,
+            skip_crds: false,
+            no_hooks: false,
+            description: None,
+            generate_name: false,
+            render_subchart_notes: false,
+            replace: false,
+            labels: vec![],
+            verify: false,
+            keyring: None,
         }
     }
 
+    /// Preset for installing onto a local development cluster.
+    ///
+    /// Accepts development versions of the chart, creates the namespace
+    /// when missing and waits for the resources to be ready. Settings can
+    /// be overridden with the other builder methods.
+    pub fn preset_local_dev<N: Into<Cow<'a, str>>, C: Into<ChartRef>>(name: N, chart: C) -> Self {
+        Self::new(name, chart).develop().create_namespace().wait()
+    }
+
+    /// Preset for installing onto a shared or production cluster.
+    ///
+    /// Only stable chart versions are considered, resources and jobs are
+    /// waited for up to ten minutes and a failed install or upgrade is
+    /// rolled back. Settings can be overridden with the other builder
+    /// methods.
+    pub fn preset_cloud<N: Into<Cow<'a, str>>, C: Into<ChartRef>>(name: N, chart: C) -> Self {
+        Self::new(name, chart)
+            .atomic()
+            .wait_for_jobs()
+            .timeout(Duration::from_secs(600))
+    }
+
     /// set chart version
-    pub fn version<S: Into<String>>(mut self, version: S) -> Self {
+    pub fn version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
         self.version = Some(version.into());
         self
     }
 
     /// set namepsace
-    pub fn namespace<S: Into<String>>(mut self, ns: S) -> Self {
+    pub fn namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
         self.namespace = Some(ns.into());
         self
     }
 
+    /// create the namespace if it doesn't exist
+    pub fn create_namespace(mut self) -> Self {
+        self.create_namespace = true;
+        self
+    }
+
+    /// set kubeconfig, overriding the one of the client
+    pub fn kubeconfig<P: Into<PathBuf>>(mut self, kubeconfig: P) -> Self {
+        self.kubeconfig = Some(kubeconfig.into());
+        self
+    }
+
+    /// set kube context, overriding the one of the client
+    pub fn kube_context<S: Into<Cow<'a, str>>>(mut self, context: S) -> Self {
+        self.kube_context = Some(context.into());
+        self
+    }
+
     /// reset array of options
-    pub fn opts(mut self, options: Vec<(String, String)>) -> Self {
-        self.opts = options;
+    pub fn opts<I, K, V>(mut self, options: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.opts = options
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
         self
     }
 
     /// set a single option
-    pub fn opt<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+    pub fn opt<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>>(mut self, key: K, value: V) -> Self {
         self.opts.push((key.into(), value.into()));
         self
     }
 
+    /// set a single option kept as a string, e.g. an image tag like `1.10`
+    /// helm would otherwise read as a number
+    pub fn opt_string<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>>(
+        mut self,
+        key: K,
+        value: V,
+    ) -> Self {
+        self.string_opts.push((key.into(), value.into()));
+        self
+    }
+
+    /// set a single option to a JSON value, e.g. a list or nested map,
+    /// requires helm 3.10 or later
+    pub fn opt_json<K: Into<Cow<'a, str>>>(mut self, key: K, value: serde_json::Value) -> Self {
+        self.json_opts.push((key.into(), value));
+        self
+    }
+
+    /// set a single option passed verbatim, e.g. a value containing commas,
+    /// requires helm 3.12 or later
+    pub fn opt_literal<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>>(
+        mut self,
+        key: K,
+        value: V,
+    ) -> Self {
+        self.literal_opts.push((key.into(), value.into()));
+        self
+    }
+
+    /// set a single option to the contents of a file, e.g. a certificate
+    pub fn set_file<K: Into<Cow<'a, str>>, P: Into<PathBuf>>(mut self, key: K, path: P) -> Self {
+        self.file_opts.push((key.into(), path.into()));
+        self
+    }
+
     /// set to use develop
     pub fn develop(mut self) -> Self {
         self.develop = true;
         self
     }
 
+    /// roll back a failed install or upgrade, implies waiting for the resources
+    pub fn atomic(mut self) -> Self {
+        self.atomic = true;
+        self
+    }
+
+    /// wait until the resources are ready before returning
+    pub fn wait(mut self) -> Self {
+        self.wait = true;
+        self
+    }
+
+    /// wait until the resources are ready and the jobs completed
+    pub fn wait_for_jobs(mut self) -> Self {
+        self.wait = true;
+        self.wait_for_jobs = true;
+        self
+    }
+
+    /// set how long to wait for the resources, helm defaults to 5 minutes
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// simulate the install or upgrade, the rendered release is returned
+    /// in the result instead of being applied
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+
+    /// leave out the CRDs of the chart, e.g. when they are managed separately
+    pub fn skip_crds(mut self) -> Self {
+        self.skip_crds = true;
+        self
+    }
+
+    /// don't run the install or upgrade hooks
+    pub fn no_hooks(mut self) -> Self {
+        self.no_hooks = true;
+        self
+    }
+
+    /// set description of the release revision, e.g. a CI build identifier
+    pub fn description<S: Into<Cow<'a, str>>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// let helm generate the release name on install, ignoring the name of
+    /// the arg; the generated name is returned in the result
+    pub fn generate_name(mut self) -> Self {
+        self.generate_name = true;
+        self
+    }
+
+    /// render the NOTES.txt of subcharts along with the one of the chart
+    pub fn render_subchart_notes(mut self) -> Self {
+        self.render_subchart_notes = true;
+        self
+    }
+
+    /// re-use the name of an uninstalled release whose history was kept,
+    /// only applies to install
+    pub fn replace(mut self) -> Self {
+        self.replace = true;
+        self
+    }
+
+    /// add a label to the release, e.g. its owner, to find it with
+    /// `find_releases_by_selector`; requires helm 3.13 or later
+    pub fn label<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>>(mut self, key: K, value: V) -> Self {
+        self.labels.push((key.into(), value.into()));
+        self
+    }
+
+    /// only accept a chart whose provenance file verifies
+    pub fn verify(mut self) -> Self {
+        self.verify = true;
+        self
+    }
+
+    /// set keyring holding the public keys the chart is verified with,
+    /// implies `verify`
+    pub fn keyring<P: Into<PathBuf>>(mut self, keyring: P) -> Self {
+        self.verify = true;
+        self.keyring = Some(keyring.into());
+        self
+    }
+
     /// set list of values
     pub fn values(mut self, values: Vec<PathBuf>) -> Self {
         self.values = values;
@@ -75,16 +430,43 @@ impl InstallArg {
         self
     }
 
+    /// Checks for flag combinations helm would reject or silently ignore
+    pub fn validate(&self) -> Result<(), HelmError> {
+        if let (Some(digest), Some(version)) = (self.chart.digest(), &self.version) {
+            return Err(HelmError::InvalidArg(format!(
+                "chart is pinned to digest {}, version {} must not be set",
+                digest, version
+            )));
+        }
+        Ok(())
+    }
+
     pub fn install(&self) -> Command {
         let mut command = Command::new("helm");
-        command.args(["install", &self.name, &self.chart]);
+        if self.generate_name {
+            command.arg("install");
+        } else {
+            command.args(["install", &self.name]);
+        }
+        self.chart.apply(&mut command);
         self.apply_args(&mut command);
+        if self.replace {
+            command.arg("--replace");
+        }
+        if self.generate_name {
+            command.arg("--generate-name");
+            // the generated name is read from the release helm prints
+            if !self.dry_run {
+                command.args(["--output", "json"]);
+            }
+        }
         command
     }
 
     pub fn upgrade(&self) -> Command {
         let mut command = Command::new("helm");
-        command.args(["upgrade", "--install", &self.name, &self.chart]);
+        command.args(["upgrade", "--install", &self.name]);
+        self.chart.apply(&mut command);
         self.apply_args(&mut command);
         command
     }
@@ -94,14 +476,77 @@ impl InstallArg {
             command.args(["--namespace", namespace]);
         }
 
+        apply_kube_flags(
+            command,
+            self.kubeconfig.as_deref(),
+            self.kube_context.as_deref(),
+        );
+
+        if self.create_namespace {
+            command.arg("--create-namespace");
+        }
+
         if self.develop {
             command.arg("--devel");
         }
 
+        if self.atomic {
+            command.arg("--atomic");
+        }
+
+        if self.wait {
+            command.arg("--wait");
+        }
+
+        if self.wait_for_jobs {
+            command.arg("--wait-for-jobs");
+        }
+
+        if let Some(timeout) = self.timeout {
+            command.args(["--timeout", &go_duration(timeout)]);
+        }
+
+        if self.dry_run {
+            command.args(["--dry-run", "--output", "json"]);
+        }
+
+        if self.skip_crds {
+            command.arg("--skip-crds");
+        }
+
+        if self.no_hooks {
+            command.arg("--no-hooks");
+        }
+
+        if let Some(description) = &self.description {
+            command.args(["--description", description]);
+        }
+
+        if self.render_subchart_notes {
+            command.arg("--render-subchart-notes");
+        }
+
+        if !self.labels.is_empty() {
+            let labels: Vec<String> = self
+                .labels
+                .iter()
+                .map(|(key, val)| format!("{}={}", key, val))
+                .collect();
+            command.args(["--labels", &labels.join(",")]);
+        }
+
         if let Some(version) = &self.version {
             command.args(["--version", version]);
         }
 
+        if self.verify {
+            command.arg("--verify");
+        }
+
+        if let Some(keyring) = &self.keyring {
+            command.arg("--keyring").arg(keyring);
+        }
+
         for value_path in &self.values {
             command.arg("--values").arg(value_path);
         }
@@ -109,62 +554,179 @@ impl InstallArg {
         for (key, val) in &self.opts {
             command.arg("--set").arg(format!("{}={}", key, val));
         }
-    }
-}
 
-impl From<InstallArg> for Command {
-    fn from(arg: InstallArg) -> Self {
-        let mut command = Command::new("helm");
-        command.args(["install", &arg.name, &arg.chart]);
+        for (key, val) in &self.string_opts {
+            command.arg("--set-string").arg(format!("{}={}", key, val));
+        }
 
-        if let Some(namespace) = &arg.namespace {
-            command.args(["--namespace", namespace]);
+        for (key, val) in &self.json_opts {
+            command.arg("--set-json").arg(format!("{}={}", key, val));
         }
 
-        if arg.develop {
-            command.arg("--devel");
+        for (key, val) in &self.literal_opts {
+            command.arg("--set-literal").arg(format!("{}={}", key, val));
         }
 
-        if let Some(version) = &arg.version {
-            command.args(["--version", version]);
+        for (key, path) in &self.file_opts {
+            let mut opt = OsString::from(&**key);
+            opt.push("=");
+            opt.push(path);
+            command.arg("--set-file").arg(opt);
         }
+    }
+}
 
-        for value_path in &arg.values {
-            command.arg("--values").arg(value_path);
+impl From<InstallArg<'_>> for Command {
+    fn from(arg: InstallArg) -> Self {
+        arg.install()
+    }
+}
+
+/// Outcome of an install or upgrade
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct InstallResult {
+    /// sha256 digest of the chart archive, when installed from a local `.tgz`
+    pub chart_digest: Option<String>,
+    /// manifest digest reported by the registry, when installed from an OCI reference
+    pub oci_digest: Option<String>,
+    /// The release helm would have applied, for dry runs
+    pub rendered: Option<RenderedRelease>,
+    /// The release name helm generated, with `generate_name`
+    pub generated_name: Option<String>,
+}
+
+impl InstallResult {
+    fn new(args: &InstallArg, output: &Output) -> Result<Self, HelmError> {
+        let chart_digest = args.chart.local_archive().map(chart_digest).transpose()?;
+        let oci_digest = parse_oci_digest(&String::from_utf8_lossy(&output.stderr))
+            .or_else(|| parse_oci_digest(&String::from_utf8_lossy(&output.stdout)));
+        let rendered = if args.dry_run {
+            Some(RenderedRelease::parse(&output.stdout)?)
+        } else {
+            None
+        };
+        let generated_name = if args.generate_name {
+            Some(parse_release_name(&output.stdout)?)
+        } else {
+            None
+        };
+        Ok(Self {
+            chart_digest,
+            oci_digest,
+            rendered,
+            generated_name,
+        })
+    }
+
+    /// The name of the release, as generated by helm or given in the arg
+    pub fn release_name<'a>(&'a self, args: &'a InstallArg) -> &'a str {
+        self.generated_name.as_deref().unwrap_or(&args.name)
+    }
+}
+
+/// Reads the name of the release printed by `--output json`
+fn parse_release_name(json: &[u8]) -> Result<String, HelmError> {
+    #[derive(Deserialize)]
+    struct Release {
+        name: String,
+    }
+
+    let release: Release = serde_json::from_slice(json)?;
+    Ok(release.name)
+}
+
+/// A release rendered by a dry run install or upgrade
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedRelease {
+    /// The rendered manifest, as YAML
+    pub manifest: String,
+    /// The rendered hook resources, as a YAML stream
+    pub hooks: String,
+    /// The rendered NOTES.txt
+    pub notes: String,
+}
+
+impl RenderedRelease {
+    /// Parses the release printed by `--dry-run --output json`
+    fn parse(json: &[u8]) -> Result<Self, HelmError> {
+        #[derive(Deserialize)]
+        struct Release {
+            #[serde(default)]
+            manifest: String,
+            #[serde(default)]
+            hooks: Vec<Hook>,
+            info: ReleaseInfo,
         }
 
-        for (key, val) in &arg.opts {
-            command.arg("--set").arg(format!("{}={}", key, val));
+        #[derive(Deserialize)]
+        struct Hook {
+            #[serde(default)]
+            manifest: String,
         }
 
-        command
+        let release: Release = serde_json::from_slice(json)?;
+        let hooks = release
+            .hooks
+            .iter()
+            .map(|hook| format!("---\n{}\n", hook.manifest.trim()))
+            .collect();
+        Ok(Self {
+            manifest: release.manifest,
+            hooks,
+            notes: release.info.notes,
+        })
     }
 }
 
 /// Uninstaller Argument
 #[derive(Debug)]
-pub struct UninstallArg {
-    pub release: String,
-    pub namespace: Option<String>,
+pub struct UninstallArg<'a> {
+    pub release: Cow<'a, str>,
+    pub namespace: Option<Cow<'a, str>>,
+    pub kubeconfig: Option<PathBuf>,
+    pub kube_context: Option<Cow<'a, str>>,
     pub ignore_not_found: bool,
     pub dry_run: bool,
-    pub timeout: Option<String>,
+    pub timeout: Option<Cow<'a, str>>,
+    pub keep_history: bool,
+    pub no_hooks: bool,
+    pub description: Option<Cow<'a, str>>,
+    pub inspect: bool,
 }
 
-impl UninstallArg {
-    pub fn new(release: String) -> Self {
+impl<'a> UninstallArg<'a> {
+    pub fn new<R: Into<Cow<'a, str>>>(release: R) -> Self {
         Self {
-            release,
+            release: release.into(),
             namespace: None,
+            kubeconfig: None,
+            kube_context: None,
             ignore_not_found: false,
             dry_run: false,
             timeout: None,
+            keep_history: false,
+            no_hooks: false,
+            description: None,
+            inspect: false,
         }
     }
 
     /// set namepsace
-    pub fn namespace(mut self, ns: String) -> Self {
-        self.namespace = Some(ns);
+    pub fn namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set kubeconfig, overriding the one of the client
+    pub fn kubeconfig<P: Into<PathBuf>>(mut self, kubeconfig: P) -> Self {
+        self.kubeconfig = Some(kubeconfig.into());
+        self
+    }
+
+    /// set kube context, overriding the one of the client
+    pub fn kube_context<S: Into<Cow<'a, str>>>(mut self, context: S) -> Self {
+        self.kube_context = Some(context.into());
         self
     }
 
@@ -181,13 +743,38 @@ impl UninstallArg {
     }
 
     /// set timeout
-    pub fn timeout(mut self, timeout: String) -> Self {
-        self.timeout = Some(timeout);
+    pub fn timeout<S: Into<Cow<'a, str>>>(mut self, timeout: S) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+
+    /// keep the release history, allowing a rollback
+    pub fn keep_history(mut self) -> Self {
+        self.keep_history = true;
+        self
+    }
+
+    /// read the release first to report the deleted resources and hooks run,
+    /// at the cost of an extra helm call
+    pub fn inspect(mut self) -> Self {
+        self.inspect = true;
+        self
+    }
+
+    /// don't run the uninstall hooks
+    pub fn no_hooks(mut self) -> Self {
+        self.no_hooks = true;
+        self
+    }
+
+    /// set description of the uninstall, kept in the history with `keep_history`
+    pub fn description<S: Into<Cow<'a, str>>>(mut self, description: S) -> Self {
+        self.description = Some(description.into());
         self
     }
 }
 
-impl From<UninstallArg> for Command {
+impl From<UninstallArg<'_>> for Command {
     fn from(arg: UninstallArg) -> Self {
         let mut command = Command::new("helm");
         command.args(["uninstall", &arg.release]);
@@ -196,124 +783,2293 @@ impl From<UninstallArg> for Command {
             command.args(["--namespace", namespace]);
         }
 
+        apply_kube_flags(
+            &mut command,
+            arg.kubeconfig.as_deref(),
+            arg.kube_context.as_deref(),
+        );
+
         if arg.dry_run {
             command.arg("--dry-run");
         }
 
-        if let Some(timeout) = arg.timeout {
-            command.arg("--timeout").arg(timeout);
+        if let Some(timeout) = &arg.timeout {
+            command.args(["--timeout", timeout]);
+        }
+
+        if arg.keep_history {
+            command.arg("--keep-history");
+        }
+
+        if arg.no_hooks {
+            command.arg("--no-hooks");
+        }
+
+        if let Some(description) = &arg.description {
+            command.args(["--description", description]);
         }
 
         command
     }
 }
 
+/// Outcome of an uninstall
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct UninstallResult {
+    /// The release name
+    pub release: String,
+    /// Whether the release existed, `false` only if not found errors are ignored
+    pub found: bool,
+    /// Whether pre-delete or post-delete hooks of the release ran, only known
+    /// with `inspect`
+    pub hooks_ran: bool,
+    /// Whether the release history was kept
+    pub kept_history: bool,
+    /// Number of objects of the release manifest which were deleted, only
+    /// counted with `inspect` and none for dry runs
+    pub resources_deleted: usize,
+    /// Objects kept due to the `helm.sh/resource-policy` annotation, as `[Kind] name`
+    pub resources_kept: Vec<String>,
+}
+
+/// How long a release listing is reused by lookups on the client
+#[cfg(feature = "client")]
+pub(crate) const DEFAULT_INVENTORY_MAX_AGE: Duration = Duration::from_secs(5);
+
 /// Client to manage helm operations
+#[cfg(feature = "client")]
 #[derive(Debug)]
 #[non_exhaustive]
-pub struct HelmClient {}
+pub struct HelmClient {
+    credentials: HashMap<String, RepoCredentials>,
+    kube_context: Option<String>,
+    local_registries: bool,
+    chart_aliases: HashMap<String, Vec<String>>,
+    inventory: Mutex<Option<ReleaseInventory>>,
+    inventory_max_age: Duration,
+    receipts: Option<ReceiptStore>,
+    constraints: HashMap<String, ChartConstraints>,
+    transport: Option<Arc<dyn Transport>>,
+    repo_lock_file: Option<PathBuf>,
+    lockfile: Option<PathBuf>,
+    strict_stderr: bool,
+    allowed_stderr: Vec<String>,
+}
 
+#[cfg(feature = "client")]
 impl HelmClient {
     /// Creates a Rust client to manage our helm needs.
     ///
     /// This only succeeds if the helm command can be found.
+    /// The helm version must be in [`DEFAULT_SUPPORTED_HELM_VERSIONS`],
+    /// use [`HelmClient::builder`] to accept a different range.
     pub fn new() -> Result<Self, HelmError> {
-        let output = Command::new("helm").arg("version").result()?;
+        Self::builder().build()
+    }
 
-        // Convert command output into a string
-        let out_str = String::from_utf8(output.stdout).map_err(HelmError::Utf8Error)?;
+    /// Returns a builder to configure the client before checking for helm
+    pub fn builder() -> HelmClientBuilder {
+        HelmClientBuilder::default()
+    }
 
-        // Check that the version command gives a version.
-        // In the future, we can parse the version string and check
-        // for compatible CLI client version.
-        if !out_str.contains("version") {
-            return Err(HelmError::HelmVersionNotFound(out_str));
-        }
+    /// Sets the kube context used by every command run by this client
+    pub fn set_kube_context<S: Into<String>>(&mut self, context: S) -> &mut Self {
+        self.kube_context = Some(context.into());
+        self
+    }
 
-        // If checks succeed, create Helm client
-        Ok(Self {})
+    /// Fails helm commands printing anything on stderr except known
+    /// informational messages, see [`ALLOWED_STDERR`], or fragments allowed
+    /// with [`HelmClient::allow_stderr`].
+    ///
+    /// The error holds the unexpected lines. Off by default, where warnings
+    /// of successful commands are ignored.
+    pub fn set_strict_stderr(&mut self, strict: bool) -> &mut Self {
+        self.strict_stderr = strict;
+        self
     }
 
-    /// Installs the given chart under the given name.
+    /// Accepts stderr lines containing `fragment` in strict stderr mode
+    pub fn allow_stderr<S: Into<String>>(&mut self, fragment: S) -> &mut Self {
+        self.allowed_stderr.push(fragment.into());
+        self
+    }
+
+    /// Targets the given local development cluster.
     ///
-    #[instrument(skip(self))]
-    pub fn install(&self, args: &InstallArg) -> Result<(), HelmError> {
-        let mut command = args.install();
-        command.result()?;
-        Ok(())
+    /// Selects its kube context and talks plain http to local OCI
+    /// registries such as `localhost:5001`.
+    pub fn use_local_cluster(&mut self, cluster: &LocalCluster) -> &mut Self {
+        self.kube_context = Some(cluster.context());
+        self.local_registries = true;
+        self
     }
 
-    /// Upgrades the given chart
+    /// Registers a logical chart name resolved against an ordered list of repos.
+    ///
+    /// Installing or searching for `name` tries `<repo>/<name>` in each repo
+    /// in turn and uses the first one that has the chart, e.g. a mirror
+    /// first with the upstream repo as fallback.
+    pub fn register_chart_alias<N, R, S>(&mut self, name: N, repos: R) -> &mut Self
+    where
+        N: Into<String>,
+        R: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.chart_aliases
+            .insert(name.into(), repos.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Resolves a chart alias to `<repo>/<chart>` in the first repo that has it.
+    ///
+    /// Returns `None` if `name` is not a registered alias.
     #[instrument(skip(self))]
-    pub fn upgrade(&self, args: &InstallArg) -> Result<(), HelmError> {
-        let mut command = args.upgrade();
-        command.result()?;
-        Ok(())
+    pub fn resolve_chart_alias(
+        &self,
+        name: &str,
+        version: Option<&str>,
+    ) -> Result<Option<String>, HelmError> {
+        let repos = match self.chart_aliases.get(name) {
+            Some(repos) => repos,
+            None => return Ok(None),
+        };
+
+        for repo in repos {
+            let candidate = format!("{}/{}", repo, name);
+            let found = self
+                .search_repo_unaliased(&candidate, version)?
+                .iter()
+                .any(|chart| chart.name == candidate);
+            if found {
+                debug!(%candidate, "resolved chart alias");
+                return Ok(Some(candidate));
+            }
+            debug!(%candidate, "chart not found, trying next repo");
+        }
+
+        Err(HelmError::ChartNotFound {
+            chart: name.to_string(),
+            repos: repos.clone(),
+        })
     }
 
-    /// Uninstalls specified chart library
-    pub fn uninstall(&self, uninstall: UninstallArg) -> Result<(), HelmError> {
-        if uninstall.ignore_not_found {
-            let app_charts = self
-                .get_installed_chart_by_name(&uninstall.release, uninstall.namespace.as_deref())?;
-            if app_charts.is_empty() {
-                warn!("Chart does not exists, {}", &uninstall.release);
-                return Ok(());
+    /// Replaces a chart alias in the install argument with the resolved chart
+    fn resolve_install_arg<'a, 'b>(
+        &self,
+        args: &'a InstallArg<'b>,
+    ) -> Result<Cow<'a, InstallArg<'b>>, HelmError> {
+        let name = match &args.chart {
+            ChartRef::Name(name) => name,
+            _ => return Ok(Cow::Borrowed(args)),
+        };
+        match self.resolve_chart_alias(name, args.version.as_deref())? {
+            Some(resolved) => {
+                let mut args = args.clone();
+                args.chart = ChartRef::Name(resolved);
+                Ok(Cow::Owned(args))
             }
+            None => Ok(Cow::Borrowed(args)),
         }
-        let mut command: Command = uninstall.into();
-        command.result()?;
-        Ok(())
     }
 
-    /// Adds a new helm repo with the given chart name and chart location
-    #[instrument(skip(self))]
-    pub fn repo_add(&self, chart: &str, location: &str) -> Result<(), HelmError> {
-        Command::new("helm")
-            .args(["repo", "add", chart, location])
-            .result()?;
-        Ok(())
+    /// Sets how long a release listing may be reused by `release_exists`,
+    /// `ensure_release` and `find_releases_using_chart`
+    pub fn set_inventory_max_age(&mut self, max_age: Duration) -> &mut Self {
+        self.inventory_max_age = max_age;
+        self
     }
 
-    /// Updates the local helm repository
-    #[instrument(skip(self))]
-    pub fn repo_update(&self) -> Result<(), HelmError> {
-        Command::new("helm").args(["repo", "update"]).result()?;
-        Ok(())
+    /// Guards repo config changes with a lock file as well, so clients in
+    /// other processes sharing the helm config don't change it concurrently.
+    ///
+    /// Changes by clients of the same process are always serialized.
+    pub fn set_repo_lock_file<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.repo_lock_file = Some(path.into());
+        self
     }
 
-    /// Searches the repo for the named helm chart
-    #[instrument(skip(self))]
-    pub fn search_repo(&self, chart: &str, version: &str) -> Result<Vec<Chart>, HelmError> {
-        let mut command = Command::new("helm");
-        command
-            .args(["search", "repo", chart])
-            .args(["--version", version])
-            .args(["--output", "json"]);
+    /// Takes the lock guarding changes to the repo config
+    fn lock_repo_config(&self) -> Result<RepoConfigGuard, HelmError> {
+        lock_repo_config(
+            self.repo_lock_file.as_deref(),
+            repo_lock::DEFAULT_LOCK_TIMEOUT,
+        )
+    }
 
-        let output = command.result()?;
+    /// Records the chart versions resolved from version requirements in a
+    /// lockfile, e.g. `helm-lock.yaml`, and reuses them on later runs
+    pub fn set_lockfile<P: Into<PathBuf>>(&mut self, path: P) -> &mut Self {
+        self.lockfile = Some(path.into());
+        self
+    }
 
-        check_helm_stderr(output.stderr)?;
-        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    /// Resolves the version of a chart, e.g. `fluvio/fluvio-app`, to install
+    /// for a requirement.
+    ///
+    /// The version locked in the lockfile is reused while it satisfies the
+    /// requirement, otherwise the newest matching version is resolved and
+    /// locked.
+    #[instrument(skip(self))]
+    pub fn resolve_chart_version(
+        &self,
+        chart: &str,
+        requirement: &VersionReq,
+        develop: bool,
+    ) -> Result<String, HelmError> {
+        if let Some(path) = &self.lockfile {
+            let lockfile = Lockfile::load(path)?;
+            if let Some(version) = lockfile.locked_version(chart, requirement, develop) {
+                debug!(%version, "using locked chart version");
+                return Ok(version.to_string());
+            }
+        }
+        self.update_chart_lock(chart, requirement, develop)
     }
 
-    /// Get all the available versions
+    /// Resolves the newest version of a chart matching a requirement,
+    /// replacing the version locked in the lockfile
     #[instrument(skip(self))]
-    pub fn versions(&self, chart: &str) -> Result<Vec<Chart>, HelmError> {
-        let mut command = Command::new("helm");
-        command
-            .args(["search", "repo"])
-            .args(["--versions", chart])
-            .args(["--output", "json", "--devel"]);
-        let output = command.result()?;
+    pub fn update_chart_lock(
+        &self,
+        chart: &str,
+        requirement: &VersionReq,
+        develop: bool,
+    ) -> Result<String, HelmError> {
+        let versions: Vec<String> = self
+            .versions(chart)?
+            .into_iter()
+            .filter(|found| found.name == chart)
+            .map(|found| found.version)
+            .collect();
+        let version = newest_matching(&versions, requirement, develop).ok_or_else(|| {
+            HelmError::ChartNotFound {
+                chart: format!("{} {}", chart, requirement),
+                repos: vec![],
+            }
+        })?;
 
-        check_helm_stderr(output.stderr)?;
-        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+        if let Some(path) = &self.lockfile {
+            let mut lockfile = Lockfile::load(path)?;
+            lockfile.lock(chart, requirement, &version);
+            lockfile.save(path)?;
+        }
+        Ok(version)
     }
 
-    /// Checks that a given version of a given chart exists in the repo.
-    #[instrument(skip(self))]
-    pub fn chart_version_exists(&self, name: &str, version: &str) -> Result<bool, HelmError> {
+    /// Records the digest of the chart installed for a locked chart
+    pub(crate) fn record_chart_digest(&self, chart: &str, digest: &str) -> Result<(), HelmError> {
+        let path = match &self.lockfile {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let mut lockfile = Lockfile::load(path)?;
+        if let Some(locked) = lockfile.charts.get_mut(chart) {
+            locked.digest = Some(digest.to_string());
+            lockfile.save(path)?;
+        }
+        Ok(())
+    }
+
+    /// Writes a receipt of every install, upgrade and uninstall into `dir`,
+    /// for an audit trail independent of helm's release history
+    pub fn set_receipts_dir<P: Into<PathBuf>>(&mut self, dir: P) -> &mut Self {
+        self.receipts = Some(ReceiptStore::new(dir.into()));
+        self
+    }
+
+    /// Receipts of the operations on a release in a namespace, oldest first.
+    /// A `None` namespace returns the operations given no namespace.
+    ///
+    /// Empty unless a receipts directory is set.
+    pub fn receipts_for(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<Vec<Receipt>, HelmError> {
+        match &self.receipts {
+            Some(store) => store.receipts_for(release, namespace),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Writes a receipt if a receipts directory is set.
+    ///
+    /// The operation already happened, so failing to record it is logged
+    /// rather than returned.
+    fn write_receipt(&self, receipt: Receipt) {
+        if let Some(store) = &self.receipts {
+            if let Err(err) = store.write(&receipt) {
+                warn!(release = %receipt.release, %err, "unable to write receipt");
+            }
+        }
+    }
+
+    /// Records an install or upgrade
+    fn write_install_receipt(
+        &self,
+        operation: ReceiptOperation,
+        args: &InstallArg,
+        command: &Command,
+        result: &InstallResult,
+    ) {
+        let mut receipt = Receipt::new(
+            result.release_name(args),
+            args.namespace.as_deref().map(String::from),
+            operation,
+            explain::command_argv(command),
+        );
+        receipt.chart = Some(args.chart.to_string());
+        receipt.chart_version = args.version.as_deref().map(String::from);
+        receipt.chart_digest = result.chart_digest.clone();
+        receipt.oci_digest = result.oci_digest.clone();
+        self.write_receipt(receipt);
+    }
+
+    /// Runs a helm command with the client wide flags applied
+    fn run(&self, command: &mut Command) -> Result<Output, HelmError> {
+        self.run_with_stdin(command, None)
+    }
+
+    /// Runs a helm command like `run`, writing `stdin` to its input
+    fn run_with_stdin(
+        &self,
+        command: &mut Command,
+        stdin: Option<&[u8]>,
+    ) -> Result<Output, HelmError> {
+        self.apply_client_flags(command);
+        let output = self.exec_with_stdin(command, stdin)?;
+        if self.strict_stderr {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            if let Some(unexpected) = failure::unexpected_stderr(&stderr, &self.allowed_stderr) {
+                return Err(HelmError::UnexpectedStderr(unexpected));
+            }
+        }
+        Ok(output)
+    }
+
+    /// Runs a command through the transport of the client
+    fn exec(&self, command: &mut Command) -> Result<Output, HelmError> {
+        self.exec_with_stdin(command, None)
+    }
+
+    /// Runs a command through the transport of the client, writing `stdin`
+    /// to its input
+    fn exec_with_stdin(
+        &self,
+        command: &mut Command,
+        stdin: Option<&[u8]>,
+    ) -> Result<Output, HelmError> {
+        Ok(process::run_tracked(
+            command,
+            self.transport.as_deref(),
+            stdin,
+        )?)
+    }
+
+    /// Adds the client wide flags to a helm command
+    fn apply_client_flags(&self, command: &mut Command) {
+        // commands targeting a cluster of their own keep it
+        let targeted = command
+            .get_args()
+            .any(|arg| arg == "--kube-context" || arg == "--kubeconfig");
+        if let Some(context) = self.kube_context.as_ref().filter(|_| !targeted) {
+            command.args(["--kube-context", context]);
+        }
+    }
+
+    /// Registers credentials for a repository or registry host, e.g. `ghcr.io`.
+    ///
+    /// Operations against a repo on that host use them unless credentials
+    /// were given explicitly.
+    pub fn register_credentials<H: Into<String>>(
+        &mut self,
+        host: H,
+        credentials: RepoCredentials,
+    ) -> &mut Self {
+        self.credentials.insert(host.into(), credentials);
+        self
+    }
+
+    /// Looks up registered credentials for the host of the given url
+    fn credentials_for(&self, url: &RepoUrl) -> Option<&RepoCredentials> {
+        self.credentials.get(url.host())
+    }
+
+    /// Adds registered credentials for charts installed with `--repo` or
+    /// from an OCI registry, and `--plain-http` for charts in a local registry
+    fn apply_chart_flags(&self, chart: &ChartRef, command: &mut Command) {
+        match chart {
+            ChartRef::Repo { repo, .. } => {
+                if let Some(credentials) = self.credentials_for(repo) {
+                    credentials.apply(command);
+                }
+            }
+            ChartRef::Url(url) if url.is_oci() => {
+                if let Some(credentials) = self.credentials_for(url) {
+                    credentials.apply(command);
+                }
+                if self.local_registries && is_local_registry(url.host()) {
+                    command.arg("--plain-http");
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Waits until the Kubernetes API server is reachable.
+    ///
+    /// Probes the cluster with a cheap `helm list`, backing off exponentially
+    /// between attempts. Useful right after provisioning a local cluster,
+    /// when the API server may not accept connections yet. Returns
+    /// `HelmError::FailedToConnect` if it is still unreachable after `timeout`.
+    #[instrument(skip(self))]
+    pub fn wait_for_cluster(&self, timeout: Duration) -> Result<(), HelmError> {
+        const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+        const MAX_BACKOFF: Duration = Duration::from_secs(5);
+
+        let deadline = Instant::now() + timeout;
+        let mut backoff = INITIAL_BACKOFF;
+        loop {
+            match self.probe_cluster() {
+                Err(HelmError::FailedToConnect) => {}
+                result => return result,
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(HelmError::FailedToConnect);
+            }
+            let delay = backoff.min(deadline - now);
+            debug!(?delay, "Kubernetes cluster unreachable, retrying");
+            thread::sleep(delay);
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+        }
+    }
+
+    /// Runs a minimal query against the cluster
+    fn probe_cluster(&self) -> Result<(), HelmError> {
+        let result = self.run(Command::new("helm").args([
+            "list",
+            "--all-namespaces",
+            "--max",
+            "1",
+            "--output",
+            "json",
+        ]));
+        match result {
+            Ok(output) => check_helm_stderr(output.stderr),
+            Err(HelmError::CommandFailed {
+                kind: HelmFailureKind::ClusterUnreachable,
+                ..
+            }) => Err(HelmError::FailedToConnect),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Installs the given chart under the given name.
+    ///
+    #[instrument(
+        skip(self, args),
+        fields(
+            release = %args.name,
+            namespace = args.namespace.as_deref().unwrap_or_default(),
+            chart = %args.chart,
+            version = args.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn install(&self, args: &InstallArg) -> Result<InstallResult, HelmError> {
+        args.validate()?;
+        let args = self.resolve_install_arg(args)?;
+        let mut command = args.install();
+        self.apply_chart_flags(&args.chart, &mut command);
+        if args.dry_run {
+            let output = self.run(&mut command)?;
+            return InstallResult::new(&args, &output);
+        }
+        let output = self.run_mutation(&mut command)?;
+        let result = InstallResult::new(&args, &output)?;
+        self.write_install_receipt(ReceiptOperation::Install, &args, &command, &result);
+        Ok(result)
+    }
+
+    /// Upgrades the given chart
+    #[instrument(
+        skip(self, args),
+        fields(
+            release = %args.name,
+            namespace = args.namespace.as_deref().unwrap_or_default(),
+            chart = %args.chart,
+            version = args.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn upgrade(&self, args: &InstallArg) -> Result<InstallResult, HelmError> {
+        args.validate()?;
+        if args.generate_name {
+            return Err(HelmError::InvalidArg(
+                "upgrade needs a release name, generate_name only applies to install".to_string(),
+            ));
+        }
+        let args = self.resolve_install_arg(args)?;
+        let mut command = args.upgrade();
+        self.apply_chart_flags(&args.chart, &mut command);
+        if args.dry_run {
+            let output = self.run(&mut command)?;
+            return InstallResult::new(&args, &output);
+        }
+        let output = self.run_mutation(&mut command)?;
+        let result = InstallResult::new(&args, &output)?;
+        self.write_install_receipt(ReceiptOperation::Upgrade, &args, &command, &result);
+        Ok(result)
+    }
+
+    /// Uninstalls specified chart library
+    ///
+    /// With `ignore_not_found`, helm's "release: not found" failure is
+    /// reported as not found instead of failing. With `inspect`, the release
+    /// manifest and hooks are read first to report what was removed.
+    #[instrument(
+        skip(self, uninstall),
+        fields(
+            release = %uninstall.release,
+            namespace = uninstall.namespace.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn uninstall(&self, uninstall: UninstallArg) -> Result<UninstallResult, HelmError> {
+        let ignore_not_found = uninstall.ignore_not_found;
+        let release = uninstall.release.to_string();
+        let namespace = uninstall.namespace.as_deref().map(String::from);
+        let dry_run = uninstall.dry_run;
+        let no_hooks = uninstall.no_hooks;
+        let mut result = UninstallResult {
+            release: release.clone(),
+            found: true,
+            hooks_ran: false,
+            kept_history: uninstall.keep_history,
+            resources_deleted: 0,
+            resources_kept: vec![],
+        };
+
+        // the release is gone afterwards, so its content is read first
+        let inspected = if uninstall.inspect {
+            let mut get_all = get_command("all", &release, namespace.as_deref());
+            apply_kube_flags(
+                &mut get_all,
+                uninstall.kubeconfig.as_deref(),
+                uninstall.kube_context.as_deref(),
+            );
+            let dump = self.run(&mut get_all).and_then(|output| {
+                check_helm_stderr(output.stderr)?;
+                ReleaseDump::parse(&String::from_utf8(output.stdout)?)
+            });
+            match dump {
+                Ok(dump) => Some((ResourceInventory::parse(&dump.manifest)?, dump.hooks)),
+                Err(err) if err.failure_kind() == Some(HelmFailureKind::ReleaseNotFound) => {
+                    if ignore_not_found {
+                        warn!("Chart does not exists, {}", &release);
+                        result.found = false;
+                        return Ok(result);
+                    }
+                    return Err(err);
+                }
+                Err(err) => return Err(err),
+            }
+        } else {
+            None
+        };
+
+        let mut command: Command = uninstall.into();
+        match self.run_mutation(&mut command) {
+            Ok(output) => {
+                result.resources_kept =
+                    resources::parse_kept_resources(&String::from_utf8_lossy(&output.stdout));
+                if dry_run {
+                    // nothing was removed and no hook ran
+                    return Ok(result);
+                }
+                self.write_receipt(Receipt::new(
+                    release,
+                    namespace,
+                    ReceiptOperation::Uninstall,
+                    explain::command_argv(&command),
+                ));
+                if let Some((resources, hooks)) = inspected {
+                    result.hooks_ran = !no_hooks
+                        && hooks.iter().any(|hook| {
+                            hook.events
+                                .iter()
+                                .any(|event| event == "pre-delete" || event == "post-delete")
+                        });
+                    result.resources_deleted = resources
+                        .resources
+                        .len()
+                        .saturating_sub(result.resources_kept.len());
+                }
+                Ok(result)
+            }
+            Err(HelmError::CommandFailed {
+                kind: HelmFailureKind::ReleaseNotFound,
+                ..
+            }) if ignore_not_found => {
+                warn!("Chart does not exists, {}", &release);
+                result.found = false;
+                Ok(result)
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Rolls back a release to a previous revision, returning the revision
+    /// rolled back to as recorded in the release history
+    #[instrument(
+        skip(self, rollback),
+        fields(
+            release = %rollback.release,
+            namespace = rollback.namespace.as_deref().unwrap_or_default(),
+            revision = ?rollback.revision,
+        )
+    )]
+    pub fn rollback(&self, rollback: RollbackArg) -> Result<RollbackResult, HelmError> {
+        let mut history = HistoryArg::new(rollback.release.clone()).max(1);
+        history.namespace = rollback.namespace.clone();
+        let release = rollback.release.to_string();
+
+        let mut command: Command = rollback.into();
+        self.run_mutation(&mut command)?;
+        RollbackResult::from_history(&release, &self.history(history)?)
+            .ok_or(HelmError::RollbackNotFound(release))
+    }
+
+    /// Returns the revisions of a release, oldest first
+    #[instrument(
+        skip(self, history),
+        fields(
+            release = %history.release,
+            namespace = history.namespace.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn history(&self, history: HistoryArg) -> Result<Vec<HistoryEntry>, HelmError> {
+        let mut command: Command = history.into();
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Runs the tests of a release, collecting the logs of the test pods.
+    ///
+    /// Failing tests are reported in the result rather than as an error.
+    #[instrument(
+        skip(self, test),
+        fields(
+            release = %test.release,
+            namespace = test.namespace.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn test(&self, test: TestArg) -> Result<TestResult, HelmError> {
+        let mut command: Command = test.into();
+        let stdout = match self.run(&mut command) {
+            Ok(output) => output.stdout,
+            // helm prints the results and logs before failing
+            Err(err) => match err.output() {
+                Some(output) if output.stdout.starts_with(b"NAME:") => output.stdout.clone(),
+                _ => return Err(err),
+            },
+        };
+        Ok(TestResult::parse(&String::from_utf8(stdout)?))
+    }
+
+    /// Returns the status of a release
+    #[instrument(
+        skip(self, status),
+        fields(
+            release = %status.release,
+            namespace = status.namespace.as_deref().unwrap_or_default(),
+            revision = ?status.revision,
+        )
+    )]
+    pub fn status(&self, status: StatusArg) -> Result<ReleaseStatus, HelmError> {
+        let mut command: Command = status.into();
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Reports the hooks of the current revision of a release and how their
+    /// last run went, e.g. to find the pre-upgrade hook that failed an
+    /// upgrade
+    #[instrument(skip(self))]
+    pub fn hook_report(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<HookReport, HelmError> {
+        let mut status = StatusArg::new(release);
+        status.namespace = namespace.map(Cow::Borrowed);
+        let mut command: Command = status.into();
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        HookReport::parse(&output.stdout)
+    }
+
+    /// Returns the user supplied values of a release
+    #[instrument(skip(self))]
+    pub fn get_values(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<serde_json::Value, HelmError> {
+        self.get_values_as(release, namespace)
+    }
+
+    /// Returns the user supplied values of a release, deserialized into `T`
+    #[instrument(skip(self))]
+    pub fn get_values_as<T: DeserializeOwned>(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<T, HelmError> {
+        let mut command = get_command("values", release, namespace);
+        command.args(["--output", "json"]);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Returns the rendered NOTES.txt of a release, optionally of a given revision
+    #[instrument(skip(self))]
+    pub fn get_notes(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+        revision: Option<u32>,
+    ) -> Result<String, HelmError> {
+        let mut command = get_command("notes", release, namespace);
+        if let Some(revision) = revision {
+            command.arg("--revision").arg(revision.to_string());
+        }
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        let notes = String::from_utf8(output.stdout)?;
+        Ok(strip_notes_header(&notes).to_string())
+    }
+
+    /// Returns the hook resources of a release
+    #[instrument(skip(self))]
+    pub fn get_hooks(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<Vec<HookManifest>, HelmError> {
+        let mut command = get_command("hooks", release, namespace);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        hooks::parse_hooks(&String::from_utf8(output.stdout)?)
+    }
+
+    /// Returns chart, version and deployment details of a release.
+    ///
+    /// Cheaper than `status`, but requires helm 3.13 or later.
+    #[instrument(skip(self))]
+    pub fn get_metadata(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<ReleaseMetadata, HelmError> {
+        let mut command = get_command("metadata", release, namespace);
+        command.args(["--output", "json"]);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Collects diagnostics about a release into `<dest_dir>/<release>`,
+    /// returning the bundle directory.
+    ///
+    /// The bundle holds the helm version and environment, the release
+    /// status, history, values and manifest, and the recent helm commands.
+    /// Sensitive looking values and Secret data are masked. Sections which
+    /// can't be collected hold the error instead.
+    #[instrument(skip(self))]
+    pub fn support_bundle(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+        dest_dir: &Path,
+    ) -> Result<PathBuf, HelmError> {
+        let dir = dest_dir.join(release);
+        fs::create_dir_all(&dir).map_err(|source| HelmError::Io {
+            path: dir.clone(),
+            source,
+        })?;
+
+        let stdout = |mut command: Command| -> Result<String, HelmError> {
+            let output = self.run(&mut command)?;
+            Ok(String::from_utf8(output.stdout)?)
+        };
+        let json = |mut command: Command| -> Result<String, HelmError> {
+            command.args(["--output", "json"]);
+            let value: serde_json::Value = serde_json::from_str(&stdout(command)?)?;
+            Ok(serde_json::to_string_pretty(&value)?)
+        };
+
+        let mut version = Command::new("helm");
+        version.arg("version");
+        bundle::write_section(&dir, "version.txt", stdout(version))?;
+        let mut env = Command::new("helm");
+        env.arg("env");
+        bundle::write_section(&dir, "env.txt", stdout(env))?;
+
+        let mut status = StatusArg::new(release);
+        status.namespace = namespace.map(Cow::Borrowed);
+        bundle::write_section(&dir, "status.json", json(status.into()))?;
+        let mut history = HistoryArg::new(release);
+        history.namespace = namespace.map(Cow::Borrowed);
+        bundle::write_section(&dir, "history.json", json(history.into()))?;
+
+        let values = self.get_values(release, namespace).and_then(|mut values| {
+            bundle::mask_values(&mut values);
+            Ok(serde_json::to_string_pretty(&values)?)
+        });
+        bundle::write_section(&dir, "values.json", values)?;
+        let manifest = stdout(get_command("manifest", release, namespace))
+            .and_then(|manifest| bundle::mask_manifest(&manifest));
+        bundle::write_section(&dir, "manifest.yaml", manifest)?;
+
+        bundle::write_section(
+            &dir,
+            "commands.log",
+            Ok(bundle::format_commands(&recent_commands())),
+        )?;
+        Ok(dir)
+    }
+
+    /// Returns the objects rendered in the manifest of a release
+    #[instrument(skip(self))]
+    pub fn resource_inventory(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<ResourceInventory, HelmError> {
+        let output = self.run(&mut get_command("manifest", release, namespace))?;
+        check_helm_stderr(output.stderr)?;
+        ResourceInventory::parse(&String::from_utf8(output.stdout)?)
+    }
+
+    /// Finds cluster objects labeled for a release but missing from its
+    /// current manifest, e.g. left behind by failed upgrades or hooks.
+    ///
+    /// Objects are looked up with kubectl by the
+    /// `app.kubernetes.io/instance` label, among the kinds of the manifest
+    /// and the kinds of the `all` category.
+    #[instrument(skip(self))]
+    pub fn orphaned_resources(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<Vec<ResourceRef>, HelmError> {
+        let inventory = self.resource_inventory(release, namespace)?;
+        let mut kinds = inventory.kinds();
+        kinds.push("all");
+
+        let mut command = Command::new("kubectl");
+        command
+            .args(["get", &kinds.join(",")])
+            .arg("--selector")
+            .arg(format!("{}={}", resources::INSTANCE_LABEL, release))
+            .args(["--output", "json"]);
+        match namespace {
+            Some(namespace) => command.args(["--namespace", namespace]),
+            None => command.arg("--all-namespaces"),
+        };
+        if let Some(context) = &self.kube_context {
+            command.args(["--context", context]);
+        }
+        let output = self.exec(&mut command)?;
+        inventory.orphans(&output.stdout, namespace.unwrap_or("default"))
+    }
+
+    /// Returns values, manifest, hooks and notes of a release in one call
+    #[instrument(skip(self))]
+    pub fn get_all(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<ReleaseDump, HelmError> {
+        let mut command = get_command("all", release, namespace);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        ReleaseDump::parse(&String::from_utf8(output.stdout)?)
+    }
+
+    /// Lists the releases in all namespaces with a single helm call
+    #[instrument(skip(self))]
+    pub fn refresh_release_inventory(&self) -> Result<ReleaseInventory, HelmError> {
+        let inventory = self.list_releases(None, None)?;
+        *self.inventory.lock().unwrap() = Some(inventory.clone());
+        Ok(inventory)
+    }
+
+    /// Lists the releases in all namespaces of the cluster selected by the
+    /// kubeconfig and context, the cluster of the client if both are `None`
+    fn list_releases(
+        &self,
+        kubeconfig: Option<&Path>,
+        kube_context: Option<&str>,
+    ) -> Result<ReleaseInventory, HelmError> {
+        let mut command = Command::new("helm");
+        command
+            .args(["list", "--all-namespaces", "--all", "--max", "0"])
+            .args(["--output", "json"]);
+        apply_kube_flags(&mut command, kubeconfig, kube_context);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        let releases = serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)?;
+        Ok(ReleaseInventory::new(releases))
+    }
+
+    /// Lists the releases of the cluster an install targets, from the
+    /// cached listing unless the install overrides the cluster of the client
+    fn target_inventory(&self, args: &InstallArg) -> Result<ReleaseInventory, HelmError> {
+        match (args.kubeconfig.as_deref(), args.kube_context.as_deref()) {
+            (None, None) => self.release_inventory(),
+            (kubeconfig, kube_context) => self.list_releases(kubeconfig, kube_context),
+        }
+    }
+
+    /// Returns the cached release listing, refreshing it if it is older
+    /// than the configured max age
+    pub fn release_inventory(&self) -> Result<ReleaseInventory, HelmError> {
+        if let Some(inventory) = self.inventory.lock().unwrap().as_ref() {
+            if inventory.is_fresh(self.inventory_max_age) {
+                return Ok(inventory.clone());
+            }
+        }
+        self.refresh_release_inventory()
+    }
+
+    /// Checks whether a release exists in any namespace
+    pub fn release_exists(&self, name: &str) -> Result<bool, HelmError> {
+        Ok(self.release_inventory()?.release_exists(name))
+    }
+
+    /// Finds releases in any namespace whose name approximately matches
+    /// `pattern`, best matches first, for interactive lookups
+    #[instrument(skip(self))]
+    pub fn find_release_fuzzy(&self, pattern: &str) -> Result<Vec<ReleaseMatch>, HelmError> {
+        Ok(self.release_inventory()?.find_release_fuzzy(pattern))
+    }
+
+    /// Returns releases installed from the named chart, in any version
+    pub fn find_releases_using_chart(&self, chart: &str) -> Result<Vec<InstalledChart>, HelmError> {
+        Ok(self
+            .release_inventory()?
+            .find_releases_using_chart(chart)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+
+    /// Reports releases that have not been updated for longer than `older_than`,
+    /// along with the newest chart version available for each of them.
+    ///
+    /// Releases whose update time can't be parsed are skipped.
+    #[instrument(skip(self))]
+    pub fn stale_releases(&self, older_than: Duration) -> Result<Vec<StaleRelease>, HelmError> {
+        let now = SystemTime::now();
+        let mut latest_versions: HashMap<String, Option<String>> = HashMap::new();
+        let mut stale = vec![];
+
+        for release in self.release_inventory()?.releases() {
+            let age = match release
+                .updated_at()
+                .and_then(|updated| now.duration_since(updated).ok())
+            {
+                Some(age) if age > older_than => age,
+                _ => continue,
+            };
+
+            let latest_version =
+                self.cached_latest_chart_version(&mut latest_versions, release.chart_name())?;
+            stale.push(StaleRelease {
+                release: release.clone(),
+                age,
+                latest_version,
+            });
+        }
+        Ok(stale)
+    }
+
+    /// Reports releases for which a newer chart version is available in the
+    /// configured repos
+    #[instrument(skip(self))]
+    pub fn outdated_releases(&self) -> Result<Vec<OutdatedRelease>, HelmError> {
+        let mut latest_versions: HashMap<String, Option<String>> = HashMap::new();
+        let mut outdated = vec![];
+
+        for release in self.release_inventory()?.releases() {
+            let latest = match self
+                .cached_latest_chart_version(&mut latest_versions, release.chart_name())?
+            {
+                Some(latest) => latest,
+                None => continue,
+            };
+            let current = release.chart_version();
+            if compare_versions(current, &latest).is_ge() {
+                continue;
+            }
+            if let Some(delta) = VersionDelta::between(current, &latest) {
+                outdated.push(OutdatedRelease {
+                    release: release.clone(),
+                    current: current.to_string(),
+                    latest,
+                    delta,
+                });
+            }
+        }
+        Ok(outdated)
+    }
+
+    /// Looks up the newest version of a chart, once per chart
+    fn cached_latest_chart_version(
+        &self,
+        cache: &mut HashMap<String, Option<String>>,
+        chart: &str,
+    ) -> Result<Option<String>, HelmError> {
+        if let Some(latest) = cache.get(chart) {
+            return Ok(latest.clone());
+        }
+        let latest = self.latest_chart_version(chart)?;
+        cache.insert(chart.to_string(), latest.clone());
+        Ok(latest)
+    }
+
+    /// Finds the newest version of a chart across the configured repos
+    fn latest_chart_version(&self, chart: &str) -> Result<Option<String>, HelmError> {
+        Ok(self
+            .search_repo_unaliased(chart, None)?
+            .into_iter()
+            .filter(|result| result.chart() == chart)
+            .map(|result| result.version)
+            .max_by(|a, b| compare_versions(a, b)))
+    }
+
+    /// Reports the effective configuration of an install without changing
+    /// the cluster: the resolved chart version, the repos consulted, the
+    /// merged values, the helm command line and the helm/Kubernetes versions.
+    ///
+    /// The chart is resolved and rendered with a dry run install.
+    #[instrument(
+        skip(self, args),
+        fields(
+            release = %args.name,
+            namespace = args.namespace.as_deref().unwrap_or_default(),
+            chart = %args.chart,
+            version = args.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn explain(&self, args: &InstallArg) -> Result<InstallExplanation, HelmError> {
+        args.validate()?;
+        let repos = match &args.chart {
+            ChartRef::Name(name) => match self.chart_aliases.get(name) {
+                Some(repos) => repos.clone(),
+                None => match name.split_once('/') {
+                    Some((repo, _)) if !Path::new(name).exists() => vec![repo.to_string()],
+                    _ => vec![],
+                },
+            },
+            chart => chart
+                .repo_url()
+                .map(|url| vec![url.to_string()])
+                .unwrap_or_default(),
+        };
+
+        let resolved = self.resolve_install_arg(args)?;
+        let mut command = resolved.install();
+        self.apply_chart_flags(&resolved.chart, &mut command);
+        // the client flags are part of the explained command, run keeps them
+        self.apply_client_flags(&mut command);
+        let argv = explain::command_argv(&command);
+
+        command.args(["--dry-run", "--output", "json"]);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        let release: DryRunRelease =
+            serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)?;
+        let mut values = release.chart.values;
+        explain::merge_values(&mut values, release.config);
+
+        Ok(InstallExplanation {
+            chart_version: release.chart.metadata.version,
+            repos,
+            values,
+            argv,
+            helm_version: self.get_helm_version()?,
+            kube_version: self
+                .kube_server_version(args.kubeconfig.as_deref(), args.kube_context.as_deref()),
+        })
+    }
+
+    /// Adds the kubeconfig and context flags to a kubectl command, using
+    /// the context of the client unless a kubeconfig or context is given
+    fn apply_kubectl_flags(
+        &self,
+        command: &mut Command,
+        kubeconfig: Option<&Path>,
+        kube_context: Option<&str>,
+    ) {
+        if let Some(kubeconfig) = kubeconfig {
+            command.arg("--kubeconfig").arg(kubeconfig);
+        }
+        let kube_context = match kubeconfig {
+            Some(_) => kube_context,
+            None => kube_context.or(self.kube_context.as_deref()),
+        };
+        if let Some(context) = kube_context {
+            command.args(["--context", context]);
+        }
+    }
+
+    /// Version of the Kubernetes server of the cluster selected by the
+    /// kubeconfig and context, as reported by kubectl
+    fn kube_server_version(
+        &self,
+        kubeconfig: Option<&Path>,
+        kube_context: Option<&str>,
+    ) -> Option<String> {
+        let mut command = Command::new("kubectl");
+        command.args(["version", "--output", "json"]);
+        self.apply_kubectl_flags(&mut command, kubeconfig, kube_context);
+        let output = match self.exec(&mut command) {
+            Ok(output) => output,
+            Err(err) => {
+                warn!(%err, "unable to get kubernetes server version");
+                return None;
+            }
+        };
+        let version: serde_json::Value = serde_json::from_slice(&output.stdout).ok()?;
+        version["serverVersion"]["gitVersion"]
+            .as_str()
+            .map(ToString::to_string)
+    }
+
+    /// Computes the changes needed to converge the repos and releases to
+    /// the release set, without applying them
+    #[instrument(skip(self))]
+    pub fn plan(&self, set: &ReleaseSet) -> Result<Plan, HelmError> {
+        let repos: Vec<String> = self
+            .repo_list()?
+            .into_iter()
+            .map(|repo| repo.name)
+            .collect();
+        let inventory = self.refresh_release_inventory()?;
+        Ok(Plan::new(set, &repos, &inventory))
+    }
+
+    /// Applies the actions of a plan in order, stopping at the first
+    /// failure and returning its error.
+    ///
+    /// Repo indexes are updated after adding repos, before any release is
+    /// installed or upgraded.
+    #[instrument(skip(self))]
+    pub fn apply(&self, plan: Plan) -> Result<ApplyReport, HelmError> {
+        self.apply_with(plan, FailurePolicy::FailFast).into_result()
+    }
+
+    /// Applies the actions of a plan in order, handling failures as the
+    /// policy says.
+    ///
+    /// Failures are reported per action rather than returned. Rolling back
+    /// uninstalls installed releases, rolls upgraded releases back to their
+    /// previous revision and removes added repos; uninstalls can't be undone.
+    #[instrument(skip(self))]
+    pub fn apply_with(&self, plan: Plan, policy: FailurePolicy) -> ApplyReport {
+        let mut report = ApplyReport::default();
+        let mut repos_added = vec![];
+        let mut failed = false;
+
+        for action in plan.actions {
+            if failed && policy != FailurePolicy::Continue {
+                report.actions.push(ActionReport {
+                    action,
+                    outcome: ActionOutcome::Skipped,
+                });
+                continue;
+            }
+            let outcome = match self.apply_action(&action, &mut repos_added) {
+                Ok(()) => ActionOutcome::Converged,
+                Err(err) => {
+                    warn!(%err, ?action, "planned action failed");
+                    failed = true;
+                    ActionOutcome::Failed(err)
+                }
+            };
+            report.actions.push(ActionReport { action, outcome });
+        }
+
+        if failed && policy == FailurePolicy::RollbackAll {
+            for entry in report.actions.iter_mut().rev() {
+                if !matches!(entry.outcome, ActionOutcome::Converged) {
+                    continue;
+                }
+                match self.undo_action(&entry.action) {
+                    Some(Ok(())) => entry.outcome = ActionOutcome::RolledBack,
+                    Some(Err(err)) => entry.outcome = ActionOutcome::RollbackFailed(err),
+                    None => {}
+                }
+            }
+        }
+        report
+    }
+
+    fn apply_action(
+        &self,
+        action: &PlannedAction,
+        repos_added: &mut Vec<String>,
+    ) -> Result<(), HelmError> {
+        match action {
+            PlannedAction::AddRepo(repo) => {
+                self.repo_add(&repo.name, &repo.url)?;
+                repos_added.push(repo.name.clone());
+            }
+            PlannedAction::Install(release) | PlannedAction::Upgrade { release, .. } => {
+                if !repos_added.is_empty() {
+                    self.repo_update_only(repos_added)?;
+                    repos_added.clear();
+                }
+                self.upgrade(&release.clone().into_install_arg())?;
+            }
+            PlannedAction::Uninstall { name, namespace } => {
+                let mut uninstall = UninstallArg::new(name).ignore_not_found();
+                uninstall.namespace = namespace.as_deref().map(Cow::Borrowed);
+                self.uninstall(uninstall)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Undoes an applied action, `None` if it can't be undone
+    fn undo_action(&self, action: &PlannedAction) -> Option<Result<(), HelmError>> {
+        let undone = match action {
+            PlannedAction::AddRepo(repo) => self.repo_remove(&repo.name),
+            PlannedAction::Install(release) => {
+                let mut uninstall = UninstallArg::new(&release.name).ignore_not_found();
+                uninstall.namespace = release.namespace.as_deref().map(Cow::Borrowed);
+                self.uninstall(uninstall).map(|_| ())
+            }
+            PlannedAction::Upgrade { release, .. } => {
+                let mut rollback = RollbackArg::new(&release.name);
+                rollback.namespace = release.namespace.as_deref().map(Cow::Borrowed);
+                self.rollback(rollback).map(|_| ())
+            }
+            PlannedAction::Uninstall { .. } => return None,
+        };
+        Some(undone)
+    }
+
+    /// Installs the releases of a batch concurrently, within its limits and
+    /// in dependency order.
+    ///
+    /// A failed release doesn't stop the others, only the releases
+    /// depending on it are skipped. Fails without installing anything if
+    /// the batch is invalid.
+    #[instrument(skip(self, batch), fields(releases = batch.entries.len()))]
+    pub fn install_many(&self, batch: &BatchArg) -> Result<BatchReport, HelmError> {
+        self.run_batch(batch, Self::install)
+    }
+
+    /// Upgrades, or installs, the releases of a batch concurrently, like
+    /// [`HelmClient::install_many`]
+    #[instrument(skip(self, batch), fields(releases = batch.entries.len()))]
+    pub fn upgrade_all(&self, batch: &BatchArg) -> Result<BatchReport, HelmError> {
+        self.run_batch(batch, Self::upgrade)
+    }
+
+    fn run_batch<F>(&self, batch: &BatchArg, operation: F) -> Result<BatchReport, HelmError>
+    where
+        F: Fn(&Self, &InstallArg) -> Result<InstallResult, HelmError> + Sync,
+    {
+        batch.validate()?;
+        let mut outcomes: Vec<Option<BatchOutcome>> = batch.entries.iter().map(|_| None).collect();
+        let mut scheduler = Scheduler::new(batch);
+        let (sender, receiver) = mpsc::channel();
+
+        thread::scope(|scope| loop {
+            for index in scheduler.skip_blocked() {
+                debug!(release = %batch.entries[index].arg.name, "skipping release");
+                outcomes[index] = Some(BatchOutcome::Skipped);
+            }
+            for index in scheduler.next() {
+                let sender = sender.clone();
+                let operation = &operation;
+                scope.spawn(move || {
+                    let arg = &batch.entries[index].arg;
+                    // a panicking release fails like any other, the batch
+                    // waits for an outcome of every running release
+                    let result = panic::catch_unwind(AssertUnwindSafe(|| operation(self, arg)))
+                        .unwrap_or_else(|payload| {
+                            Err(HelmError::BatchPanicked {
+                                release: arg.name.to_string(),
+                                message: panic_message(payload.as_ref()),
+                            })
+                        });
+                    // the receiver outlives the scope
+                    let _ = sender.send((index, result));
+                });
+            }
+            if scheduler.is_done() {
+                break;
+            }
+            // every worker reports, even when panicking, and a sender is
+            // kept here, so this doesn't fail while releases are running
+            let (index, result) = match receiver.recv() {
+                Ok(received) => received,
+                Err(_) => break,
+            };
+            scheduler.finish(index, result.is_ok());
+            outcomes[index] = Some(match result {
+                Ok(result) => BatchOutcome::Installed(result),
+                Err(err) => BatchOutcome::Failed(err),
+            });
+        });
+
+        Ok(BatchReport {
+            releases: batch
+                .entries
+                .iter()
+                .zip(outcomes)
+                .map(|(entry, outcome)| {
+                    (
+                        entry.arg.name.to_string(),
+                        outcome.unwrap_or(BatchOutcome::Skipped),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    /// Registers the requirements of a chart on the target cluster, checked
+    /// by `preflight` and `ensure_release`.
+    ///
+    /// `chart` is the chart name without repo, e.g. `fluvio-app`.
+    pub fn register_constraints<S: Into<String>>(
+        &mut self,
+        chart: S,
+        constraints: ChartConstraints,
+    ) -> &mut Self {
+        self.constraints.insert(chart.into(), constraints);
+        self
+    }
+
+    /// Checks the constraints registered for the chart of an install
+    /// against the target cluster.
+    ///
+    /// The report is empty if no constraints are registered for the chart.
+    #[instrument(
+        skip(self, args),
+        fields(
+            release = %args.name,
+            namespace = args.namespace.as_deref().unwrap_or_default(),
+            chart = %args.chart,
+            version = args.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn preflight(&self, args: &InstallArg) -> Result<PreflightReport, HelmError> {
+        self.check_constraints(args, None)
+    }
+
+    /// Checks the constraints of an install like `preflight`, reusing the
+    /// listing of the target cluster if the caller already has it
+    fn check_constraints(
+        &self,
+        args: &InstallArg,
+        inventory: Option<ReleaseInventory>,
+    ) -> Result<PreflightReport, HelmError> {
+        let chart = args.chart.to_string();
+        let name = compat::chart_name(match &args.chart {
+            ChartRef::Repo { name, .. } => name,
+            _ => &chart,
+        });
+        let constraints = match self.constraints.get(name) {
+            Some(constraints) => constraints,
+            None => {
+                return Ok(PreflightReport {
+                    chart: name.to_string(),
+                    violations: vec![],
+                })
+            }
+        };
+
+        // the install may target another cluster than the client
+        let kubeconfig = args.kubeconfig.as_deref();
+        let kube_context = args.kube_context.as_deref();
+        let kube_version = match constraints.kube_versions {
+            Some(_) => self.kube_server_version(kubeconfig, kube_context),
+            None => None,
+        };
+        let crds = if constraints.required_crds.is_empty() {
+            vec![]
+        } else {
+            self.installed_crds(kubeconfig, kube_context)?
+        };
+        let inventory = match inventory {
+            Some(inventory) => inventory,
+            None => self.target_inventory(args)?,
+        };
+        Ok(constraints.evaluate(name, kube_version.as_deref(), &crds, &inventory))
+    }
+
+    /// Names of the CRDs installed in the cluster selected by the
+    /// kubeconfig and context, as reported by kubectl
+    fn installed_crds(
+        &self,
+        kubeconfig: Option<&Path>,
+        kube_context: Option<&str>,
+    ) -> Result<Vec<String>, HelmError> {
+        let mut command = Command::new("kubectl");
+        command.args(["get", "customresourcedefinitions", "--output", "name"]);
+        self.apply_kubectl_flags(&mut command, kubeconfig, kube_context);
+        let output = self.exec(&mut command)?;
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .filter_map(|line| line.rsplit('/').next())
+            .filter(|name| !name.is_empty())
+            .map(ToString::to_string)
+            .collect())
+    }
+
+    /// Installs the release unless it already exists in the cluster and
+    /// namespace the install targets, or in any namespace if it sets none.
+    ///
+    /// Returns `None` if the release was already installed. Fails with
+    /// `HelmError::PreflightFailed` if the constraints registered for the
+    /// chart are not met.
+    #[instrument(
+        skip(self, args),
+        fields(
+            release = %args.name,
+            namespace = args.namespace.as_deref().unwrap_or_default(),
+            chart = %args.chart,
+            version = args.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn ensure_release(&self, args: &InstallArg) -> Result<Option<InstallResult>, HelmError> {
+        let inventory = self.target_inventory(args)?;
+        let exists = match args.namespace.as_deref() {
+            Some(namespace) => inventory.find_release_in(namespace, &args.name).is_some(),
+            None => inventory.release_exists(&args.name),
+        };
+        if exists {
+            debug!(release = %args.name, "release already installed");
+            return Ok(None);
+        }
+        let report = self.check_constraints(args, Some(inventory))?;
+        if !report.is_ok() {
+            return Err(HelmError::PreflightFailed(report));
+        }
+        self.install(args).map(Some)
+    }
+
+    /// Runs a command changing releases, dropping the cached listing
+    fn run_mutation(&self, command: &mut Command) -> Result<Output, HelmError> {
+        self.inventory.lock().unwrap().take();
+        self.run(command)
+    }
+
+    /// Renders a chart locally, returning the manifests as YAML
+    #[instrument(
+        skip(self, template),
+        fields(
+            release = %template.name,
+            namespace = template.namespace.as_deref().unwrap_or_default(),
+            chart = %template.chart,
+            version = template.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn template(&self, template: &TemplateArg) -> Result<String, HelmError> {
+        let mut command = template.template();
+        self.apply_chart_flags(&template.chart, &mut command);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Renders a chart locally like [`HelmClient::template`], also
+    /// reporting deprecated chart formats and layouts
+    #[instrument(
+        skip(self, template),
+        fields(
+            release = %template.name,
+            namespace = template.namespace.as_deref().unwrap_or_default(),
+            chart = %template.chart,
+            version = template.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn template_with_warnings(
+        &self,
+        template: &TemplateArg,
+    ) -> Result<RenderedChart, HelmError> {
+        let manifest = self.template(template)?;
+        let warnings = self.chart_warnings(&template.chart, template.version.as_deref())?;
+        Ok(RenderedChart { manifest, warnings })
+    }
+
+    /// Renders a chart into the `output_dir` of the template argument,
+    /// returning the paths of the generated files
+    #[instrument(
+        skip(self, template),
+        fields(
+            release = %template.name,
+            namespace = template.namespace.as_deref().unwrap_or_default(),
+            chart = %template.chart,
+            version = template.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn template_to_dir(&self, template: &TemplateArg) -> Result<Vec<PathBuf>, HelmError> {
+        if template.output_dir.is_none() {
+            return Err(HelmError::InvalidArg(
+                "template output directory is not set".to_string(),
+            ));
+        }
+        let rendered = self.template(template)?;
+        Ok(template::parse_written_paths(&rendered))
+    }
+
+    /// Renders a chart locally, returning one YAML value per manifest, so
+    /// kinds, names and images can be inspected before installing.
+    ///
+    /// Documents left empty by the templates are skipped.
+    #[instrument(
+        skip(self, template),
+        fields(
+            release = %template.name,
+            namespace = template.namespace.as_deref().unwrap_or_default(),
+            chart = %template.chart,
+            version = template.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn template_documents(
+        &self,
+        template: &TemplateArg,
+    ) -> Result<Vec<serde_yaml::Value>, HelmError> {
+        let rendered = self.template(template)?;
+        parse_yaml_documents(&rendered)
+    }
+
+    /// Reports deprecated formats and layouts of a chart, e.g. charts still
+    /// using `apiVersion: v1`.
+    ///
+    /// The files of chart directories on disk are checked too, otherwise
+    /// only the Chart.yaml reported by `helm show chart` is.
+    #[instrument(skip(self))]
+    pub fn chart_warnings(
+        &self,
+        chart: &ChartRef,
+        version: Option<&str>,
+    ) -> Result<Vec<ChartWarning>, HelmError> {
+        let chart_yaml = self.show("chart", chart, version)?;
+        let mut warnings = metadata_warnings(&serde_yaml::from_str(&chart_yaml)?);
+        if let ChartRef::Name(path) = chart {
+            if Path::new(path).is_dir() {
+                warnings.extend(layout_warnings(Path::new(path)));
+            }
+        }
+        for warning in &warnings {
+            warn!(chart = %chart, %warning, "deprecated chart");
+        }
+        Ok(warnings)
+    }
+
+    /// Lints a chart directory, packaged chart or OCI chart, returning the
+    /// reported messages.
+    ///
+    /// A chart failing lint is not an error, callers decide which severity
+    /// to fail on.
+    #[instrument(
+        skip(self, lint),
+        fields(
+            chart = %lint.chart,
+            version = lint.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn lint(&self, lint: &LintArg) -> Result<Vec<LintMessage>, HelmError> {
+        // charts on disk are linted in place, others are pulled first
+        let _pulled: TempDir;
+        let chart_path = match &lint.chart {
+            ChartRef::Name(path) => PathBuf::from(path),
+            chart => {
+                let dir = TempDir::new()?;
+                let mut pull = PullArg::new(chart.clone()).destination(dir.path());
+                pull.version = lint.version.clone();
+                let archive = self.pull(&pull)?;
+                _pulled = dir;
+                archive
+            }
+        };
+        let stdout = match self.run(&mut lint.command(&chart_path)) {
+            Ok(output) => output.stdout,
+            // helm exits with an error on failed charts, the messages are still on stdout
+            Err(err) => match err.output() {
+                Some(output) if !output.stdout.is_empty() => output.stdout.clone(),
+                _ => return Err(err),
+            },
+        };
+        Ok(lint::parse_lint_messages(&String::from_utf8(stdout)?))
+    }
+
+    /// Reports the keys of user supplied values which the chart doesn't
+    /// define in its default values or values schema, e.g. a misspelled
+    /// `resouces.limits`.
+    ///
+    /// Charts on disk are read in place, others are pulled first.
+    #[instrument(skip(self, chart, values), fields(chart = %chart))]
+    pub fn lint_values(
+        &self,
+        chart: &ChartRef,
+        version: Option<&str>,
+        values: &serde_yaml::Value,
+    ) -> Result<Vec<UnknownValueKey>, HelmError> {
+        let _pulled: TempDir;
+        let chart_dir = match chart {
+            ChartRef::Name(path) if Path::new(path).is_dir() => PathBuf::from(path),
+            chart => {
+                let dir = TempDir::new()?;
+                let mut pull = PullArg::new(chart.clone()).destination(dir.path()).untar();
+                pull.version = version.map(Cow::Borrowed);
+                let pulled = self.pull(&pull)?;
+                _pulled = dir;
+                pulled
+            }
+        };
+
+        let read = |name: &str| {
+            let path = chart_dir.join(name);
+            match fs::read_to_string(&path) {
+                Ok(contents) => Ok(Some(contents)),
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(source) => Err(HelmError::Io { path, source }),
+            }
+        };
+        let defaults = match read("values.yaml")? {
+            Some(defaults) => serde_yaml::from_str(&defaults)?,
+            None => serde_yaml::Value::Null,
+        };
+        let schema = read("values.schema.json")?
+            .map(|schema| serde_json::from_str(&schema))
+            .transpose()?;
+        let subcharts = match read("Chart.yaml")? {
+            Some(chart_yaml) => values_lint::subchart_keys(&serde_yaml::from_str(&chart_yaml)?),
+            None => vec![],
+        };
+
+        Ok(values_lint::unknown_keys(
+            values,
+            &defaults,
+            schema.as_ref(),
+            &subcharts,
+        ))
+    }
+
+    /// Updates `charts/` of a chart directory from its Chart.yaml,
+    /// resolving dependency versions and rewriting Chart.lock
+    #[instrument(skip(self))]
+    pub fn dependency_update(&self, chart_path: &Path) -> Result<(), HelmError> {
+        self.dependency("update", chart_path)?;
+        Ok(())
+    }
+
+    /// Rebuilds `charts/` of a chart directory from its Chart.lock
+    #[instrument(skip(self))]
+    pub fn dependency_build(&self, chart_path: &Path) -> Result<(), HelmError> {
+        self.dependency("build", chart_path)?;
+        Ok(())
+    }
+
+    /// Lists the dependencies of a chart directory and their state
+    #[instrument(skip(self))]
+    pub fn dependency_list(&self, chart_path: &Path) -> Result<Vec<DependencyEntry>, HelmError> {
+        let output = self.dependency("list", chart_path)?;
+        Ok(dependency::parse_dependency_list(&String::from_utf8(
+            output.stdout,
+        )?))
+    }
+
+    /// Runs `helm dependency <what>` for a chart directory
+    fn dependency(&self, what: &str, chart_path: &Path) -> Result<Output, HelmError> {
+        let mut command = Command::new("helm");
+        command.args(["dependency", what]).arg(chart_path);
+        self.run(&mut command)
+    }
+
+    /// Packages a chart directory, returning the path of the archive and
+    /// of its provenance file when signed
+    #[instrument(skip(self))]
+    pub fn package(&self, package: PackageArg) -> Result<PackageResult, HelmError> {
+        let chart_path = package.chart_path.clone();
+        let signed = package.sign;
+        let mut command: Command = package.into();
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        let archive =
+            package::parse_package_path(&String::from_utf8(output.stdout)?).ok_or_else(|| {
+                HelmError::InvalidArg(format!(
+                    "no chart archive packaged from {}",
+                    chart_path.display()
+                ))
+            })?;
+        Ok(PackageResult::new(archive, signed))
+    }
+
+    /// Scaffolds a new chart, returning its directory
+    #[instrument(skip(self))]
+    pub fn create(&self, create: CreateArg) -> Result<PathBuf, HelmError> {
+        create.validate()?;
+        let chart_path = create.chart_path();
+        let mut command: Command = create.into();
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        Ok(chart_path)
+    }
+
+    /// Pushes a packaged chart to an OCI registry, returning the digest
+    /// reported by the registry
+    #[instrument(skip(self))]
+    pub fn push(&self, mut push: PushArg) -> Result<String, HelmError> {
+        push.validate()?;
+        if self.local_registries && is_local_registry(push.remote.host()) {
+            push.plain_http = true;
+        }
+        let mut command: Command = push.into();
+        let output = self.run(&mut command)?;
+        parse_oci_digest(&String::from_utf8_lossy(&output.stderr))
+            .or_else(|| parse_oci_digest(&String::from_utf8_lossy(&output.stdout)))
+            .ok_or_else(|| HelmError::InvalidArg("registry reported no digest".to_string()))
+    }
+
+    /// Downloads a chart, returning the path of the archive, or of the
+    /// chart directory when unpacked
+    #[instrument(
+        skip(self, pull),
+        fields(
+            chart = %pull.chart,
+            version = pull.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn pull(&self, pull: &PullArg) -> Result<PathBuf, HelmError> {
+        let target = pull.target_dir();
+        fs::create_dir_all(&target).map_err(|source| HelmError::Io {
+            path: target.clone(),
+            source,
+        })?;
+
+        // helm doesn't report what it wrote, so the chart is pulled into an
+        // empty staging directory and moved from there
+        let staging = TempDir::new_in(&target)?;
+        let mut command = pull.command(staging.path());
+        if pull.credentials.is_none() {
+            self.apply_chart_flags(&pull.chart, &mut command);
+        }
+        self.run(&mut command)?;
+
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| HelmError::Io { path, source }
+        };
+        // the chart, and its provenance file when requested
+        let mut chart = None;
+        for entry in fs::read_dir(staging.path()).map_err(io_error(staging.path()))? {
+            let pulled = entry.map_err(io_error(staging.path()))?.path();
+            let destination = target.join(pulled.file_name().unwrap_or_default());
+            fs::rename(&pulled, &destination).map_err(io_error(&destination))?;
+            if destination.is_dir() || destination.extension().is_some_and(|ext| ext == "tgz") {
+                chart = Some(destination);
+            }
+        }
+        chart.ok_or_else(|| HelmError::InvalidArg(format!("no chart pulled for {}", pull.chart)))
+    }
+
+    /// Verifies a chart archive against its provenance file and keyring
+    #[instrument(skip(self))]
+    pub fn verify(&self, verify: VerifyArg) -> Result<(), HelmError> {
+        let mut command: Command = verify.into();
+        self.run(&mut command)?;
+        Ok(())
+    }
+
+    /// Gathers the README, default values and CRDs of a chart
+    #[instrument(skip(self))]
+    pub fn chart_docs(
+        &self,
+        chart: &ChartRef,
+        version: Option<&str>,
+    ) -> Result<ChartDocs, HelmError> {
+        Ok(ChartDocs {
+            readme: self.show_readme(chart, version)?,
+            values: self.show_values(chart, version)?,
+            crds: self.show("crds", chart, version)?,
+        })
+    }
+
+    /// Reports the CRDs added or changed between the chart version a
+    /// release is running and `target_version`.
+    ///
+    /// Helm never upgrades CRDs, so these must be applied explicitly
+    /// before upgrading the release.
+    #[instrument(skip(self))]
+    pub fn crd_changes(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+        target_version: &str,
+    ) -> Result<Vec<CrdChange>, HelmError> {
+        let metadata = self.get_metadata(release, namespace)?;
+        let chart = self
+            .search_repo(&metadata.chart, &metadata.version)?
+            .into_iter()
+            .find(|found| found.chart() == metadata.chart)
+            .map(|found| ChartRef::Name(found.name))
+            .ok_or_else(|| HelmError::ChartNotFound {
+                chart: metadata.chart.clone(),
+                repos: vec![],
+            })?;
+
+        let current = self.show("crds", &chart, Some(&metadata.version))?;
+        let target = self.show("crds", &chart, Some(target_version))?;
+        crd::crd_changes(&current, &target)
+    }
+
+    /// Returns the README of a chart, empty if the chart has none
+    #[instrument(skip(self))]
+    pub fn show_readme(
+        &self,
+        chart: &ChartRef,
+        version: Option<&str>,
+    ) -> Result<String, HelmError> {
+        self.show("readme", chart, version)
+    }
+
+    /// Returns the CRD manifests shipped in the chart's `crds/` directory,
+    /// one YAML document per entry
+    #[instrument(skip(self))]
+    pub fn show_crds(
+        &self,
+        chart: &ChartRef,
+        version: Option<&str>,
+    ) -> Result<Vec<String>, HelmError> {
+        let crds = self.show("crds", chart, version)?;
+        Ok(crd::split_manifests(&crds))
+    }
+
+    /// Returns the default values.yaml of a chart
+    #[instrument(skip(self))]
+    pub fn show_values(
+        &self,
+        chart: &ChartRef,
+        version: Option<&str>,
+    ) -> Result<String, HelmError> {
+        self.show("values", chart, version)
+    }
+
+    /// Returns the default values of a chart deserialized into `T`, e.g.
+    /// `serde_yaml::Value`
+    #[instrument(skip(self))]
+    pub fn show_values_as<T: DeserializeOwned>(
+        &self,
+        chart: &ChartRef,
+        version: Option<&str>,
+    ) -> Result<T, HelmError> {
+        let values = self.show_values(chart, version)?;
+        Ok(serde_yaml::from_str(&values)?)
+    }
+
+    /// Downloads a chart like [`HelmClient::pull`], also reporting
+    /// deprecated chart formats and layouts
+    #[instrument(
+        skip(self, pull),
+        fields(
+            chart = %pull.chart,
+            version = pull.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn pull_with_warnings(&self, pull: &PullArg) -> Result<PulledChart, HelmError> {
+        let path = self.pull(pull)?;
+        let local = ChartRef::Name(path.to_string_lossy().into_owned());
+        let warnings = self.chart_warnings(&local, None)?;
+        Ok(PulledChart { path, warnings })
+    }
+
+    /// Runs `helm show <what>` for a chart, returning stdout
+    fn show(
+        &self,
+        what: &str,
+        chart: &ChartRef,
+        version: Option<&str>,
+    ) -> Result<String, HelmError> {
+        let mut command = Command::new("helm");
+        command.args(["show", what]);
+        chart.apply(&mut command);
+        self.apply_chart_flags(chart, &mut command);
+        if let Some(version) = version {
+            command.args(["--version", version]);
+        }
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Renders the NOTES.txt of two installs and returns a line diff from
+    /// `from` to `to`, e.g. for a proposed chart version or values change.
+    ///
+    /// Both are rendered with a dry run using their chart, version and
+    /// values. Helm 3.13 or later renders them client side. Older versions
+    /// need access to the cluster.
+    ///
+    /// Lines are prefixed with `-` when removed, `+` when added and a space
+    /// when unchanged.
+    #[instrument(
+        skip(self, from, to),
+        fields(
+            release = %to.name,
+            chart = %to.chart,
+            from_version = from.version.as_deref().unwrap_or_default(),
+            to_version = to.version.as_deref().unwrap_or_default(),
+        )
+    )]
+    pub fn notes_diff(&self, from: &InstallArg, to: &InstallArg) -> Result<String, HelmError> {
+        let client_side = Version::parse(&self.get_helm_version()?)
+            .is_ok_and(|version| (version.major, version.minor) >= (3, 13));
+        let from = self.render_notes(from, client_side)?;
+        let to = self.render_notes(to, client_side)?;
+        Ok(diff::line_diff(&from, &to))
+    }
+
+    /// Renders the NOTES.txt of an install with a dry run
+    fn render_notes(&self, args: &InstallArg, client_side: bool) -> Result<String, HelmError> {
+        args.validate()?;
+        let mut args = self.resolve_install_arg(args)?.into_owned();
+        // the dry run flags are added below, generated names are not rendered
+        args.dry_run = false;
+        args.generate_name = false;
+        let mut command = args.install();
+        self.apply_chart_flags(&args.chart, &mut command);
+        command
+            .arg(if client_side {
+                "--dry-run=client"
+            } else {
+                "--dry-run"
+            })
+            .args(["--output", "json"]);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        Ok(RenderedRelease::parse(&output.stdout)?.notes)
+    }
+
+    /// Checks whether the named helm plugin is installed
+    #[instrument(skip(self))]
+    pub fn plugin_installed(&self, name: &str) -> Result<bool, HelmError> {
+        let output = self.run(Command::new("helm").args(["plugin", "list"]))?;
+        let plugins = migrate::parse_plugin_names(&String::from_utf8(output.stdout)?);
+        Ok(plugins.iter().any(|plugin| plugin == name))
+    }
+
+    /// Converts a helm 2 release to helm 3 using the `2to3` plugin
+    #[instrument(skip(self))]
+    pub fn convert_release(&self, convert: ConvertReleaseArg) -> Result<(), HelmError> {
+        self.require_plugin(MIGRATE_PLUGIN)?;
+        let mut command: Command = convert.into();
+        self.run_mutation(&mut command)?;
+        Ok(())
+    }
+
+    /// Removes helm 2 configuration, release data and tiller using the
+    /// `2to3` plugin, once all releases are converted
+    #[instrument(skip(self))]
+    pub fn cleanup_v2(&self, cleanup: CleanupV2Arg) -> Result<(), HelmError> {
+        self.require_plugin(MIGRATE_PLUGIN)?;
+        let mut command: Command = cleanup.into();
+        self.run(&mut command)?;
+        Ok(())
+    }
+
+    /// Runs an installed helm plugin with the given arguments, for plugins
+    /// not wrapped by the client.
+    ///
+    /// Helm passes the arguments after the plugin name to it unparsed, so the
+    /// client flags come before the name, where helm reads them and hands
+    /// them to the plugin, e.g. the kube context as `HELM_KUBECONTEXT`.
+    #[instrument(skip(self, args))]
+    pub fn run_plugin<I, S>(&self, name: &str, args: I) -> Result<Output, HelmError>
+    where
+        I: IntoIterator<Item = S> + fmt::Debug,
+        S: AsRef<OsStr>,
+    {
+        self.require_plugin(name)?;
+        let mut command = Command::new("helm");
+        self.apply_client_flags(&mut command);
+        command.arg(name).args(args);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr.clone())?;
+        Ok(output)
+    }
+
+    fn require_plugin(&self, name: &str) -> Result<(), HelmError> {
+        if self.plugin_installed(name)? {
+            Ok(())
+        } else {
+            Err(HelmError::PluginNotInstalled(name.to_string()))
+        }
+    }
+
+    /// Adds a new helm repo with the given chart name and chart location
+    #[instrument(skip(self))]
+    pub fn repo_add(&self, chart: &str, location: &str) -> Result<(), HelmError> {
+        self.repo_add_with(RepoAddArg::new(chart, location.parse()?))
+    }
+
+    /// Adds a new helm repo described by the given argument
+    #[instrument(skip(self, arg), fields(name = %arg.name, url = %arg.url))]
+    pub fn repo_add_with(&self, mut arg: RepoAddArg) -> Result<(), HelmError> {
+        if arg.credentials.is_none() {
+            arg.credentials = self.credentials_for(&arg.url).cloned();
+        }
+        let name = arg.name.to_string();
+        let password = arg.credentials.as_ref().map(|c| c.password.clone());
+        let mut command: Command = arg.into();
+        let _lock = self.lock_repo_config()?;
+        match self.run_with_stdin(&mut command, password.as_deref().map(str::as_bytes)) {
+            Ok(_) => Ok(()),
+            Err(HelmError::CommandFailed { source, .. }) => {
+                let reason = match &source.source {
+                    CommandErrorKind::ExitError(_, output) => {
+                        RepoAddFailure::from_stderr(&String::from_utf8_lossy(&output.stderr))
+                    }
+                    _ => RepoAddFailure::Other,
+                };
+                Err(HelmError::RepoAddFailed {
+                    name,
+                    reason,
+                    source,
+                })
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Lists the configured repositories
+    #[instrument(skip(self))]
+    pub fn repo_list(&self) -> Result<Vec<HelmRepo>, HelmError> {
+        let mut command = Command::new("helm");
+        command.args(["repo", "list", "--output", "json"]);
+        let output = match self.run(&mut command) {
+            Ok(output) => output,
+            // helm fails instead of listing nothing
+            Err(err)
+                if err.output().is_some_and(|output| {
+                    String::from_utf8_lossy(&output.stderr).contains("no repositories")
+                }) =>
+            {
+                return Ok(vec![])
+            }
+            Err(err) => return Err(err),
+        };
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Removes a configured helm repo
+    #[instrument(skip(self))]
+    pub fn repo_remove(&self, name: &str) -> Result<(), HelmError> {
+        let _lock = self.lock_repo_config()?;
+        self.run(Command::new("helm").args(["repo", "remove", name]))?;
+        Ok(())
+    }
+
+    /// Updates the local helm repository
+    #[instrument(skip(self))]
+    pub fn repo_update(&self) -> Result<(), HelmError> {
+        self.repo_update_only::<&str>(&[])
+    }
+
+    /// Updates the index of the named repos only, or of every repo when
+    /// `names` is empty. Naming repos requires helm 3.7 or later.
+    #[instrument(skip(self, names))]
+    pub fn repo_update_only<S: AsRef<str>>(&self, names: &[S]) -> Result<(), HelmError> {
+        let _lock = self.lock_repo_config()?;
+        let mut command = Command::new("helm");
+        command.args(["repo", "update"]);
+        command.args(names.iter().map(AsRef::as_ref));
+        self.run(&mut command)?;
+        Ok(())
+    }
+
+    /// Logs in to an OCI registry, using the credentials registered for
+    /// the host unless given explicitly
+    #[instrument(skip(self, login), fields(host = %login.host))]
+    pub fn registry_login(&self, mut login: RegistryLoginArg) -> Result<(), HelmError> {
+        if login.credentials.is_none() {
+            login.credentials = self.credentials.get(&*login.host).cloned();
+        }
+        let password = login.credentials.as_ref().map(|c| c.password.clone());
+        let mut command: Command = login.into();
+        self.run_with_stdin(&mut command, password.as_deref().map(str::as_bytes))?;
+        Ok(())
+    }
+
+    /// Logs out of an OCI registry, removing the stored credentials
+    #[instrument(skip(self))]
+    pub fn registry_logout(&self, host: &str) -> Result<(), HelmError> {
+        let mut command: Command = RegistryLogoutArg::new(host).into();
+        self.run(&mut command)?;
+        Ok(())
+    }
+
+    /// Searches the repo for the named helm chart
+    ///
+    /// If `chart` is a registered alias, its repos are searched in order
+    /// and the matches from the first repo having the chart are returned.
+    #[instrument(skip(self))]
+    pub fn search_repo(&self, chart: &str, version: &str) -> Result<Vec<SearchResult>, HelmError> {
+        self.search_repo_with(&SearchRepoArg::new(chart).version(version))
+    }
+
+    /// Searches the configured repos as described by `search`
+    ///
+    /// Unless the term is a regular expression, a registered alias is
+    /// resolved as with [`HelmClient::search_repo`].
+    #[instrument(skip(self))]
+    pub fn search_repo_with(&self, search: &SearchRepoArg) -> Result<Vec<SearchResult>, HelmError> {
+        let repos = match self.chart_aliases.get(&*search.term) {
+            Some(repos) if !search.regexp => repos,
+            _ => return self.search_repo_unaliased_with(search),
+        };
+        for repo in repos {
+            let candidate = format!("{}/{}", repo, search.term);
+            let mut unlimited = search.with_term(&candidate);
+            unlimited.limit = None;
+            let charts: Vec<SearchResult> = self
+                .search_repo_unaliased_with(&unlimited)?
+                .into_iter()
+                .filter(|found| found.name == candidate)
+                .take(search.limit.unwrap_or(usize::MAX))
+                .collect();
+            if !charts.is_empty() {
+                return Ok(charts);
+            }
+        }
+        Ok(vec![])
+    }
+
+    fn search_repo_unaliased(
+        &self,
+        chart: &str,
+        version: Option<&str>,
+    ) -> Result<Vec<SearchResult>, HelmError> {
+        let mut search = SearchRepoArg::new(chart);
+        search.version = version.map(Cow::Borrowed);
+        self.search_repo_unaliased_with(&search)
+    }
+
+    fn search_repo_unaliased_with(
+        &self,
+        search: &SearchRepoArg,
+    ) -> Result<Vec<SearchResult>, HelmError> {
+        let output = self.run(&mut search.command())?;
+
+        check_helm_stderr(output.stderr)?;
+        let mut results: Vec<SearchResult> =
+            serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)?;
+        if let Some(limit) = search.limit {
+            results.truncate(limit);
+        }
+        Ok(results)
+    }
+
+    /// Searches every configured repo for charts matching `term`.
+    ///
+    /// Returns one result per chart and repo, with the highest version
+    /// available in that repo, sorted by chart name then repo.
+    #[instrument(skip(self))]
+    pub fn search_all(&self, term: &str) -> Result<Vec<SearchResult>, HelmError> {
+        let mut command = Command::new("helm");
+        command
+            .args(["search", "repo", term])
+            .args(["--versions", "--output", "json"]);
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        let results: Vec<SearchResult> =
+            serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)?;
+        Ok(merge_search_results(results))
+    }
+
+    /// Searches Artifact Hub for charts matching `term`, or the hub at
+    /// `endpoint` when given
+    #[instrument(skip(self))]
+    pub fn search_hub(
+        &self,
+        term: &str,
+        endpoint: Option<&str>,
+    ) -> Result<Vec<HubResult>, HelmError> {
+        let mut command = Command::new("helm");
+        command
+            .args(["search", "hub", term])
+            .args(["--output", "json"]);
+        if let Some(endpoint) = endpoint {
+            command.args(["--endpoint", endpoint]);
+        }
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Lists the chart versions published in an OCI repository, newest
+    /// first, e.g. for `oci://ghcr.io/infinyon/charts/fluvio-app`.
+    ///
+    /// `helm search repo` doesn't support OCI registries. With the
+    /// `oci-native` feature the tags are listed with the registry API, using
+    /// registered credentials for the registry host. Without it helm can
+    /// only report the latest version, with `helm show chart`.
+    #[instrument(skip(self), fields(chart = %reference))]
+    pub fn registry_tags(&self, reference: &RepoUrl) -> Result<Vec<String>, HelmError> {
+        let (host, repository) = oci::split_reference(reference)?;
+
+        #[cfg(feature = "oci-native")]
+        let tags = oci::list_tags(
+            host,
+            repository,
+            self.credentials_for(reference),
+            self.local_registries && is_local_registry(host),
+        )?;
+
+        #[cfg(not(feature = "oci-native"))]
+        let tags = {
+            let chart = ChartRef::Url(RepoUrl::new(format!("oci://{}/{}", host, repository))?);
+            let metadata: serde_yaml::Value =
+                serde_yaml::from_str(&self.show("chart", &chart, None)?)?;
+            metadata["version"]
+                .as_str()
+                .map(|version| vec![version.to_string()])
+                .unwrap_or_default()
+        };
+
+        Ok(oci::chart_versions(tags))
+    }
+
+    /// Get all the available versions
+    #[instrument(skip(self))]
+    pub fn versions(&self, chart: &str) -> Result<Vec<SearchResult>, HelmError> {
+        let mut command = Command::new("helm");
+        command
+            .args(["search", "repo"])
+            .args(["--versions", chart])
+            .args(["--output", "json", "--devel"]);
+        let output = self.run(&mut command)?;
+
+        check_helm_stderr(output.stderr)?;
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Checks that a given version of a given chart exists in the repo.
+    #[instrument(skip(self))]
+    pub fn chart_version_exists(&self, name: &str, version: &str) -> Result<bool, HelmError> {
         let versions = self.search_repo(name, version)?;
         let count = versions
             .iter()
@@ -348,24 +3104,121 @@ impl HelmClient {
             }
         }
 
-        let output = command.result()?;
+        let output = self.run(&mut command)?;
+        check_helm_stderr(output.stderr)?;
+        serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
+    }
+
+    /// Returns the releases whose labels match a selector, e.g.
+    /// `owner=fluvio-ci`, in all namespaces unless one is given
+    #[instrument(skip(self))]
+    pub fn find_releases_by_selector(
+        &self,
+        selector: &str,
+        namespace: Option<&str>,
+    ) -> Result<Vec<InstalledChart>, HelmError> {
+        let mut command = Command::new("helm");
+        command
+            .args(["list", "--selector", selector])
+            .args(["--output", "json"]);
+
+        match namespace {
+            Some(ns) => {
+                command.args(["--namespace", ns]);
+            }
+            None => {
+                command.arg("--all-namespaces");
+            }
+        }
+
+        let output = self.run(&mut command)?;
         check_helm_stderr(output.stderr)?;
         serde_json::from_slice(&output.stdout).map_err(HelmError::Serde)
     }
 
+    /// Returns the settings helm runs with
+    #[instrument(skip(self))]
+    pub fn env(&self) -> Result<HelmEnv, HelmError> {
+        let output = self.run(Command::new("helm").arg("env"))?;
+        check_helm_stderr(output.stderr)?;
+        Ok(HelmEnv::parse(&String::from_utf8(output.stdout)?))
+    }
+
     /// get helm package version
     #[instrument(skip(self))]
     pub fn get_helm_version(&self) -> Result<String, HelmError> {
-        let helm_version = Command::new("helm")
-            .arg("version")
-            .arg("--short")
-            .output()
-            .map_err(HelmError::HelmNotInstalled)?;
+        // helm may only exist where the transport runs it
+        let mut command = Command::new("helm");
+        command.args(["version", "--short"]);
+        let helm_version = process::run_tracked(&mut command, self.transport.as_deref(), None)
+            .map_err(HelmError::from_helm_check)?;
         let version_text = String::from_utf8(helm_version.stdout).map_err(HelmError::Utf8Error)?;
         Ok(sanitize_helm_version_string(&version_text))
     }
 }
 
+/// The message a panic was raised with, if any
+#[cfg(feature = "client")]
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+/// Keeps the highest version of each chart in each repo
+fn merge_search_results(results: Vec<SearchResult>) -> Vec<SearchResult> {
+    let mut latest: HashMap<String, SearchResult> = HashMap::new();
+    for result in results {
+        match latest.get(&result.name) {
+            Some(existing) if compare_versions(&existing.version, &result.version).is_ge() => {}
+            _ => {
+                latest.insert(result.name.clone(), result);
+            }
+        }
+    }
+
+    let mut merged: Vec<SearchResult> = latest.into_values().collect();
+    merged.sort_by(|a, b| (a.chart(), a.repo()).cmp(&(b.chart(), b.repo())));
+    merged
+}
+
+/// Builds a `helm get <what>` command for a release
+fn get_command(what: &str, release: &str, namespace: Option<&str>) -> Command {
+    let mut command = Command::new("helm");
+    command.args(["get", what, release]);
+    if let Some(namespace) = namespace {
+        command.args(["--namespace", namespace]);
+    }
+    command
+}
+
+/// Adds `--kubeconfig` and `--kube-context` flags of an arg targeting a
+/// cluster other than the one of the client
+pub(crate) fn apply_kube_flags(
+    command: &mut Command,
+    kubeconfig: Option<&Path>,
+    kube_context: Option<&str>,
+) {
+    if let Some(kubeconfig) = kubeconfig {
+        command.arg("--kubeconfig").arg(kubeconfig);
+    }
+    if let Some(context) = kube_context {
+        command.args(["--kube-context", context]);
+    }
+}
+
+/// Removes the `NOTES:` header helm prints before release notes
+fn strip_notes_header(notes: &str) -> &str {
+    notes
+        .strip_prefix("NOTES:")
+        .map(|notes| notes.trim_start_matches(['\r', '\n']))
+        .unwrap_or(notes)
+}
+
 /// Sanitize the version string returned by helm
 ///
 /// Returns a sanitized version text parseable by the semver crate
@@ -380,7 +3233,7 @@ fn sanitize_helm_version_string(version_text: &str) -> String {
 fn check_helm_stderr(stderr: Vec<u8>) -> Result<(), HelmError> {
     if !stderr.is_empty() {
         let stderr = String::from_utf8(stderr)?;
-        if stderr.contains("Kubernetes cluster unreachable") {
+        if HelmFailureKind::from_stderr(&stderr) == HelmFailureKind::ClusterUnreachable {
             return Err(HelmError::FailedToConnect);
         }
     }
@@ -406,11 +3259,63 @@ impl Chart {
     }
 }
 
-/// A representation of an installed chart.
+/// An entry returned by searching chart repositories.
 #[derive(Debug, Deserialize)]
+pub struct SearchResult {
+    /// The chart name, prefixed with the repo name, e.g. `fluvio/fluvio-app`
+    name: String,
+    /// The chart version
+    version: String,
+    /// The version of the app packaged by the chart
+    #[serde(default)]
+    app_version: String,
+    /// The chart description
+    #[serde(default)]
+    description: String,
+}
+
+impl SearchResult {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn version(&self) -> &str {
+        &self.version
+    }
+    pub fn app_version(&self) -> &str {
+        &self.app_version
+    }
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+    /// The repo this chart was found in
+    pub fn repo(&self) -> Option<&str> {
+        self.name.split_once('/').map(|(repo, _)| repo)
+    }
+    /// The chart name without the repo prefix
+    pub fn chart(&self) -> &str {
+        self.name
+            .split_once('/')
+            .map(|(_, chart)| chart)
+            .unwrap_or(&self.name)
+    }
+}
+
+impl From<SearchResult> for Chart {
+    fn from(result: SearchResult) -> Self {
+        Self {
+            name: result.name,
+            version: result.version,
+        }
+    }
+}
+
+/// A representation of an installed chart.
+#[derive(Debug, Clone, Deserialize)]
 pub struct InstalledChart {
     /// The chart name
     pub name: String,
+    /// The namespace the release is installed in
+    pub namespace: String,
     /// The version of the app this chart installed
     pub app_version: String,
     /// The chart revision
@@ -423,10 +3328,374 @@ pub struct InstalledChart {
     pub chart: String,
 }
 
+impl InstalledChart {
+    /// The name of the installed chart, without its version
+    pub fn chart_name(&self) -> &str {
+        split_chart_id(&self.chart).0
+    }
+
+    /// The version of the installed chart
+    pub fn chart_version(&self) -> &str {
+        split_chart_id(&self.chart).1
+    }
+
+    /// When the release was last updated, if the timestamp could be parsed
+    pub fn updated_at(&self) -> Option<SystemTime> {
+        parse_helm_timestamp(&self.updated)
+    }
+}
+
+/// Splits a chart id such as `fluvio-app-0.9.2-rc1` into name and version.
+///
+/// The version starts at the first `-` followed by a digit.
+fn split_chart_id(chart: &str) -> (&str, &str) {
+    chart
+        .match_indices('-')
+        .map(|(index, _)| index)
+        .find(|&index| {
+            chart[index + 1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_digit())
+        })
+        .map(|index| (&chart[..index], &chart[index + 1..]))
+        .unwrap_or((chart, ""))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Answers helm commands with the output of the first response whose
+    /// prefix matches their arguments, recording the commands
+    #[cfg(all(unix, feature = "client"))]
+    #[derive(Debug)]
+    struct ScriptedTransport {
+        responses: Vec<(&'static str, &'static str)>,
+        commands: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    impl Transport for ScriptedTransport {
+        fn wrap(&self, command: &Command) -> Command {
+            let args: Vec<String> = command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            let args = args.join(" ");
+            let stdout = self
+                .responses
+                .iter()
+                .find(|(prefix, _)| args.starts_with(prefix))
+                .map(|(_, stdout)| *stdout)
+                .unwrap_or_default();
+            self.commands.lock().unwrap().push(args);
+            let mut printf = Command::new("printf");
+            printf.args(["%s", stdout]);
+            printf
+        }
+    }
+
+    /// A client running helm through a [`ScriptedTransport`], and the
+    /// commands it ran after being built
+    #[cfg(all(unix, feature = "client"))]
+    fn scripted_client(
+        responses: &[(&'static str, &'static str)],
+    ) -> (HelmClient, Arc<Mutex<Vec<String>>>) {
+        let commands = Arc::new(Mutex::new(vec![]));
+        let mut responses = responses.to_vec();
+        responses.push(("version --short", "v3.15.4+gfa9efb0\n"));
+        let transport = ScriptedTransport {
+            responses,
+            commands: commands.clone(),
+        };
+        let client = HelmClientBuilder::default()
+            .transport(transport)
+            .build()
+            .unwrap();
+        commands.lock().unwrap().clear();
+        (client, commands)
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_helm_version_through_transport() {
+        let (client, commands) = scripted_client(&[]);
+        assert_eq!(client.get_helm_version().unwrap(), "3.15.4+gfa9efb0");
+        assert_eq!(*commands.lock().unwrap(), ["version --short"]);
+    }
+
+    /// Runs commands with a helm executable that doesn't exist
+    #[cfg(all(unix, feature = "client"))]
+    #[derive(Debug)]
+    struct MissingHelmTransport;
+
+    #[cfg(all(unix, feature = "client"))]
+    impl Transport for MissingHelmTransport {
+        fn wrap(&self, command: &Command) -> Command {
+            let mut missing = Command::new("/nonexistent/helm");
+            missing.args(command.get_args());
+            missing
+        }
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_helm_not_installed() {
+        let err = HelmClientBuilder::default()
+            .transport(MissingHelmTransport)
+            .build()
+            .unwrap_err();
+        assert!(matches!(err, HelmError::HelmNotInstalled(_)));
+
+        let (mut client, _) = scripted_client(&[]);
+        client.transport = Some(Arc::new(MissingHelmTransport));
+        let err = client.get_helm_version().unwrap_err();
+        assert!(matches!(err, HelmError::HelmNotInstalled(_)));
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_run_plugin_kube_context() {
+        let (mut client, commands) = scripted_client(&[(
+            "plugin list",
+            "NAME\tVERSION\tDESCRIPTION\ndiff\t3.9.5\tPreview\n",
+        )]);
+        client.set_kube_context("staging");
+        client
+            .run_plugin("diff", ["upgrade", "fluvio", "fluvio/fluvio-app"])
+            .unwrap();
+        assert_eq!(
+            commands.lock().unwrap().last().unwrap(),
+            "--kube-context staging diff upgrade fluvio fluvio/fluvio-app"
+        );
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_repo_add_password_stdin() {
+        let (mut client, commands) = scripted_client(&[]);
+        client.register_credentials(
+            "charts.example.com",
+            RepoCredentials::new("fluvio", "secret"),
+        );
+        client
+            .repo_add("private", "https://charts.example.com/fluvio")
+            .unwrap();
+        assert_eq!(
+            commands.lock().unwrap().last().unwrap(),
+            "repo add private https://charts.example.com/fluvio --username fluvio --password-stdin"
+        );
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_oci_registered_credentials() {
+        let (mut client, commands) = scripted_client(&[]);
+        client.register_credentials("ghcr.io", RepoCredentials::new("fluvio", "secret"));
+        let dir = TempDir::new().unwrap();
+        let pull = PullArg::new(ChartRef::url("oci://ghcr.io/infinyon/fluvio-app").unwrap())
+            .destination(dir.path());
+        let _ = client.pull(&pull);
+        let pulled = commands.lock().unwrap()[0].clone();
+        assert!(pulled.starts_with("pull oci://ghcr.io/infinyon/fluvio-app "));
+        assert!(pulled.ends_with(" --username fluvio --password secret"));
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_ssh_transport_masks_passwords() {
+        const SECRET: &str = "ssh-transport-secret";
+        let (mut client, _) = scripted_client(&[]);
+        client.transport = Some(Arc::new(
+            SshTransport::new("ops@bastion.invalid")
+                .option("-o")
+                .option("BatchMode=yes"),
+        ));
+        client.register_credentials("charts.example.com", RepoCredentials::new("fluvio", SECRET));
+
+        assert!(client
+            .repo_add("private", "https://charts.example.com/fluvio")
+            .is_err());
+        let dir = TempDir::new().unwrap();
+        let pull = PullArg::new("fluvio-app")
+            .repo("https://charts.example.com/fluvio".parse().unwrap())
+            .destination(dir.path());
+        let err = client.pull(&pull).unwrap_err();
+        assert!(!err.to_string().contains(SECRET));
+
+        let recent = process::recent_commands();
+        assert!(recent
+            .iter()
+            .any(|record| record.argv.iter().any(|arg| arg == "pull")));
+        assert!(recent
+            .iter()
+            .all(|record| record.argv.iter().all(|arg| !arg.contains(SECRET))));
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_ensure_release_targets_arg_cluster() {
+        const LIST: &str = r#"[{"name":"fluvio","namespace":"fluvio","revision":"1","updated":"","status":"deployed","chart":"fluvio-app-0.9.2","app_version":"0.9.2"}]"#;
+        let (mut client, commands) = scripted_client(&[("list", LIST)]);
+        client.set_kube_context("prod");
+
+        let args = InstallArg::new("fluvio", "fluvio/fluvio-app")
+            .kube_context("staging")
+            .namespace("fluvio");
+        assert!(client.ensure_release(&args).unwrap().is_none());
+        assert_eq!(
+            *commands.lock().unwrap(),
+            ["list --all-namespaces --all --max 0 --output json --kube-context staging"]
+        );
+
+        commands.lock().unwrap().clear();
+        let args = args.namespace("other");
+        client.ensure_release(&args).unwrap();
+        assert_eq!(
+            *commands.lock().unwrap(),
+            [
+                "list --all-namespaces --all --max 0 --output json --kube-context staging",
+                "install fluvio fluvio/fluvio-app --namespace other --kube-context staging"
+            ]
+        );
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_rollback_result() {
+        const HISTORY: &str = r#"[{"revision":5,"updated":"2021-03-17T08:42:54.546347741Z","status":"deployed","chart":"fluvio-app-0.9.2","app_version":"0.9.2","description":"Rollback to 3"}]"#;
+        let (client, commands) = scripted_client(&[("history fluvio", HISTORY)]);
+        let result = client
+            .rollback(RollbackArg::new("fluvio").namespace("fluvio"))
+            .unwrap();
+        assert_eq!(
+            result,
+            RollbackResult {
+                release: "fluvio".to_string(),
+                rolled_back_to: 3,
+                revision: 5,
+            }
+        );
+        assert_eq!(
+            *commands.lock().unwrap(),
+            [
+                "rollback fluvio --namespace fluvio",
+                "history fluvio --output json --namespace fluvio --max 1"
+            ]
+        );
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_notes_diff_with_values() {
+        const ONE_REPLICA: &str = r#"{"name":"fluvio","info":{"status":"pending-install","notes":"Fluvio is installed\nReplicas: 1"}}"#;
+        const THREE_REPLICAS: &str = r#"{"name":"fluvio","info":{"status":"pending-install","notes":"Fluvio is installed\nReplicas: 3"}}"#;
+        let (client, commands) = scripted_client(&[
+            (
+                "install fluvio fluvio/fluvio-app --set replicas=1",
+                ONE_REPLICA,
+            ),
+            (
+                "install fluvio fluvio/fluvio-app --set replicas=3",
+                THREE_REPLICAS,
+            ),
+        ]);
+        let from = InstallArg::new("fluvio", "fluvio/fluvio-app").opt("replicas", "1");
+        let to = InstallArg::new("fluvio", "fluvio/fluvio-app").opt("replicas", "3");
+        assert_eq!(
+            client.notes_diff(&from, &to).unwrap(),
+            " Fluvio is installed\n-Replicas: 1\n+Replicas: 3\n"
+        );
+        assert_eq!(
+            *commands.lock().unwrap(),
+            [
+                "version --short",
+                "install fluvio fluvio/fluvio-app --set replicas=1 --dry-run=client --output json",
+                "install fluvio fluvio/fluvio-app --set replicas=3 --dry-run=client --output json",
+            ]
+        );
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_explain_runs_with_client_flags() {
+        const DRY_RUN: &str = r#"{"chart":{"metadata":{"version":"0.9.2"},"values":{"replicas":1}},"config":{"replicas":3}}"#;
+        let (mut client, commands) = scripted_client(&[("install fluvio", DRY_RUN)]);
+        client.set_kube_context("staging");
+        let explanation = client
+            .explain(&InstallArg::new("fluvio", "fluvio/fluvio-app").opt("replicas", "3"))
+            .unwrap();
+        assert_eq!(
+            explanation.argv,
+            [
+                "helm",
+                "install",
+                "fluvio",
+                "fluvio/fluvio-app",
+                "--set",
+                "replicas=3",
+                "--kube-context",
+                "staging"
+            ]
+        );
+        assert_eq!(explanation.values["replicas"], 3);
+        assert_eq!(
+            commands.lock().unwrap()[0],
+            "install fluvio fluvio/fluvio-app --set replicas=3 --kube-context staging --dry-run --output json"
+        );
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_batch_survives_panicking_release() {
+        let (client, _) = scripted_client(&[]);
+        let batch = BatchArg::new()
+            .release(InstallArg::new("fluvio-sys", "fluvio/fluvio-sys"))
+            .release_after(
+                InstallArg::new("fluvio", "fluvio/fluvio-app"),
+                ["fluvio-sys"],
+            )
+            .release(InstallArg::new("monitoring", "prometheus/prometheus"));
+        let report = client
+            .run_batch(&batch, |_, arg| {
+                if arg.name == "fluvio-sys" {
+                    panic!("sys chart exploded");
+                }
+                Ok(InstallResult::default())
+            })
+            .unwrap();
+        assert!(matches!(
+            &report.releases[0].1,
+            BatchOutcome::Failed(HelmError::BatchPanicked { message, .. })
+                if message == "sys chart exploded"
+        ));
+        assert!(matches!(report.releases[1].1, BatchOutcome::Skipped));
+        assert!(matches!(report.releases[2].1, BatchOutcome::Installed(_)));
+    }
+
+    #[cfg(all(unix, feature = "client"))]
+    #[test]
+    fn test_uninstall_inspect() {
+        const GET_ALL: &str = "NAME: fluvio\nMANIFEST:\n---\napiVersion: v1\nkind: Service\nmetadata:\n  name: fluvio-sc\n";
+
+        let (client, commands) = scripted_client(&[("get all", GET_ALL)]);
+        let result = client.uninstall(UninstallArg::new("fluvio")).unwrap();
+        assert_eq!(result.resources_deleted, 0);
+        assert_eq!(*commands.lock().unwrap(), ["uninstall fluvio"]);
+
+        let result = client
+            .uninstall(UninstallArg::new("fluvio").inspect())
+            .unwrap();
+        assert_eq!(result.resources_deleted, 1);
+
+        let result = client
+            .uninstall(UninstallArg::new("fluvio").inspect().dry_run())
+            .unwrap();
+        assert_eq!(result.resources_deleted, 0);
+        assert!(!result.hooks_ran);
+    }
+
     #[test]
     fn test_parse_get_installed_charts() {
         const JSON_RESPONSE: &str = r#"[{"name":"test_chart","namespace":"default","revision":"50","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"test_chart-1.2.32-rc2","app_version":"1.2.32-rc2"}]"#;
@@ -437,7 +3706,228 @@ mod tests {
             .first()
             .expect("can not grab the first result");
         assert_eq!(test_chart.name, "test_chart");
+        assert_eq!(test_chart.namespace, "default");
         assert_eq!(test_chart.chart, "test_chart-1.2.32-rc2");
+        assert_eq!(test_chart.chart_name(), "test_chart");
+        assert_eq!(test_chart.chart_version(), "1.2.32-rc2");
+    }
+
+    #[test]
+    fn test_install_presets() {
+        let local = InstallArg::preset_local_dev("fluvio", "fluvio/fluvio-app").namespace("dev");
+        assert!(local.develop);
+        assert!(local.wait);
+        assert!(local.create_namespace);
+        assert!(!local.atomic);
+        assert_eq!(local.namespace.as_deref(), Some("dev"));
+
+        let cloud = InstallArg::preset_cloud("fluvio", "fluvio/fluvio-app");
+        assert!(!cloud.develop);
+        assert!(!cloud.create_namespace);
+        assert!(cloud.atomic);
+        let args: Vec<_> = cloud
+            .upgrade()
+            .get_args()
+            .map(|arg| arg.to_owned())
+            .collect();
+        assert!(args.iter().any(|arg| arg == "--atomic"));
+        assert!(args.iter().any(|arg| arg == "--wait-for-jobs"));
+        assert!(args
+            .windows(2)
+            .any(|pair| pair[0] == "--timeout" && pair[1] == "10m0s"));
+    }
+
+    #[test]
+    fn test_parse_rendered_release() {
+        const JSON_RESPONSE: &str = r#"{"name":"fluvio","info":{"status":"pending-install","notes":"Fluvio is installed"},"manifest":"---\n# Source: fluvio-app/templates/service.yaml\napiVersion: v1\nkind: Service\n","hooks":[{"name":"fluvio-migrate","kind":"Job","manifest":"apiVersion: batch/v1\nkind: Job\n"}],"version":1,"namespace":"default"}"#;
+        let rendered =
+            RenderedRelease::parse(JSON_RESPONSE.as_bytes()).expect("can not parse json");
+        assert!(rendered.manifest.contains("kind: Service"));
+        assert_eq!(rendered.hooks, "---\napiVersion: batch/v1\nkind: Job\n");
+        assert_eq!(rendered.notes, "Fluvio is installed");
+
+        let args = InstallArg::new("fluvio", "fluvio/fluvio-app").dry_run();
+        assert!(args.upgrade().get_args().any(|arg| arg == "--dry-run"));
+    }
+
+    #[test]
+    fn test_install_flags() {
+        let args = InstallArg::new("fluvio", "fluvio/fluvio-app");
+        assert!(!args.install().get_args().any(|arg| arg == "--skip-crds"));
+        assert!(!args.install().get_args().any(|arg| arg == "--no-hooks"));
+        let args = args.skip_crds().no_hooks().render_subchart_notes();
+        for command in [args.install(), args.upgrade()] {
+            assert!(command.get_args().any(|arg| arg == "--skip-crds"));
+            assert!(command.get_args().any(|arg| arg == "--no-hooks"));
+            assert!(command
+                .get_args()
+                .any(|arg| arg == "--render-subchart-notes"));
+        }
+
+        // helm upgrade has no --replace
+        let args = args.replace();
+        assert!(args.install().get_args().any(|arg| arg == "--replace"));
+        assert!(!args.upgrade().get_args().any(|arg| arg == "--replace"));
+    }
+
+    #[test]
+    fn test_install_string_opts() {
+        let args = InstallArg::new("fluvio", "fluvio/fluvio-app")
+            .opt("replicas", "3")
+            .opt_string("image.tag", "1.10")
+            .opt_json("tolerations", serde_json::json!([{"key": "spot"}]))
+            .opt_literal("command", "run --a=1,b=2")
+            .set_file("tls.cert", "certs/server.crt");
+        let command = args.install();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            &args[args.len() - 10..],
+            [
+                "--set",
+                "replicas=3",
+                "--set-string",
+                "image.tag=1.10",
+                "--set-json",
+                r#"tolerations=[{"key":"spot"}]"#,
+                "--set-literal",
+                "command=run --a=1,b=2",
+                "--set-file",
+                "tls.cert=certs/server.crt"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_install_generate_name() {
+        let args = InstallArg::new("", "fluvio/fluvio-app").generate_name();
+        let command = args.install();
+        let argv: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            argv,
+            [
+                "install",
+                "fluvio/fluvio-app",
+                "--generate-name",
+                "--output",
+                "json"
+            ]
+        );
+
+        let name = parse_release_name(br#"{"name":"fluvio-app-1718029391","version":1}"#).unwrap();
+        assert_eq!(name, "fluvio-app-1718029391");
+        let result = InstallResult {
+            generated_name: Some(name),
+            ..InstallResult::default()
+        };
+        assert_eq!(result.release_name(&args), "fluvio-app-1718029391");
+    }
+
+    #[test]
+    fn test_install_verify() {
+        let command = InstallArg::new("fluvio", "fluvio/fluvio-app")
+            .version("0.11.0")
+            .keyring("/etc/helm/pubring.gpg")
+            .install();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            &args[args.len() - 3..],
+            ["--verify", "--keyring", "/etc/helm/pubring.gpg"]
+        );
+    }
+
+    #[test]
+    fn test_install_labels() {
+        let command = InstallArg::new("fluvio", "fluvio/fluvio-app")
+            .label("owner", "fluvio-ci")
+            .label("build", "1234")
+            .upgrade();
+        let args: Vec<_> = command.get_args().collect();
+        let at = args.iter().position(|arg| *arg == "--labels").unwrap();
+        assert_eq!(args[at + 1], "owner=fluvio-ci,build=1234");
+    }
+
+    #[test]
+    fn test_release_description() {
+        let install = InstallArg::new("fluvio", "fluvio/fluvio-app")
+            .description("build 1234")
+            .upgrade();
+        let args: Vec<_> = install.get_args().collect();
+        let at = args.iter().position(|arg| *arg == "--description").unwrap();
+        assert_eq!(args[at + 1], "build 1234");
+
+        let uninstall: Command = UninstallArg::new("fluvio")
+            .keep_history()
+            .description("build 1235")
+            .into();
+        let args: Vec<_> = uninstall.get_args().collect();
+        assert_eq!(&args[args.len() - 2..], ["--description", "build 1235"]);
+    }
+
+    #[test]
+    fn test_install_kube_flags() {
+        let args = InstallArg::new("fluvio", "fluvio/fluvio-app")
+            .namespace("fluvio")
+            .kubeconfig("/etc/kube/staging.yaml")
+            .kube_context("staging");
+        let command = args.upgrade();
+        let args: Vec<_> = command.get_args().collect();
+        let at = args.iter().position(|arg| *arg == "--kubeconfig").unwrap();
+        assert_eq!(
+            &args[at..at + 4],
+            [
+                "--kubeconfig",
+                "/etc/kube/staging.yaml",
+                "--kube-context",
+                "staging"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_search_results() {
+        const JSON_RESPONSE: &str = r#"[{"name":"fluvio/fluvio-app","version":"0.9.2","app_version":"0.9.2","description":"Fluvio application chart"}]"#;
+        let results: Vec<SearchResult> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        assert_eq!(results.len(), 1);
+        let result = &results[0];
+        assert_eq!(result.repo(), Some("fluvio"));
+        assert_eq!(result.chart(), "fluvio-app");
+        assert_eq!(result.version(), "0.9.2");
+        assert_eq!(result.description(), "Fluvio application chart");
+    }
+
+    #[test]
+    fn test_merge_search_results() {
+        const JSON_RESPONSE: &str = r#"[
+            {"name":"mirror/fluvio-app","version":"0.9.1","app_version":"0.9.1","description":""},
+            {"name":"fluvio/fluvio-app","version":"0.9.2","app_version":"0.9.2","description":""},
+            {"name":"fluvio/fluvio-app","version":"0.10.0","app_version":"0.10.0","description":""},
+            {"name":"fluvio/fluvio-sys","version":"0.9.2","app_version":"0.9.2","description":""}
+        ]"#;
+        let results: Vec<SearchResult> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        let merged = merge_search_results(results);
+        let summary: Vec<(&str, &str)> = merged
+            .iter()
+            .map(|result| (result.name(), result.version()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("fluvio/fluvio-app", "0.10.0"),
+                ("mirror/fluvio-app", "0.9.1"),
+                ("fluvio/fluvio-sys", "0.9.2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strip_notes_header() {
+        assert_eq!(
+            strip_notes_header("NOTES:\nFluvio is installed\n"),
+            "Fluvio is installed\n"
+        );
+        assert_eq!(strip_notes_header(""), "");
     }
 
     #[test]
@@ -0,0 +1,157 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::ChartRef;
+
+/// Lint Argument
+///
+/// The chart may be a chart directory, a packaged `.tgz` or an OCI
+/// reference, which is pulled to a temporary directory before linting.
+#[derive(Debug)]
+pub struct LintArg<'a> {
+    pub chart: ChartRef,
+    pub version: Option<Cow<'a, str>>,
+    pub values: Vec<PathBuf>,
+    pub strict: bool,
+    pub with_subcharts: bool,
+}
+
+impl<'a> LintArg<'a> {
+    pub fn new<C: Into<ChartRef>>(chart: C) -> Self {
+        Self {
+            chart: chart.into(),
+            version: None,
+            values: vec![],
+            strict: false,
+            with_subcharts: false,
+        }
+    }
+
+    /// set chart version, used when pulling an OCI chart
+    pub fn version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// set list of values
+    pub fn values(mut self, values: Vec<PathBuf>) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// set one value
+    pub fn value(mut self, value: PathBuf) -> Self {
+        self.values.push(value);
+        self
+    }
+
+    /// fail on warnings
+    pub fn strict(mut self) -> Self {
+        self.strict = true;
+        self
+    }
+
+    /// lint dependent charts as well
+    pub fn with_subcharts(mut self) -> Self {
+        self.with_subcharts = true;
+        self
+    }
+
+    /// the `helm lint` command for the chart at the given local path
+    pub(crate) fn command(&self, chart_path: &Path) -> Command {
+        let mut command = Command::new("helm");
+        command.arg("lint").arg(chart_path);
+
+        for value_path in &self.values {
+            command.arg("--values").arg(value_path);
+        }
+
+        if self.strict {
+            command.arg("--strict");
+        }
+
+        if self.with_subcharts {
+            command.arg("--with-subcharts");
+        }
+
+        command
+    }
+}
+
+/// Severity of a lint message, ordered from least to most severe
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A message reported by `helm lint`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintMessage {
+    /// The message severity
+    pub severity: LintSeverity,
+    /// The chart file the message refers to, e.g. `templates/service.yaml`
+    pub path: Option<String>,
+    /// The message text
+    pub message: String,
+}
+
+/// Parses the `[SEVERITY] path: message` lines of `helm lint` output
+pub(crate) fn parse_lint_messages(output: &str) -> Vec<LintMessage> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (severity, rest) = if let Some(rest) = line.strip_prefix("[INFO]") {
+                (LintSeverity::Info, rest)
+            } else if let Some(rest) = line.strip_prefix("[WARNING]") {
+                (LintSeverity::Warning, rest)
+            } else if let Some(rest) = line.strip_prefix("[ERROR]") {
+                (LintSeverity::Error, rest)
+            } else {
+                return None;
+            };
+            let rest = rest.trim();
+            let (path, message) = match rest.split_once(": ") {
+                Some((path, message)) if !path.contains(' ') => {
+                    (Some(path.to_string()), message.to_string())
+                }
+                _ => (None, rest.to_string()),
+            };
+            Some(LintMessage {
+                severity,
+                path,
+                message,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lint_messages() {
+        const OUTPUT: &str = r#"==> Linting ./fluvio-app
+[INFO] Chart.yaml: icon is recommended
+[WARNING] templates/service.yaml: object name does not conform to Kubernetes naming requirements
+[ERROR] values.yaml: unable to parse YAML: error converting YAML to JSON
+
+Error: 1 chart(s) linted, 1 chart(s) failed
+"#;
+        let messages = parse_lint_messages(OUTPUT);
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[0].severity, LintSeverity::Info);
+        assert_eq!(messages[0].path.as_deref(), Some("Chart.yaml"));
+        assert_eq!(messages[0].message, "icon is recommended");
+        assert_eq!(messages[1].severity, LintSeverity::Warning);
+        assert_eq!(messages[2].severity, LintSeverity::Error);
+        assert_eq!(messages[2].path.as_deref(), Some("values.yaml"));
+        assert!(messages
+            .iter()
+            .any(|message| message.severity >= LintSeverity::Warning));
+    }
+}
@@ -0,0 +1,105 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// A local development cluster, recognized by its kube context name
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalCluster {
+    /// kind cluster, context `kind-<name>`
+    Kind(String),
+    /// k3d cluster, context `k3d-<name>`
+    K3d(String),
+    /// minikube cluster, context `minikube`
+    Minikube,
+}
+
+impl LocalCluster {
+    /// Recognizes the cluster from a kube context name
+    pub fn from_context(context: &str) -> Option<Self> {
+        if let Some(name) = context.strip_prefix("kind-") {
+            Some(Self::Kind(name.to_string()))
+        } else if let Some(name) = context.strip_prefix("k3d-") {
+            Some(Self::K3d(name.to_string()))
+        } else if context == "minikube" {
+            Some(Self::Minikube)
+        } else {
+            None
+        }
+    }
+
+    /// Detects a local cluster from the current context of the active kubeconfig.
+    ///
+    /// Returns `None` if the kubeconfig can't be read or the current
+    /// context is not a local cluster.
+    pub fn detect() -> Option<Self> {
+        current_context().and_then(|context| Self::from_context(&context))
+    }
+
+    /// the kube context name of this cluster
+    pub fn context(&self) -> String {
+        match self {
+            Self::Kind(name) => format!("kind-{}", name),
+            Self::K3d(name) => format!("k3d-{}", name),
+            Self::Minikube => "minikube".to_string(),
+        }
+    }
+}
+
+/// Whether the registry host is one served locally over plain http, such as
+/// the registries set up by kind (`localhost:5001`), k3d (`*.localhost`) or
+/// the minikube registry addon.
+pub fn is_local_registry(host: &str) -> bool {
+    let hostname = host.rsplit_once(':').map(|(h, _)| h).unwrap_or(host);
+    hostname == "localhost"
+        || hostname == "127.0.0.1"
+        || hostname == "host.minikube.internal"
+        || hostname.ends_with(".localhost")
+}
+
+/// Reads `current-context` from the kubeconfig helm would use
+fn current_context() -> Option<String> {
+    kubeconfig_paths().into_iter().find_map(|path| {
+        let config = fs::read_to_string(path).ok()?;
+        config.lines().find_map(|line| {
+            let context = line.strip_prefix("current-context:")?.trim();
+            let context = context.trim_matches(|c| c == '"' || c == '\'');
+            (!context.is_empty()).then(|| context.to_string())
+        })
+    })
+}
+
+fn kubeconfig_paths() -> Vec<PathBuf> {
+    match env::var_os("KUBECONFIG") {
+        Some(paths) if !paths.is_empty() => env::split_paths(&paths).collect(),
+        _ => env::var_os("HOME")
+            .or_else(|| env::var_os("USERPROFILE"))
+            .map(|home| vec![PathBuf::from(home).join(".kube").join("config")])
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_cluster_from_context() {
+        assert_eq!(
+            LocalCluster::from_context("kind-fluvio"),
+            Some(LocalCluster::Kind("fluvio".to_string()))
+        );
+        assert_eq!(
+            LocalCluster::from_context("k3d-fluvio"),
+            Some(LocalCluster::K3d("fluvio".to_string()))
+        );
+        assert_eq!(
+            LocalCluster::from_context("minikube"),
+            Some(LocalCluster::Minikube)
+        );
+        assert_eq!(LocalCluster::from_context("arn:aws:eks:cluster"), None);
+
+        assert!(is_local_registry("localhost:5001"));
+        assert!(is_local_registry("k3d-registry.localhost:5000"));
+        assert!(!is_local_registry("ghcr.io"));
+    }
+}
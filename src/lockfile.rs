@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use semver::VersionReq;
+use serde::{Deserialize, Serialize};
+
+use crate::version::matches_requirement;
+use crate::HelmError;
+
+/// Conventional name of the lockfile
+pub const LOCKFILE_NAME: &str = "helm-lock.yaml";
+
+/// Chart versions resolved from version requirements, so later runs
+/// install the same versions until the lock is updated
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lockfile {
+    /// Locked charts by reference, e.g. `fluvio/fluvio-app`
+    #[serde(default)]
+    pub charts: BTreeMap<String, LockedChart>,
+}
+
+/// A chart version recorded in the lockfile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedChart {
+    /// The requirement the version was resolved from
+    pub requirement: String,
+    /// The resolved version
+    pub version: String,
+    /// Digest of the installed chart, when helm reported one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+}
+
+impl Lockfile {
+    /// Reads a lockfile, empty if the file doesn't exist
+    pub fn load(path: &Path) -> Result<Self, HelmError> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Ok(serde_yaml::from_str(&contents)?),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(source) => Err(HelmError::Io {
+                path: path.to_path_buf(),
+                source,
+            }),
+        }
+    }
+
+    /// Writes the lockfile
+    pub fn save(&self, path: &Path) -> Result<(), HelmError> {
+        let contents = serde_yaml::to_string(self)?;
+        fs::write(path, contents).map_err(|source| HelmError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    /// The locked version of a chart, if it still satisfies `requirement`
+    pub fn locked_version(
+        &self,
+        chart: &str,
+        requirement: &VersionReq,
+        develop: bool,
+    ) -> Option<&str> {
+        let locked = self.charts.get(chart)?;
+        let version = semver::Version::parse(&locked.version).ok()?;
+        if matches_requirement(&version, requirement, develop) {
+            Some(&locked.version)
+        } else {
+            None
+        }
+    }
+
+    /// Records the version resolved for a chart, dropping any digest of a
+    /// previous version
+    pub fn lock(&mut self, chart: &str, requirement: &VersionReq, version: &str) {
+        self.charts.insert(
+            chart.to_string(),
+            LockedChart {
+                requirement: requirement.to_string(),
+                version: version.to_string(),
+                digest: None,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_lockfile() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOCKFILE_NAME);
+        let mut lockfile = Lockfile::load(&path).unwrap();
+        assert!(lockfile.charts.is_empty());
+
+        let requirement = VersionReq::parse("^0.9").unwrap();
+        lockfile.lock("fluvio/fluvio-app", &requirement, "0.9.2");
+        lockfile.charts.get_mut("fluvio/fluvio-app").unwrap().digest =
+            Some("sha256:abc".to_string());
+        lockfile.save(&path).unwrap();
+
+        let loaded = Lockfile::load(&path).unwrap();
+        assert_eq!(loaded, lockfile);
+        assert_eq!(
+            loaded.locked_version("fluvio/fluvio-app", &requirement, false),
+            Some("0.9.2")
+        );
+        // a changed requirement no longer accepts the locked version
+        let requirement = VersionReq::parse("^0.10").unwrap();
+        assert_eq!(
+            loaded.locked_version("fluvio/fluvio-app", &requirement, false),
+            None
+        );
+    }
+}
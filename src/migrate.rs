@@ -0,0 +1,130 @@
+use std::borrow::Cow;
+use std::process::Command;
+
+/// Name of the helm plugin migrating helm 2 releases
+pub const MIGRATE_PLUGIN: &str = "2to3";
+
+/// Argument to convert a helm 2 release to helm 3 with the `2to3` plugin
+#[derive(Debug)]
+pub struct ConvertReleaseArg<'a> {
+    pub release: Cow<'a, str>,
+    pub tiller_namespace: Option<Cow<'a, str>>,
+    pub delete_v2_releases: bool,
+    pub dry_run: bool,
+}
+
+impl<'a> ConvertReleaseArg<'a> {
+    pub fn new<R: Into<Cow<'a, str>>>(release: R) -> Self {
+        Self {
+            release: release.into(),
+            tiller_namespace: None,
+            delete_v2_releases: false,
+            dry_run: false,
+        }
+    }
+
+    /// set namespace tiller is installed in, defaults to `kube-system`
+    pub fn tiller_namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.tiller_namespace = Some(ns.into());
+        self
+    }
+
+    /// delete the helm 2 release data once converted
+    pub fn delete_v2_releases(mut self) -> Self {
+        self.delete_v2_releases = true;
+        self
+    }
+
+    /// only simulate the conversion
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+}
+
+impl From<ConvertReleaseArg<'_>> for Command {
+    fn from(arg: ConvertReleaseArg) -> Self {
+        let mut command = Command::new("helm");
+        command.args([MIGRATE_PLUGIN, "convert", &arg.release]);
+
+        if let Some(namespace) = &arg.tiller_namespace {
+            command.args(["--tiller-ns", namespace]);
+        }
+
+        if arg.delete_v2_releases {
+            command.arg("--delete-v2-releases");
+        }
+
+        if arg.dry_run {
+            command.arg("--dry-run");
+        }
+
+        command
+    }
+}
+
+/// Argument to remove helm 2 configuration, release data and tiller with
+/// the `2to3` plugin
+#[derive(Debug, Default)]
+pub struct CleanupV2Arg<'a> {
+    pub tiller_namespace: Option<Cow<'a, str>>,
+    pub dry_run: bool,
+}
+
+impl<'a> CleanupV2Arg<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// set namespace tiller is installed in, defaults to `kube-system`
+    pub fn tiller_namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.tiller_namespace = Some(ns.into());
+        self
+    }
+
+    /// only simulate the cleanup
+    pub fn dry_run(mut self) -> Self {
+        self.dry_run = true;
+        self
+    }
+}
+
+impl From<CleanupV2Arg<'_>> for Command {
+    fn from(arg: CleanupV2Arg) -> Self {
+        let mut command = Command::new("helm");
+        // the plugin asks for confirmation on stdin otherwise
+        command.args([MIGRATE_PLUGIN, "cleanup", "--skip-confirmation"]);
+
+        if let Some(namespace) = &arg.tiller_namespace {
+            command.args(["--tiller-ns", namespace]);
+        }
+
+        if arg.dry_run {
+            command.arg("--dry-run");
+        }
+
+        command
+    }
+}
+
+/// Extracts plugin names from `helm plugin list` output
+pub(crate) fn parse_plugin_names(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_plugin_names() {
+        const OUTPUT: &str = "NAME\tVERSION\tDESCRIPTION\n2to3\t0.10.3\tmigrate and cleanup Helm v2 configuration and releases in-place to Helm v3\ndiff\t3.9.4\tPreview helm upgrade changes as a diff\n";
+        assert_eq!(parse_plugin_names(OUTPUT), vec!["2to3", "diff"]);
+        assert!(parse_plugin_names("NAME\tVERSION\tDESCRIPTION\n").is_empty());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::version::compare_versions;
+use crate::{HelmError, RepoUrl};
+
+/// Splits an OCI chart reference into registry host and repository, any
+/// tag or digest being dropped
+pub(crate) fn split_reference(reference: &RepoUrl) -> Result<(&str, &str), HelmError> {
+    let invalid = |reason: &str| HelmError::InvalidRepoUrl {
+        url: reference.to_string(),
+        reason: reason.to_string(),
+    };
+    if !reference.is_oci() {
+        return Err(invalid("expected an oci:// reference"));
+    }
+    let rest = reference
+        .as_str()
+        .trim_start_matches("oci://")
+        .split('@')
+        .next()
+        .unwrap_or_default();
+    let (host, repository) = rest
+        .split_once('/')
+        .ok_or_else(|| invalid("missing repository"))?;
+    // a tag follows the last colon after the last slash, a port doesn't
+    let repository = match repository.rsplit_once(':') {
+        Some((repository, _)) if !repository.is_empty() => repository,
+        _ => repository,
+    };
+    Ok((host, repository.trim_end_matches('/')))
+}
+
+/// Converts registry tags to chart versions, newest first.
+///
+/// OCI tags can't hold `+`, so helm pushes versions with build metadata
+/// with `_` instead.
+pub(crate) fn chart_versions(tags: Vec<String>) -> Vec<String> {
+    let mut versions: Vec<String> = tags.into_iter().map(|tag| tag.replace('_', "+")).collect();
+    versions.sort_by(|a, b| compare_versions(b, a));
+    versions
+}
+
+#[cfg(feature = "oci-native")]
+pub(crate) use native::list_tags;
+
+#[cfg(feature = "oci-native")]
+mod native {
+    use std::collections::HashMap;
+
+    use base64::Engine;
+    use serde::Deserialize;
+    use tracing::debug;
+
+    use crate::{HelmError, RepoCredentials};
+
+    /// A page of the tags of an OCI repository, as returned by the registry API
+    #[derive(Debug, Deserialize)]
+    pub(super) struct TagList {
+        #[serde(default)]
+        pub tags: Option<Vec<String>>,
+    }
+
+    fn request_error(url: &str, err: ureq::Error) -> HelmError {
+        HelmError::RegistryRequest {
+            url: url.to_string(),
+            reason: err.to_string(),
+        }
+    }
+
+    /// Parses the parameters of a `WWW-Authenticate: Bearer ...` challenge
+    pub(super) fn parse_challenge(header: &str) -> Option<HashMap<String, String>> {
+        let params = header.strip_prefix("Bearer ")?;
+        let mut parsed = HashMap::new();
+        let mut rest = params.trim();
+        while let Some((key, value)) = rest.split_once('=') {
+            let value = value.trim_start();
+            let (value, remaining) = match value.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted.find('"')?;
+                    (&quoted[..end], &quoted[end + 1..])
+                }
+                None => value.split_once(',').unwrap_or((value, "")),
+            };
+            parsed.insert(key.trim().to_string(), value.to_string());
+            rest = remaining.trim_start_matches([',', ' ']);
+        }
+        Some(parsed)
+    }
+
+    /// Gets a token for the scope of a bearer challenge
+    fn token(
+        challenge: &HashMap<String, String>,
+        credentials: Option<&RepoCredentials>,
+    ) -> Result<String, HelmError> {
+        #[derive(Deserialize)]
+        struct Token {
+            token: Option<String>,
+            access_token: Option<String>,
+        }
+
+        let realm = challenge
+            .get("realm")
+            .ok_or_else(|| HelmError::RegistryRequest {
+                url: String::new(),
+                reason: "bearer challenge without realm".to_string(),
+            })?;
+        let mut request = ureq::get(realm);
+        for key in ["service", "scope"] {
+            if let Some(value) = challenge.get(key) {
+                request = request.query(key, value);
+            }
+        }
+        if let Some(credentials) = credentials {
+            let basic = base64::engine::general_purpose::STANDARD
+                .encode(format!("{}:{}", credentials.username, credentials.password));
+            request = request.set("Authorization", &format!("Basic {}", basic));
+        }
+        let token: Token = request
+            .call()
+            .map_err(|err| request_error(realm, err))?
+            .into_json()
+            .map_err(|err| HelmError::RegistryRequest {
+                url: realm.to_string(),
+                reason: err.to_string(),
+            })?;
+        token
+            .token
+            .or(token.access_token)
+            .ok_or_else(|| HelmError::RegistryRequest {
+                url: realm.to_string(),
+                reason: "no token in response".to_string(),
+            })
+    }
+
+    /// Lists the tags of a repository with the registry API, following
+    /// pagination and bearer token challenges
+    pub(crate) fn list_tags(
+        host: &str,
+        repository: &str,
+        credentials: Option<&RepoCredentials>,
+        plain_http: bool,
+    ) -> Result<Vec<String>, HelmError> {
+        let scheme = if plain_http { "http" } else { "https" };
+        let base = format!("{}://{}", scheme, host);
+        let mut url = format!("{}/v2/{}/tags/list", base, repository);
+        let mut authorization: Option<String> = None;
+        let mut tags = vec![];
+
+        loop {
+            let mut request = ureq::get(&url);
+            if let Some(authorization) = &authorization {
+                request = request.set("Authorization", authorization);
+            }
+            let response = match request.call() {
+                Ok(response) => response,
+                Err(ureq::Error::Status(401, response)) if authorization.is_none() => {
+                    let challenge = response
+                        .header("WWW-Authenticate")
+                        .and_then(parse_challenge)
+                        .ok_or_else(|| HelmError::RegistryRequest {
+                            url: url.clone(),
+                            reason: "unauthorized".to_string(),
+                        })?;
+                    debug!(?challenge, "requesting registry token");
+                    authorization = Some(format!("Bearer {}", token(&challenge, credentials)?));
+                    continue;
+                }
+                Err(err) => return Err(request_error(&url, err)),
+            };
+
+            // e.g. `</v2/<name>/tags/list?last=b&n=100>; rel="next"`
+            let next = response
+                .header("Link")
+                .and_then(|link| link.split_once('<'))
+                .and_then(|(_, link)| link.split_once('>'))
+                .map(|(path, _)| format!("{}{}", base, path));
+            let page: TagList = response
+                .into_json()
+                .map_err(|err| HelmError::RegistryRequest {
+                    url: url.clone(),
+                    reason: err.to_string(),
+                })?;
+            tags.extend(page.tags.unwrap_or_default());
+
+            match next {
+                Some(next) => url = next,
+                None => return Ok(tags),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_reference() {
+        let reference = RepoUrl::new("oci://ghcr.io/infinyon/charts/fluvio-app:0.11.0").unwrap();
+        assert_eq!(
+            split_reference(&reference).unwrap(),
+            ("ghcr.io", "infinyon/charts/fluvio-app")
+        );
+        let reference = RepoUrl::new("oci://localhost:5000/fluvio-app").unwrap();
+        assert_eq!(
+            split_reference(&reference).unwrap(),
+            ("localhost:5000", "fluvio-app")
+        );
+        let reference = RepoUrl::new("https://charts.fluvio.io").unwrap();
+        assert!(split_reference(&reference).is_err());
+    }
+
+    #[test]
+    fn test_chart_versions() {
+        let tags = ["0.9.2", "0.10.0", "0.10.0-rc1", "0.10.1_build.3"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            chart_versions(tags),
+            vec!["0.10.1+build.3", "0.10.0", "0.10.0-rc1", "0.9.2"]
+        );
+    }
+
+    #[cfg(feature = "oci-native")]
+    #[test]
+    fn test_parse_tag_list() {
+        let page: native::TagList = serde_json::from_str(
+            r#"{"name":"infinyon/charts/fluvio-app","tags":["0.9.2","0.10.0"]}"#,
+        )
+        .unwrap();
+        assert_eq!(page.tags.unwrap(), vec!["0.9.2", "0.10.0"]);
+        let page: native::TagList =
+            serde_json::from_str(r#"{"name":"infinyon/charts/fluvio-app","tags":null}"#).unwrap();
+        assert!(page.tags.is_none());
+    }
+
+    #[cfg(feature = "oci-native")]
+    #[test]
+    fn test_parse_challenge() {
+        let challenge = native::parse_challenge(
+            r#"Bearer realm="https://ghcr.io/token",service="ghcr.io",scope="repository:infinyon/fluvio-app:pull""#,
+        )
+        .unwrap();
+        assert_eq!(challenge["realm"], "https://ghcr.io/token");
+        assert_eq!(challenge["service"], "ghcr.io");
+        assert_eq!(challenge["scope"], "repository:infinyon/fluvio-app:pull");
+    }
+}
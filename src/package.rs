@@ -0,0 +1,173 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Package Argument, packages a chart directory into a versioned archive
+#[derive(Debug)]
+pub struct PackageArg<'a> {
+    pub chart_path: PathBuf,
+    pub destination: Option<PathBuf>,
+    pub version: Option<Cow<'a, str>>,
+    pub app_version: Option<Cow<'a, str>>,
+    pub dependency_update: bool,
+    pub sign: bool,
+    pub key: Option<Cow<'a, str>>,
+    pub keyring: Option<PathBuf>,
+    pub passphrase_file: Option<PathBuf>,
+}
+
+impl<'a> PackageArg<'a> {
+    pub fn new<P: Into<PathBuf>>(chart_path: P) -> Self {
+        Self {
+            chart_path: chart_path.into(),
+            destination: None,
+            version: None,
+            app_version: None,
+            dependency_update: false,
+            sign: false,
+            key: None,
+            keyring: None,
+            passphrase_file: None,
+        }
+    }
+
+    /// set directory the archive is written to, defaults to the current directory
+    pub fn destination<P: Into<PathBuf>>(mut self, destination: P) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    /// override the chart version
+    pub fn version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// override the app version
+    pub fn app_version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.app_version = Some(version.into());
+        self
+    }
+
+    /// update dependencies from Chart.yaml before packaging
+    pub fn dependency_update(mut self) -> Self {
+        self.dependency_update = true;
+        self
+    }
+
+    /// sign the package with the named key, writing a provenance file
+    pub fn sign<S: Into<Cow<'a, str>>>(mut self, key: S) -> Self {
+        self.sign = true;
+        self.key = Some(key.into());
+        self
+    }
+
+    /// set keyring holding the signing key, defaults to `~/.gnupg/pubring.gpg`
+    pub fn keyring<P: Into<PathBuf>>(mut self, keyring: P) -> Self {
+        self.keyring = Some(keyring.into());
+        self
+    }
+
+    /// set file holding the passphrase of the signing key, `-` reads stdin
+    pub fn passphrase_file<P: Into<PathBuf>>(mut self, file: P) -> Self {
+        self.passphrase_file = Some(file.into());
+        self
+    }
+}
+
+/// Outcome of packaging a chart
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct PackageResult {
+    /// The chart archive
+    pub archive: PathBuf,
+    /// The provenance file, when the package was signed
+    pub provenance: Option<PathBuf>,
+}
+
+impl PackageResult {
+    pub(crate) fn new(archive: PathBuf, signed: bool) -> Self {
+        // helm writes the provenance file next to the archive
+        let provenance = if signed {
+            let mut path = archive.clone().into_os_string();
+            path.push(".prov");
+            Some(PathBuf::from(path))
+        } else {
+            None
+        };
+        Self {
+            archive,
+            provenance,
+        }
+    }
+}
+
+impl From<PackageArg<'_>> for Command {
+    fn from(arg: PackageArg) -> Self {
+        let mut command = Command::new("helm");
+        command.arg("package").arg(&arg.chart_path);
+
+        if let Some(destination) = &arg.destination {
+            command.arg("--destination").arg(destination);
+        }
+
+        if let Some(version) = &arg.version {
+            command.args(["--version", version]);
+        }
+
+        if let Some(version) = &arg.app_version {
+            command.args(["--app-version", version]);
+        }
+
+        if arg.dependency_update {
+            command.arg("--dependency-update");
+        }
+
+        if arg.sign {
+            command.arg("--sign");
+        }
+
+        if let Some(key) = &arg.key {
+            command.args(["--key", key]);
+        }
+
+        if let Some(keyring) = &arg.keyring {
+            command.arg("--keyring").arg(keyring);
+        }
+
+        if let Some(file) = &arg.passphrase_file {
+            command.arg("--passphrase-file").arg(file);
+        }
+
+        command
+    }
+}
+
+/// Extracts the archive path from `helm package` output
+pub(crate) fn parse_package_path(output: &str) -> Option<PathBuf> {
+    output
+        .lines()
+        .find_map(|line| line.split_once("saved it to: "))
+        .map(|(_, path)| PathBuf::from(path.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_package_path() {
+        const OUTPUT: &str = "Saving 1 charts\nSuccessfully packaged chart and saved it to: /tmp/charts/fluvio-app-0.9.2.tgz\n";
+        assert_eq!(
+            parse_package_path(OUTPUT),
+            Some(PathBuf::from("/tmp/charts/fluvio-app-0.9.2.tgz"))
+        );
+        assert_eq!(parse_package_path(""), None);
+
+        let result = PackageResult::new(PathBuf::from("/tmp/fluvio-app-0.9.2.tgz"), true);
+        assert_eq!(
+            result.provenance,
+            Some(PathBuf::from("/tmp/fluvio-app-0.9.2.tgz.prov"))
+        );
+    }
+}
@@ -0,0 +1,474 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HelmError, InstallArg, ReleaseInventory};
+
+/// A chart repository a release set depends on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoSpec {
+    pub name: String,
+    pub url: String,
+}
+
+/// Desired state of a single release
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseSpec {
+    pub name: String,
+    /// The chart as passed to helm, e.g. `fluvio/fluvio-app`
+    pub chart: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub values: Vec<PathBuf>,
+    #[serde(default)]
+    pub opts: Vec<(String, String)>,
+}
+
+impl ReleaseSpec {
+    pub fn new<N: Into<String>, C: Into<String>>(name: N, chart: C) -> Self {
+        Self {
+            name: name.into(),
+            chart: chart.into(),
+            version: None,
+            namespace: None,
+            values: vec![],
+            opts: vec![],
+        }
+    }
+
+    /// set chart version
+    pub fn version<S: Into<String>>(mut self, version: S) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// set namespace
+    pub fn namespace<S: Into<String>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set one value
+    pub fn value(mut self, value: PathBuf) -> Self {
+        self.values.push(value);
+        self
+    }
+
+    /// set a single option
+    pub fn opt<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.opts.push((key.into(), value.into()));
+        self
+    }
+
+    /// the install argument for the release
+    pub fn into_install_arg(self) -> InstallArg<'static> {
+        let mut arg = InstallArg::new(self.name, self.chart)
+            .values(self.values)
+            .opts(self.opts);
+        arg.version = self.version.map(Cow::Owned);
+        arg.namespace = self.namespace.map(Cow::Owned);
+        arg
+    }
+}
+
+/// A release which must not be installed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "AbsentEntry")]
+pub struct AbsentSpec {
+    pub name: String,
+    /// The namespace of the release, any namespace if `None`
+    pub namespace: Option<String>,
+}
+
+/// An absent release as written in a release set, either its name or the
+/// full spec
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AbsentEntry {
+    Name(String),
+    Spec {
+        name: String,
+        #[serde(default)]
+        namespace: Option<String>,
+    },
+}
+
+impl From<AbsentEntry> for AbsentSpec {
+    fn from(entry: AbsentEntry) -> Self {
+        match entry {
+            AbsentEntry::Name(name) => Self {
+                name,
+                namespace: None,
+            },
+            AbsentEntry::Spec { name, namespace } => Self { name, namespace },
+        }
+    }
+}
+
+/// Desired state of a group of releases, converged with
+/// `HelmClient::plan` and `HelmClient::apply`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseSet {
+    /// Repositories which must be configured
+    #[serde(default)]
+    pub repos: Vec<RepoSpec>,
+    /// Releases which must be installed
+    #[serde(default)]
+    pub releases: Vec<ReleaseSpec>,
+    /// Releases which must not be installed
+    #[serde(default)]
+    pub absent: Vec<AbsentSpec>,
+}
+
+impl ReleaseSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// require a repository
+    pub fn repo<N: Into<String>, U: Into<String>>(mut self, name: N, url: U) -> Self {
+        self.repos.push(RepoSpec {
+            name: name.into(),
+            url: url.into(),
+        });
+        self
+    }
+
+    /// require a release
+    pub fn release(mut self, release: ReleaseSpec) -> Self {
+        self.releases.push(release);
+        self
+    }
+
+    /// require a release to be uninstalled from every namespace
+    pub fn absent<N: Into<String>>(mut self, name: N) -> Self {
+        self.absent.push(AbsentSpec {
+            name: name.into(),
+            namespace: None,
+        });
+        self
+    }
+
+    /// require a release to be uninstalled from the given namespace
+    pub fn absent_in<N: Into<String>, S: Into<String>>(mut self, name: N, ns: S) -> Self {
+        self.absent.push(AbsentSpec {
+            name: name.into(),
+            namespace: Some(ns.into()),
+        });
+        self
+    }
+}
+
+/// A change needed to converge a release set
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum PlannedAction {
+    AddRepo(RepoSpec),
+    Install(ReleaseSpec),
+    Upgrade {
+        /// The installed chart version
+        from: String,
+        #[serde(flatten)]
+        release: ReleaseSpec,
+    },
+    Uninstall {
+        name: String,
+        #[serde(default)]
+        namespace: Option<String>,
+    },
+}
+
+impl PlannedAction {
+    /// The release the action changes, `None` for repo actions
+    pub fn release_name(&self) -> Option<&str> {
+        match self {
+            Self::AddRepo(_) => None,
+            Self::Install(release) | Self::Upgrade { release, .. } => Some(&release.name),
+            Self::Uninstall { name, .. } => Some(name),
+        }
+    }
+}
+
+/// The ordered changes needed to converge a release set.
+///
+/// Plans serialize to JSON, so they can be reviewed before being applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub actions: Vec<PlannedAction>,
+}
+
+impl Plan {
+    /// Computes the actions converging the configured repos and installed
+    /// releases to the release set.
+    ///
+    /// Installed releases are only upgraded when the set pins a different
+    /// chart version, changes to values are not detected. Releases without
+    /// a namespace are looked up in any namespace.
+    ///
+    /// `HelmClient::plan` computes the plan from the live state, this can
+    /// be used instead when helm runs elsewhere, e.g. without the `client`
+    /// feature.
+    pub fn new(set: &ReleaseSet, repos: &[String], inventory: &ReleaseInventory) -> Self {
+        let mut actions = vec![];
+
+        for repo in &set.repos {
+            if !repos.contains(&repo.name) {
+                actions.push(PlannedAction::AddRepo(repo.clone()));
+            }
+        }
+
+        for release in &set.releases {
+            let installed = match &release.namespace {
+                Some(namespace) => inventory.find_release_in(namespace, &release.name),
+                None => inventory.find_release(&release.name),
+            };
+            match installed {
+                None => actions.push(PlannedAction::Install(release.clone())),
+                Some(installed) => match &release.version {
+                    Some(version) if version != installed.chart_version() => {
+                        actions.push(PlannedAction::Upgrade {
+                            from: installed.chart_version().to_string(),
+                            release: release.clone(),
+                        })
+                    }
+                    _ => {}
+                },
+            }
+        }
+
+        for absent in &set.absent {
+            let installed = inventory.releases().iter().filter(|release| {
+                release.name == absent.name
+                    && absent
+                        .namespace
+                        .as_ref()
+                        .is_none_or(|namespace| *namespace == release.namespace)
+            });
+            for release in installed {
+                actions.push(PlannedAction::Uninstall {
+                    name: release.name.clone(),
+                    namespace: Some(release.namespace.clone()),
+                });
+            }
+        }
+
+        Self { actions }
+    }
+
+    /// whether the release set is already converged
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+/// How applying a plan reacts to a failed action
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FailurePolicy {
+    /// Stop at the first failure, skipping the remaining actions
+    #[default]
+    FailFast,
+    /// Carry on with the remaining actions
+    Continue,
+    /// Stop at the first failure and undo the actions applied so far
+    RollbackAll,
+}
+
+/// What happened to a planned action
+#[derive(Debug)]
+pub enum ActionOutcome {
+    /// The action was applied
+    Converged,
+    /// The action failed
+    Failed(HelmError),
+    /// The action was not attempted after an earlier failure
+    Skipped,
+    /// The action was applied, then undone after a later failure
+    RolledBack,
+    /// The action was applied, but undoing it after a later failure failed
+    RollbackFailed(HelmError),
+}
+
+/// An action of an applied plan and its outcome
+#[derive(Debug)]
+pub struct ActionReport {
+    pub action: PlannedAction,
+    pub outcome: ActionOutcome,
+}
+
+/// Outcome of applying a plan, one entry per action in plan order
+#[derive(Debug, Default)]
+pub struct ApplyReport {
+    pub actions: Vec<ActionReport>,
+}
+
+impl ApplyReport {
+    /// whether every action was applied
+    pub fn is_success(&self) -> bool {
+        self.actions
+            .iter()
+            .all(|report| matches!(report.outcome, ActionOutcome::Converged))
+    }
+
+    /// names of the releases whose actions ended with an outcome matching `filter`
+    pub fn releases<F>(&self, filter: F) -> Vec<&str>
+    where
+        F: Fn(&ActionOutcome) -> bool,
+    {
+        self.actions
+            .iter()
+            .filter(|report| filter(&report.outcome))
+            .filter_map(|report| report.action.release_name())
+            .collect()
+    }
+
+    /// The report, or the error of the first failed action
+    pub(crate) fn into_result(mut self) -> Result<Self, HelmError> {
+        let failed = self
+            .actions
+            .iter()
+            .position(|report| matches!(report.outcome, ActionOutcome::Failed(_)));
+        match failed.map(|index| self.actions.remove(index).outcome) {
+            Some(ActionOutcome::Failed(err)) => Err(err),
+            _ => Ok(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::InstalledChart;
+
+    #[test]
+    fn test_plan() {
+        const JSON_RESPONSE: &str = r#"[{"name":"fluvio","namespace":"default","revision":"1","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"fluvio-app-0.9.0","app_version":"0.9.0"},{"name":"legacy","namespace":"default","revision":"3","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"legacy-1.0.0","app_version":"1.0.0"}]"#;
+        let releases: Vec<InstalledChart> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        let inventory = ReleaseInventory::new(releases);
+
+        let set = ReleaseSet::new()
+            .repo("fluvio", "https://charts.fluvio.io")
+            .repo("stable", "https://charts.helm.sh/stable")
+            .release(ReleaseSpec::new("fluvio", "fluvio/fluvio-app").version("0.9.2"))
+            .release(ReleaseSpec::new("fluvio-sys", "fluvio/fluvio-sys"))
+            .absent("legacy")
+            .absent("gone");
+        let plan = Plan::new(&set, &["stable".to_string()], &inventory);
+
+        assert_eq!(
+            plan.actions,
+            vec![
+                PlannedAction::AddRepo(set.repos[0].clone()),
+                PlannedAction::Upgrade {
+                    from: "0.9.0".to_string(),
+                    release: set.releases[0].clone(),
+                },
+                PlannedAction::Install(set.releases[1].clone()),
+                PlannedAction::Uninstall {
+                    name: "legacy".to_string(),
+                    namespace: Some("default".to_string()),
+                },
+            ]
+        );
+
+        let json = serde_json::to_string(&plan).expect("can not serialize plan");
+        let parsed: Plan = serde_json::from_str(&json).expect("can not parse plan");
+        assert_eq!(parsed, plan);
+    }
+
+    #[test]
+    fn test_plan_namespaces() {
+        const JSON_RESPONSE: &str = r#"[{"name":"fluvio","namespace":"default","revision":"1","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"fluvio-app-0.9.0","app_version":"0.9.0"},{"name":"fluvio","namespace":"staging","revision":"2","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"fluvio-app-0.9.2","app_version":"0.9.2"},{"name":"legacy","namespace":"staging","revision":"3","updated":"2021-03-17 08:42:54.546347741 +0000 UTC","status":"deployed","chart":"legacy-1.0.0","app_version":"1.0.0"}]"#;
+        let releases: Vec<InstalledChart> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        let inventory = ReleaseInventory::new(releases);
+
+        let set = ReleaseSet::new()
+            .release(
+                ReleaseSpec::new("fluvio", "fluvio/fluvio-app")
+                    .namespace("staging")
+                    .version("0.9.2"),
+            )
+            .release(
+                ReleaseSpec::new("fluvio", "fluvio/fluvio-app")
+                    .namespace("prod")
+                    .version("0.9.2"),
+            )
+            .absent_in("fluvio", "default")
+            .absent_in("legacy", "default")
+            .absent("legacy");
+        let plan = Plan::new(&set, &[], &inventory);
+
+        assert_eq!(
+            plan.actions,
+            vec![
+                PlannedAction::Install(set.releases[1].clone()),
+                PlannedAction::Uninstall {
+                    name: "fluvio".to_string(),
+                    namespace: Some("default".to_string()),
+                },
+                PlannedAction::Uninstall {
+                    name: "legacy".to_string(),
+                    namespace: Some("staging".to_string()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_absent_releases() {
+        let set: ReleaseSet = serde_json::from_str(
+            r#"{"absent":["legacy",{"name":"fluvio","namespace":"staging"}]}"#,
+        )
+        .expect("can not parse release set");
+        assert_eq!(
+            set.absent,
+            ReleaseSet::new()
+                .absent("legacy")
+                .absent_in("fluvio", "staging")
+                .absent
+        );
+    }
+
+    #[test]
+    fn test_apply_report() {
+        let install =
+            |name: &str| PlannedAction::Install(ReleaseSpec::new(name, "fluvio/fluvio-app"));
+        let report = ApplyReport {
+            actions: vec![
+                ActionReport {
+                    action: install("fluvio-sys"),
+                    outcome: ActionOutcome::RolledBack,
+                },
+                ActionReport {
+                    action: install("fluvio"),
+                    outcome: ActionOutcome::Failed(HelmError::InvalidArg("failed".to_string())),
+                },
+                ActionReport {
+                    action: PlannedAction::Uninstall {
+                        name: "legacy".to_string(),
+                        namespace: None,
+                    },
+                    outcome: ActionOutcome::Skipped,
+                },
+            ],
+        };
+        assert!(!report.is_success());
+        assert_eq!(
+            report.releases(|outcome| matches!(outcome, ActionOutcome::RolledBack)),
+            vec!["fluvio-sys"]
+        );
+        assert_eq!(
+            report.releases(|outcome| matches!(outcome, ActionOutcome::Skipped)),
+            vec!["legacy"]
+        );
+        assert!(matches!(
+            report.into_result(),
+            Err(HelmError::InvalidArg(message)) if message == "failed"
+        ));
+    }
+}
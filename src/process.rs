@@ -0,0 +1,451 @@
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::process::{Child, Command, Output, Stdio};
+use std::sync::Mutex;
+#[cfg(windows)]
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use fluvio_command::{CommandError, CommandErrorKind};
+use tracing::{debug, warn};
+
+use crate::explain::command_argv;
+use crate::transport::Transport;
+
+/// The helm children started by any client
+static CHILDREN: ChildTracker = ChildTracker::new();
+
+/// The most recent helm invocations, oldest first
+static RECENT_COMMANDS: Mutex<VecDeque<CommandRecord>> = Mutex::new(VecDeque::new());
+
+/// Number of invocations kept in [`RECENT_COMMANDS`]
+const RECENT_COMMANDS_CAPACITY: usize = 50;
+
+/// A helm invocation, kept for diagnostics
+#[derive(Debug, Clone)]
+pub struct CommandRecord {
+    /// The command line before the transport wrapped it, with passwords
+    /// masked
+    pub argv: Vec<String>,
+    /// When the command was started
+    pub started_at: SystemTime,
+    /// How long the command ran
+    pub duration: Duration,
+    /// The exit code, `None` if it was terminated or could not be spawned
+    pub exit_code: Option<i32>,
+}
+
+/// The most recent helm invocations of any client, oldest first
+pub fn recent_commands() -> Vec<CommandRecord> {
+    RECENT_COMMANDS.lock().unwrap().iter().cloned().collect()
+}
+
+fn record(argv: &[String], started_at: SystemTime, started: Instant, exit_code: Option<i32>) {
+    let mut recent = RECENT_COMMANDS.lock().unwrap();
+    if recent.len() == RECENT_COMMANDS_CAPACITY {
+        recent.pop_front();
+    }
+    recent.push_back(CommandRecord {
+        argv: argv.to_vec(),
+        started_at,
+        duration: started.elapsed(),
+        exit_code,
+    });
+}
+
+/// Interval at which terminated children are checked for exit
+const EXIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Runs a command like `CommandExt::result` through the transport, if any,
+/// tracking the child so it can be stopped by [`terminate_children`].
+/// `stdin` is written to the input of the child, e.g. a password passed
+/// with `--password-stdin`.
+pub(crate) fn run_tracked(
+    command: &mut Command,
+    transport: Option<&dyn Transport>,
+    stdin: Option<&[u8]>,
+) -> Result<Output, CommandError> {
+    CHILDREN.run(command, transport, stdin)
+}
+
+/// Stops the helm processes started by any client, so they don't keep
+/// holding release locks after the caller gives up on them.
+///
+/// On Unix children are sent `SIGTERM`, then `SIGKILL` if still running
+/// after `grace`. Callers blocked on a terminated child get a `Terminated`
+/// error.
+///
+/// # Windows
+///
+/// Helm processes run in a Job Object, which is terminated at once: this
+/// kills them along with the plugins and post-renderers they started, also
+/// those whose helm parent already exited. The job kills its processes as
+/// well when this process exits. Callers blocked on a terminated child get
+/// an exit error.
+pub fn terminate_children(grace: Duration) {
+    CHILDREN.terminate(grace);
+}
+
+/// Running children, so they can be stopped together
+struct ChildTracker {
+    pids: Mutex<Vec<u32>>,
+    /// The Job Object the children are assigned to, created on first use
+    #[cfg(windows)]
+    job: OnceLock<Option<job::Job>>,
+}
+
+impl ChildTracker {
+    const fn new() -> Self {
+        Self {
+            pids: Mutex::new(Vec::new()),
+            #[cfg(windows)]
+            job: OnceLock::new(),
+        }
+    }
+
+    /// Runs a command like [`run_tracked`], tracking the child here
+    fn run(
+        &self,
+        command: &mut Command,
+        transport: Option<&dyn Transport>,
+        stdin: Option<&[u8]>,
+    ) -> Result<Output, CommandError> {
+        // the command is masked before a transport joins its arguments, and
+        // errors carry the masked command line as they are displayed to users
+        let argv = command_argv(command);
+        let command_error = |source| CommandError {
+            command: argv.join(" "),
+            source,
+        };
+
+        let mut wrapped = transport.map(|transport| transport.wrap(command));
+        let command = wrapped.as_mut().unwrap_or(command);
+
+        let started_at = SystemTime::now();
+        let started = Instant::now();
+        let mut child = command
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| {
+                record(&argv, started_at, started, None);
+                command_error(CommandErrorKind::IoError(err))
+            })?;
+        #[cfg(windows)]
+        self.assign_to_job(&child);
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            // dropping the pipe closes the input of the child
+            if let Err(err) = pipe.write_all(input) {
+                warn!(%err, "unable to write the input of the helm process");
+            }
+        }
+        let output = self.wait_with_output(child);
+        record(
+            &argv,
+            started_at,
+            started,
+            output.as_ref().ok().and_then(|output| output.status.code()),
+        );
+
+        let output = output.map_err(|err| command_error(CommandErrorKind::IoError(err)))?;
+        match output.status.code() {
+            Some(0) => Ok(output),
+            None => Err(command_error(CommandErrorKind::Terminated)),
+            Some(code) => Err(command_error(CommandErrorKind::ExitError(code, output))),
+        }
+    }
+
+    /// Waits for a child like `Child::wait_with_output`, tracking it until
+    /// it exited.
+    ///
+    /// The pid is untracked before the child is reaped, as the OS may reuse
+    /// it for another process afterwards.
+    fn wait_with_output(&self, mut child: Child) -> io::Result<Output> {
+        let pid = child.id();
+        self.pids.lock().unwrap().push(pid);
+
+        // stderr is read on another thread so neither pipe fills up
+        let stderr = child
+            .stderr
+            .take()
+            .map(|pipe| thread::spawn(|| read_pipe(pipe)));
+        let stdout = child.stdout.take().map_or(Ok(vec![]), read_pipe);
+        let stderr = stderr.map_or(Ok(vec![]), |reader| {
+            reader.join().unwrap_or_else(|_| Ok(vec![]))
+        });
+
+        wait_exited(&child);
+        self.pids.lock().unwrap().retain(|&child| child != pid);
+        let status = child.wait()?;
+        Ok(Output {
+            status,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    }
+
+    /// Stops the children tracked here like [`terminate_children`]
+    fn terminate(&self, grace: Duration) {
+        // the pids are signalled while the lock is held, so none of them
+        // was reaped and reused in between
+        let children = {
+            let pids = self.pids.lock().unwrap();
+            if pids.is_empty() {
+                return;
+            }
+            debug!(children = ?*pids, "terminating helm processes");
+            #[cfg(unix)]
+            for &pid in pids.iter() {
+                signal(pid, libc::SIGTERM);
+            }
+            #[cfg(windows)]
+            self.terminate_job();
+            pids.clone()
+        };
+
+        let deadline = Instant::now() + grace;
+        while Instant::now() < deadline {
+            if !self
+                .pids
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|pid| children.contains(pid))
+            {
+                return;
+            }
+            thread::sleep(EXIT_POLL_INTERVAL);
+        }
+
+        let pids = self.pids.lock().unwrap();
+        for pid in pids.iter().filter(|pid| children.contains(pid)) {
+            warn!(pid, "helm process did not exit, killing it");
+            #[cfg(unix)]
+            signal(*pid, libc::SIGKILL);
+        }
+        #[cfg(windows)]
+        self.terminate_job();
+    }
+
+    /// Assigns a child to the Job Object of the tracker, the processes it
+    /// starts then belong to the job too
+    #[cfg(windows)]
+    fn assign_to_job(&self, child: &Child) {
+        let job = self.job.get_or_init(|| match job::Job::new() {
+            Ok(job) => Some(job),
+            Err(err) => {
+                warn!(%err, "unable to create a job object for helm processes");
+                None
+            }
+        });
+        if let Some(job) = job {
+            if let Err(err) = job.assign(child) {
+                warn!(pid = child.id(), %err, "unable to assign helm process to job object");
+            }
+        }
+    }
+
+    /// Kills every process of the Job Object of the tracker
+    #[cfg(windows)]
+    fn terminate_job(&self) {
+        if let Some(Some(job)) = self.job.get() {
+            if let Err(err) = job.terminate() {
+                warn!(%err, "unable to terminate helm processes");
+            }
+        }
+    }
+}
+
+fn read_pipe<R: Read>(mut pipe: R) -> io::Result<Vec<u8>> {
+    let mut buf = vec![];
+    pipe.read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Blocks until the child exited, leaving it to be reaped by `Child::wait`
+#[cfg(unix)]
+fn wait_exited(child: &Child) {
+    loop {
+        // SAFETY: siginfo_t is a plain C struct, zeroed is a valid value
+        let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+        // SAFETY: info outlives the call, WNOWAIT leaves the child a zombie
+        // so its pid stays reserved
+        let result = unsafe {
+            libc::waitid(
+                libc::P_PID,
+                child.id() as libc::id_t,
+                &mut info,
+                libc::WEXITED | libc::WNOWAIT,
+            )
+        };
+        if result == 0 || io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return;
+        }
+    }
+}
+
+/// The pid of a child stays reserved while `Child` holds its handle, so
+/// there is nothing to wait for before reaping it
+#[cfg(windows)]
+fn wait_exited(_child: &Child) {}
+
+#[cfg(unix)]
+fn signal(pid: u32, signum: libc::c_int) {
+    // SAFETY: kill has no memory safety requirements
+    unsafe {
+        libc::kill(pid as libc::pid_t, signum);
+    }
+}
+
+#[cfg(windows)]
+mod job {
+    use std::ffi::c_void;
+    use std::io;
+    use std::mem;
+    use std::os::windows::io::AsRawHandle;
+    use std::process::Child;
+    use std::ptr;
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE};
+    use windows_sys::Win32::System::JobObjects::{
+        AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation,
+        SetInformationJobObject, TerminateJobObject, JOBOBJECT_EXTENDED_LIMIT_INFORMATION,
+        JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+    };
+
+    /// A Job Object killing its processes once its handle is closed, at
+    /// the latest when this process exits
+    pub(super) struct Job(HANDLE);
+
+    impl Job {
+        pub(super) fn new() -> io::Result<Self> {
+            // SAFETY: null attributes and name create an anonymous job
+            let handle = unsafe { CreateJobObjectW(ptr::null(), ptr::null()) };
+            if handle == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            let job = Self(handle);
+
+            // SAFETY: the limits are a plain C struct, zeroed is a valid value
+            let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { mem::zeroed() };
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            // SAFETY: the limits outlive the call and match the information class
+            let set = unsafe {
+                SetInformationJobObject(
+                    job.0,
+                    JobObjectExtendedLimitInformation,
+                    &limits as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const c_void,
+                    mem::size_of_val(&limits) as u32,
+                )
+            };
+            if set == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(job)
+        }
+
+        pub(super) fn assign(&self, child: &Child) -> io::Result<()> {
+            // SAFETY: both handles are valid while borrowed
+            let assigned =
+                unsafe { AssignProcessToJobObject(self.0, child.as_raw_handle() as HANDLE) };
+            if assigned == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+
+        pub(super) fn terminate(&self) -> io::Result<()> {
+            // SAFETY: the job handle is valid until dropped
+            if unsafe { TerminateJobObject(self.0, 1) } == 0 {
+                return Err(io::Error::last_os_error());
+            }
+            Ok(())
+        }
+    }
+
+    impl Drop for Job {
+        fn drop(&mut self) {
+            // SAFETY: the handle is owned and not used afterwards
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+}
+
+/// Terminates running helm processes when this process receives `SIGINT`
+/// or `SIGTERM` (Ctrl-C or Ctrl-Break on Windows), then exits with code 130.
+///
+/// This replaces any handler installed with the `ctrlc` crate, so
+/// applications with their own handler should call [`terminate_children`]
+/// from it instead.
+#[cfg(feature = "signals")]
+pub fn install_signal_handler(grace: Duration) -> Result<(), ctrlc::Error> {
+    ctrlc::set_handler(move || {
+        terminate_children(grace);
+        std::process::exit(130);
+    })
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminate_children() {
+        // a tracker of its own, terminating children of other tests would
+        // make them fail
+        static TRACKER: ChildTracker = ChildTracker::new();
+        let runner = thread::spawn(|| TRACKER.run(Command::new("sleep").arg("30"), None, None));
+        while TRACKER.pids.lock().unwrap().is_empty() {
+            thread::sleep(EXIT_POLL_INTERVAL);
+        }
+
+        TRACKER.terminate(Duration::from_secs(5));
+        let err = runner.join().unwrap().unwrap_err();
+        assert!(matches!(err.source, CommandErrorKind::Terminated));
+        assert!(TRACKER.pids.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_wait_with_output() {
+        static TRACKER: ChildTracker = ChildTracker::new();
+        // more output than a pipe buffers, on both pipes
+        let output = TRACKER
+            .run(
+                Command::new("sh").args([
+                    "-c",
+                    "head -c 200000 /dev/zero; head -c 200000 /dev/zero >&2",
+                ]),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(output.stdout.len(), 200000);
+        assert_eq!(output.stderr.len(), 200000);
+        assert!(TRACKER.pids.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_run_tracked_stdin() {
+        let output = run_tracked(&mut Command::new("cat"), None, Some(b"secret")).unwrap();
+        assert_eq!(output.stdout, b"secret");
+    }
+
+    #[test]
+    fn test_command_error_masks_password() {
+        let err = run_tracked(
+            Command::new("false").args(["--username", "fluvio", "--password", "secret"]),
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.command, "false --username fluvio --password ********");
+    }
+}
@@ -0,0 +1,154 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{ChartRef, RepoCredentials, RepoUrl};
+
+/// Pull Argument, downloads a chart archive and optionally unpacks it
+#[derive(Debug, Clone)]
+pub struct PullArg<'a> {
+    pub chart: ChartRef,
+    pub version: Option<Cow<'a, str>>,
+    pub destination: Option<PathBuf>,
+    pub untar: bool,
+    pub untardir: Option<PathBuf>,
+    pub credentials: Option<RepoCredentials>,
+    pub prov: bool,
+}
+
+impl<'a> PullArg<'a> {
+    pub fn new<C: Into<ChartRef>>(chart: C) -> Self {
+        Self {
+            chart: chart.into(),
+            version: None,
+            destination: None,
+            untar: false,
+            untardir: None,
+            credentials: None,
+            prov: false,
+        }
+    }
+
+    /// set chart version, defaults to the latest
+    pub fn version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// set directory the chart is written to, defaults to the current directory
+    pub fn destination<P: Into<PathBuf>>(mut self, destination: P) -> Self {
+        self.destination = Some(destination.into());
+        self
+    }
+
+    /// unpack the chart after downloading it
+    pub fn untar(mut self) -> Self {
+        self.untar = true;
+        self
+    }
+
+    /// set directory, relative to the destination, the chart is unpacked into
+    pub fn untardir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.untar = true;
+        self.untardir = Some(dir.into());
+        self
+    }
+
+    /// download the provenance file next to the archive
+    pub fn prov(mut self) -> Self {
+        self.prov = true;
+        self
+    }
+
+    /// look the chart name up in the given repository, as with `--repo`
+    pub fn repo(mut self, repo: RepoUrl) -> Self {
+        self.chart = match self.chart {
+            ChartRef::Name(name) | ChartRef::Repo { name, .. } => ChartRef::Repo { repo, name },
+            chart => chart,
+        };
+        self
+    }
+
+    /// set credentials, defaults to those registered on the client for the repo host
+    pub fn credentials<U: Into<String>, P: Into<String>>(
+        mut self,
+        username: U,
+        password: P,
+    ) -> Self {
+        self.credentials = Some(RepoCredentials::new(username, password));
+        self
+    }
+
+    /// the `helm pull` command writing the chart into `destination`
+    ///
+    /// The chart is unpacked into `destination` directly, `untardir` is
+    /// applied when moving it to its final location.
+    pub(crate) fn command(&self, destination: &Path) -> Command {
+        let mut command = Command::new("helm");
+        command.arg("pull");
+        self.chart.apply(&mut command);
+
+        if let Some(version) = &self.version {
+            command.args(["--version", version]);
+        }
+
+        if self.untar {
+            command.arg("--untar");
+        }
+
+        if self.prov {
+            command.arg("--prov");
+        }
+
+        if let Some(credentials) = &self.credentials {
+            credentials.apply(&mut command);
+        }
+
+        command.arg("--destination").arg(destination);
+        command
+    }
+
+    /// the directory the pulled chart ends up in
+    pub(crate) fn target_dir(&self) -> PathBuf {
+        let destination = self
+            .destination
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("."));
+        match &self.untardir {
+            Some(dir) if self.untar => destination.join(dir),
+            _ => destination,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pull_command() {
+        let repo: RepoUrl = "https://charts.fluvio.io".parse().unwrap();
+        let pull = PullArg::new("fluvio-app")
+            .repo(repo)
+            .version("0.9.2")
+            .destination("charts")
+            .untardir("unpacked");
+        let command = pull.command(Path::new("staging"));
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "pull",
+                "fluvio-app",
+                "--repo",
+                "https://charts.fluvio.io",
+                "--version",
+                "0.9.2",
+                "--untar",
+                "--destination",
+                "staging"
+            ]
+        );
+        assert_eq!(pull.target_dir(), Path::new("charts/unpacked"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::{HelmError, RepoUrl};
+
+/// Push Argument, uploads a packaged chart to an OCI registry
+#[derive(Debug)]
+pub struct PushArg {
+    pub archive: PathBuf,
+    pub remote: RepoUrl,
+    pub insecure_skip_tls_verify: bool,
+    pub plain_http: bool,
+}
+
+impl PushArg {
+    /// push the chart archive to `remote`, e.g. `oci://ghcr.io/infinyon`
+    pub fn new<P: Into<PathBuf>>(archive: P, remote: RepoUrl) -> Self {
+        Self {
+            archive: archive.into(),
+            remote,
+            insecure_skip_tls_verify: false,
+            plain_http: false,
+        }
+    }
+
+    /// skip verification of the registry certificate
+    pub fn insecure_skip_tls_verify(mut self) -> Self {
+        self.insecure_skip_tls_verify = true;
+        self
+    }
+
+    /// use http instead of https to reach the registry
+    pub fn plain_http(mut self) -> Self {
+        self.plain_http = true;
+        self
+    }
+
+    /// Checks the remote is an OCI registry, the only kind helm pushes to
+    pub fn validate(&self) -> Result<(), HelmError> {
+        if !self.remote.is_oci() {
+            return Err(HelmError::InvalidArg(format!(
+                "charts can only be pushed to oci:// registries, not {}",
+                self.remote
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl From<PushArg> for Command {
+    fn from(arg: PushArg) -> Self {
+        let mut command = Command::new("helm");
+        command
+            .arg("push")
+            .arg(&arg.archive)
+            .arg(arg.remote.as_str());
+
+        if arg.insecure_skip_tls_verify {
+            command.arg("--insecure-skip-tls-verify");
+        }
+
+        if arg.plain_http {
+            command.arg("--plain-http");
+        }
+
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_command() {
+        let remote: RepoUrl = "oci://localhost:5000/charts".parse().unwrap();
+        let arg = PushArg::new("fluvio-app-0.9.2.tgz", remote).plain_http();
+        arg.validate().expect("oci remote is valid");
+        let command: Command = arg.into();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "push",
+                "fluvio-app-0.9.2.tgz",
+                "oci://localhost:5000/charts",
+                "--plain-http"
+            ]
+        );
+
+        let remote: RepoUrl = "https://charts.fluvio.io".parse().unwrap();
+        assert!(PushArg::new("fluvio-app-0.9.2.tgz", remote)
+            .validate()
+            .is_err());
+    }
+}
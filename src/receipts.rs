@@ -0,0 +1,196 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::HelmError;
+
+/// Orders receipts written within the same millisecond
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory of the receipts of operations given no namespace, not a
+/// valid namespace name so it can't collide with one
+const NO_NAMESPACE_DIR: &str = "_";
+
+/// Release operation recorded by a receipt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiptOperation {
+    Install,
+    Upgrade,
+    Uninstall,
+}
+
+/// Record of a release operation run by this crate
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Receipt {
+    /// The release name
+    pub release: String,
+    /// The namespace given for the operation
+    pub namespace: Option<String>,
+    pub operation: ReceiptOperation,
+    /// The OS user running the operation
+    pub user: Option<String>,
+    pub recorded_at: SystemTime,
+    /// The helm command line, with passwords masked
+    pub argv: Vec<String>,
+    /// The chart installed or upgraded to
+    pub chart: Option<String>,
+    /// The requested chart version
+    pub chart_version: Option<String>,
+    /// sha256 digest of the chart archive, when installed from a local `.tgz`
+    pub chart_digest: Option<String>,
+    /// manifest digest reported by the registry, when installed from an OCI reference
+    pub oci_digest: Option<String>,
+}
+
+impl Receipt {
+    pub(crate) fn new<R: Into<String>>(
+        release: R,
+        namespace: Option<String>,
+        operation: ReceiptOperation,
+        argv: Vec<String>,
+    ) -> Self {
+        Self {
+            release: release.into(),
+            namespace,
+            operation,
+            user: env::var("USER").or_else(|_| env::var("USERNAME")).ok(),
+            recorded_at: SystemTime::now(),
+            argv,
+            chart: None,
+            chart_version: None,
+            chart_digest: None,
+            oci_digest: None,
+        }
+    }
+}
+
+/// Directory of receipts, one JSON file per operation under a directory
+/// per namespace and release
+#[derive(Debug, Clone)]
+pub(crate) struct ReceiptStore {
+    dir: PathBuf,
+}
+
+impl ReceiptStore {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// Directory of the receipts of a release in a namespace
+    fn release_dir(&self, release: &str, namespace: Option<&str>) -> PathBuf {
+        self.dir
+            .join(namespace.unwrap_or(NO_NAMESPACE_DIR))
+            .join(release)
+    }
+
+    pub(crate) fn write(&self, receipt: &Receipt) -> Result<(), HelmError> {
+        let dir = self.release_dir(&receipt.release, receipt.namespace.as_deref());
+        fs::create_dir_all(&dir).map_err(|source| HelmError::Io {
+            path: dir.clone(),
+            source,
+        })?;
+        let millis = receipt
+            .recorded_at
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let path = dir.join(format!(
+            "{:020}-{:06}.json",
+            millis,
+            COUNTER.fetch_add(1, Ordering::Relaxed) % 1_000_000
+        ));
+        let json = serde_json::to_vec_pretty(receipt)?;
+        fs::write(&path, json).map_err(|source| HelmError::Io { path, source })
+    }
+
+    /// receipts of a release in a namespace, oldest first
+    pub(crate) fn receipts_for(
+        &self,
+        release: &str,
+        namespace: Option<&str>,
+    ) -> Result<Vec<Receipt>, HelmError> {
+        let dir = self.release_dir(release, namespace);
+        if !dir.is_dir() {
+            return Ok(vec![]);
+        }
+        let io_error = |path: &Path| {
+            let path = path.to_path_buf();
+            move |source| HelmError::Io { path, source }
+        };
+        let mut paths: Vec<PathBuf> = fs::read_dir(&dir)
+            .map_err(io_error(&dir))?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+        paths
+            .iter()
+            .map(|path| {
+                let json = fs::read(path).map_err(io_error(path))?;
+                Ok(serde_json::from_slice(&json)?)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_receipt_store() {
+        let dir = TempDir::new().unwrap();
+        let store = ReceiptStore::new(dir.path().to_path_buf());
+        assert!(store.receipts_for("fluvio", None).unwrap().is_empty());
+
+        let mut install = Receipt::new(
+            "fluvio",
+            Some("default".to_string()),
+            ReceiptOperation::Install,
+            vec!["helm".to_string(), "install".to_string()],
+        );
+        install.chart = Some("fluvio/fluvio-app".to_string());
+        let uninstall = Receipt::new(
+            "fluvio",
+            Some("default".to_string()),
+            ReceiptOperation::Uninstall,
+            vec![],
+        );
+        store.write(&install).unwrap();
+        store.write(&uninstall).unwrap();
+        // a release of the same name in another namespace
+        let staging = Receipt::new(
+            "fluvio",
+            Some("staging".to_string()),
+            ReceiptOperation::Install,
+            vec![],
+        );
+        store.write(&staging).unwrap();
+        let unscoped = Receipt::new("fluvio", None, ReceiptOperation::Install, vec![]);
+        store.write(&unscoped).unwrap();
+        store
+            .write(&Receipt::new(
+                "other",
+                None,
+                ReceiptOperation::Install,
+                vec![],
+            ))
+            .unwrap();
+
+        assert_eq!(
+            store.receipts_for("fluvio", Some("default")).unwrap(),
+            vec![install, uninstall]
+        );
+        assert_eq!(
+            store.receipts_for("fluvio", Some("staging")).unwrap(),
+            vec![staging]
+        );
+        assert_eq!(store.receipts_for("fluvio", None).unwrap(), vec![unscoped]);
+    }
+}
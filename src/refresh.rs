@@ -0,0 +1,71 @@
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+use tracing::{debug, warn};
+
+use crate::HelmClient;
+
+/// Background worker refreshing the repo indexes on an interval.
+///
+/// Long running services can resolve chart versions against reasonably
+/// fresh indexes without running `helm repo update` on the request path.
+/// The worker stops when dropped.
+#[derive(Debug)]
+pub struct RepoRefresher {
+    state: Arc<RefreshState>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Debug, Default)]
+struct RefreshState {
+    last_refreshed: Mutex<Option<SystemTime>>,
+    stopped: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl RepoRefresher {
+    /// Starts refreshing immediately and then every `interval`
+    pub fn spawn(client: Arc<HelmClient>, interval: Duration) -> Self {
+        let state = Arc::new(RefreshState::default());
+        let worker_state = state.clone();
+        let handle = thread::spawn(move || loop {
+            match client.repo_update() {
+                Ok(()) => {
+                    debug!("repo indexes refreshed");
+                    *worker_state.last_refreshed.lock().unwrap() = Some(SystemTime::now());
+                }
+                Err(err) => warn!("failed to refresh repo indexes: {}", err),
+            }
+
+            let stopped = worker_state.stopped.lock().unwrap();
+            let (stopped, _) = worker_state
+                .wake
+                .wait_timeout_while(stopped, interval, |stopped| !*stopped)
+                .unwrap();
+            if *stopped {
+                break;
+            }
+        });
+
+        Self {
+            state,
+            handle: Some(handle),
+        }
+    }
+
+    /// When the indexes were last refreshed successfully
+    pub fn last_refreshed(&self) -> Option<SystemTime> {
+        *self.state.last_refreshed.lock().unwrap()
+    }
+}
+
+impl Drop for RepoRefresher {
+    fn drop(&mut self) {
+        *self.state.stopped.lock().unwrap() = true;
+        self.state.wake.notify_all();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::borrow::Cow;
+use std::process::Command;
+
+use crate::RepoCredentials;
+
+/// Registry Login Argument, authenticates helm against an OCI registry
+#[derive(Debug)]
+pub struct RegistryLoginArg<'a> {
+    pub host: Cow<'a, str>,
+    pub credentials: Option<RepoCredentials>,
+    pub insecure: bool,
+}
+
+impl<'a> RegistryLoginArg<'a> {
+    /// log in to `host`, e.g. `ghcr.io` or `localhost:5000`
+    pub fn new<H: Into<Cow<'a, str>>>(host: H) -> Self {
+        Self {
+            host: host.into(),
+            credentials: None,
+            insecure: false,
+        }
+    }
+
+    /// set credentials, defaults to those registered on the client for the host
+    pub fn credentials<U: Into<String>, P: Into<String>>(
+        mut self,
+        username: U,
+        password: P,
+    ) -> Self {
+        self.credentials = Some(RepoCredentials::new(username, password));
+        self
+    }
+
+    /// allow insecure tls connections to the registry
+    pub fn insecure(mut self) -> Self {
+        self.insecure = true;
+        self
+    }
+}
+
+impl From<RegistryLoginArg<'_>> for Command {
+    fn from(arg: RegistryLoginArg) -> Self {
+        let mut command = Command::new("helm");
+        command.args(["registry", "login", &arg.host]);
+
+        // the password is written to the input of helm by the client
+        if let Some(credentials) = &arg.credentials {
+            credentials.apply_stdin(&mut command);
+        }
+
+        if arg.insecure {
+            command.arg("--insecure");
+        }
+
+        command
+    }
+}
+
+/// Registry Logout Argument, removes the stored credentials of an OCI registry
+#[derive(Debug)]
+pub struct RegistryLogoutArg<'a> {
+    pub host: Cow<'a, str>,
+}
+
+impl<'a> RegistryLogoutArg<'a> {
+    pub fn new<H: Into<Cow<'a, str>>>(host: H) -> Self {
+        Self { host: host.into() }
+    }
+}
+
+impl From<RegistryLogoutArg<'_>> for Command {
+    fn from(arg: RegistryLogoutArg) -> Self {
+        let mut command = Command::new("helm");
+        command.args(["registry", "logout", &arg.host]);
+        command
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_commands() {
+        let login: Command = RegistryLoginArg::new("ghcr.io")
+            .credentials("fluvio", "secret")
+            .into();
+        let args: Vec<_> = login.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "registry",
+                "login",
+                "ghcr.io",
+                "--username",
+                "fluvio",
+                "--password-stdin"
+            ]
+        );
+
+        let logout: Command = RegistryLogoutArg::new("ghcr.io").into();
+        let args: Vec<_> = logout.get_args().collect();
+        assert_eq!(args, ["registry", "logout", "ghcr.io"]);
+    }
+}
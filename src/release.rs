@@ -0,0 +1,635 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde::Deserialize;
+
+use crate::hooks::parse_hooks;
+use crate::{apply_kube_flags, HelmError, HookManifest};
+
+/// Rollback Argument
+#[derive(Debug)]
+pub struct RollbackArg<'a> {
+    pub release: Cow<'a, str>,
+    pub revision: Option<u32>,
+    pub namespace: Option<Cow<'a, str>>,
+    pub wait: bool,
+    pub timeout: Option<Cow<'a, str>>,
+}
+
+impl<'a> RollbackArg<'a> {
+    pub fn new<R: Into<Cow<'a, str>>>(release: R) -> Self {
+        Self {
+            release: release.into(),
+            revision: None,
+            namespace: None,
+            wait: false,
+            timeout: None,
+        }
+    }
+
+    /// set revision to roll back to, defaults to the previous revision
+    pub fn revision(mut self, revision: u32) -> Self {
+        self.revision = Some(revision);
+        self
+    }
+
+    /// set namespace
+    pub fn namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// wait until resources are ready
+    pub fn wait(mut self) -> Self {
+        self.wait = true;
+        self
+    }
+
+    /// set timeout
+    pub fn timeout<S: Into<Cow<'a, str>>>(mut self, timeout: S) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+}
+
+impl From<RollbackArg<'_>> for Command {
+    fn from(arg: RollbackArg) -> Self {
+        let mut command = Command::new("helm");
+        command.args(["rollback", &arg.release]);
+
+        if let Some(revision) = arg.revision {
+            command.arg(revision.to_string());
+        }
+
+        if let Some(namespace) = &arg.namespace {
+            command.args(["--namespace", namespace]);
+        }
+
+        if arg.wait {
+            command.arg("--wait");
+        }
+
+        if let Some(timeout) = &arg.timeout {
+            command.args(["--timeout", timeout]);
+        }
+
+        command
+    }
+}
+
+/// History Argument
+#[derive(Debug)]
+pub struct HistoryArg<'a> {
+    pub release: Cow<'a, str>,
+    pub namespace: Option<Cow<'a, str>>,
+    pub kubeconfig: Option<PathBuf>,
+    pub kube_context: Option<Cow<'a, str>>,
+    pub max: Option<u32>,
+}
+
+impl<'a> HistoryArg<'a> {
+    pub fn new<R: Into<Cow<'a, str>>>(release: R) -> Self {
+        Self {
+            release: release.into(),
+            namespace: None,
+            kubeconfig: None,
+            kube_context: None,
+            max: None,
+        }
+    }
+
+    /// set namespace
+    pub fn namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set kubeconfig, overriding the one of the client
+    pub fn kubeconfig<P: Into<PathBuf>>(mut self, kubeconfig: P) -> Self {
+        self.kubeconfig = Some(kubeconfig.into());
+        self
+    }
+
+    /// set kube context, overriding the one of the client
+    pub fn kube_context<S: Into<Cow<'a, str>>>(mut self, context: S) -> Self {
+        self.kube_context = Some(context.into());
+        self
+    }
+
+    /// set maximum number of revisions to return
+    pub fn max(mut self, max: u32) -> Self {
+        self.max = Some(max);
+        self
+    }
+}
+
+impl From<HistoryArg<'_>> for Command {
+    fn from(arg: HistoryArg) -> Self {
+        let mut command = Command::new("helm");
+        command
+            .args(["history", &arg.release])
+            .args(["--output", "json"]);
+
+        if let Some(namespace) = &arg.namespace {
+            command.args(["--namespace", namespace]);
+        }
+
+        apply_kube_flags(
+            &mut command,
+            arg.kubeconfig.as_deref(),
+            arg.kube_context.as_deref(),
+        );
+
+        if let Some(max) = arg.max {
+            command.arg("--max").arg(max.to_string());
+        }
+
+        command
+    }
+}
+
+/// A revision of a release, as listed by `helm history`
+#[derive(Debug, Clone, Deserialize)]
+pub struct HistoryEntry {
+    /// The release revision
+    pub revision: u32,
+    /// Date/time when the revision was deployed
+    pub updated: String,
+    /// Status of the revision, e.g. `deployed`, `superseded` or `failed`
+    pub status: String,
+    /// The ID of the chart deployed in this revision
+    pub chart: String,
+    /// The version of the app deployed in this revision
+    pub app_version: String,
+    /// Description of the operation that produced the revision
+    pub description: String,
+}
+
+/// Outcome of a rollback
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RollbackResult {
+    /// The release name
+    pub release: String,
+    /// The revision whose configuration was restored
+    pub rolled_back_to: u32,
+    /// The revision the rollback created
+    pub revision: u32,
+}
+
+impl RollbackResult {
+    /// Reads the outcome from the history of the release after the
+    /// rollback, `None` if its latest revision is not a rollback
+    pub(crate) fn from_history(release: &str, history: &[HistoryEntry]) -> Option<Self> {
+        let latest = history.iter().max_by_key(|entry| entry.revision)?;
+        // helm describes the revision it creates as "Rollback to <revision>"
+        let rolled_back_to = latest
+            .description
+            .strip_prefix("Rollback to ")?
+            .trim()
+            .parse()
+            .ok()?;
+        Some(Self {
+            release: release.to_string(),
+            rolled_back_to,
+            revision: latest.revision,
+        })
+    }
+}
+
+/// Test Argument
+#[derive(Debug)]
+pub struct TestArg<'a> {
+    pub release: Cow<'a, str>,
+    pub namespace: Option<Cow<'a, str>>,
+    pub timeout: Option<Cow<'a, str>>,
+    pub filter: Vec<Cow<'a, str>>,
+}
+
+impl<'a> TestArg<'a> {
+    pub fn new<R: Into<Cow<'a, str>>>(release: R) -> Self {
+        Self {
+            release: release.into(),
+            namespace: None,
+            timeout: None,
+            filter: vec![],
+        }
+    }
+
+    /// set namespace
+    pub fn namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set timeout of each test, e.g. `5m0s`
+    pub fn timeout<S: Into<Cow<'a, str>>>(mut self, timeout: S) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+
+    /// only run the test with the given name, can be repeated
+    pub fn only<S: Into<Cow<'a, str>>>(mut self, name: S) -> Self {
+        self.filter
+            .push(Cow::Owned(format!("name={}", name.into())));
+        self
+    }
+}
+
+impl From<TestArg<'_>> for Command {
+    fn from(arg: TestArg) -> Self {
+        let mut command = Command::new("helm");
+        command.args(["test", &arg.release]).arg("--logs");
+
+        if let Some(namespace) = &arg.namespace {
+            command.args(["--namespace", namespace]);
+        }
+
+        if let Some(timeout) = &arg.timeout {
+            command.args(["--timeout", timeout]);
+        }
+
+        for filter in &arg.filter {
+            command.args(["--filter", filter]);
+        }
+
+        command
+    }
+}
+
+/// A test hook run by `helm test`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestSuite {
+    /// The test hook name
+    pub name: String,
+    /// The phase the test ended in, e.g. `Succeeded` or `Failed`
+    pub phase: String,
+}
+
+impl TestSuite {
+    /// whether the test succeeded
+    pub fn passed(&self) -> bool {
+        self.phase == "Succeeded"
+    }
+}
+
+/// Outcome of `helm test`
+#[derive(Debug, Clone, Default)]
+pub struct TestResult {
+    /// The tests which ran
+    pub suites: Vec<TestSuite>,
+    /// Logs of the test pods, by pod name
+    pub logs: Vec<(String, String)>,
+}
+
+impl TestResult {
+    /// whether every test succeeded
+    pub fn passed(&self) -> bool {
+        self.suites.iter().all(TestSuite::passed)
+    }
+
+    /// Parses the output of `helm test --logs`
+    pub(crate) fn parse(output: &str) -> Self {
+        let mut result = Self::default();
+        let mut in_logs = false;
+
+        for line in output.lines() {
+            if let Some(pod) = line.strip_prefix("POD LOGS: ") {
+                result.logs.push((pod.trim().to_string(), String::new()));
+                in_logs = true;
+            } else if in_logs {
+                if let Some((_, log)) = result.logs.last_mut() {
+                    log.push_str(line);
+                    log.push('\n');
+                }
+            } else if let Some(name) = line.strip_prefix("TEST SUITE:") {
+                let name = name.trim();
+                if name != "None" {
+                    result.suites.push(TestSuite {
+                        name: name.to_string(),
+                        phase: String::new(),
+                    });
+                }
+            } else if let Some(phase) = line.strip_prefix("Phase:") {
+                if let Some(suite) = result.suites.last_mut() {
+                    suite.phase = phase.trim().to_string();
+                }
+            }
+        }
+
+        // helm ends every pod log with an empty line
+        for (_, log) in &mut result.logs {
+            if log.ends_with("\n\n") {
+                log.pop();
+            }
+        }
+        result
+    }
+}
+
+/// Status Argument
+#[derive(Debug)]
+pub struct StatusArg<'a> {
+    pub release: Cow<'a, str>,
+    pub namespace: Option<Cow<'a, str>>,
+    pub kubeconfig: Option<PathBuf>,
+    pub kube_context: Option<Cow<'a, str>>,
+    pub revision: Option<u32>,
+}
+
+impl<'a> StatusArg<'a> {
+    pub fn new<R: Into<Cow<'a, str>>>(release: R) -> Self {
+        Self {
+            release: release.into(),
+            namespace: None,
+            kubeconfig: None,
+            kube_context: None,
+            revision: None,
+        }
+    }
+
+    /// set namespace
+    pub fn namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set kubeconfig, overriding the one of the client
+    pub fn kubeconfig<P: Into<PathBuf>>(mut self, kubeconfig: P) -> Self {
+        self.kubeconfig = Some(kubeconfig.into());
+        self
+    }
+
+    /// set kube context, overriding the one of the client
+    pub fn kube_context<S: Into<Cow<'a, str>>>(mut self, context: S) -> Self {
+        self.kube_context = Some(context.into());
+        self
+    }
+
+    /// set revision, defaults to the latest
+    pub fn revision(mut self, revision: u32) -> Self {
+        self.revision = Some(revision);
+        self
+    }
+}
+
+impl From<StatusArg<'_>> for Command {
+    fn from(arg: StatusArg) -> Self {
+        let mut command = Command::new("helm");
+        command
+            .args(["status", &arg.release])
+            .args(["--output", "json"]);
+
+        if let Some(namespace) = &arg.namespace {
+            command.args(["--namespace", namespace]);
+        }
+
+        apply_kube_flags(
+            &mut command,
+            arg.kubeconfig.as_deref(),
+            arg.kube_context.as_deref(),
+        );
+
+        if let Some(revision) = arg.revision {
+            command.arg("--revision").arg(revision.to_string());
+        }
+
+        command
+    }
+}
+
+/// Status of a release, as reported by `helm status`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseStatus {
+    /// The release name
+    pub name: String,
+    /// The namespace the release is installed in
+    pub namespace: String,
+    /// The release revision
+    #[serde(rename = "version")]
+    pub revision: u32,
+    /// Deployment details of the revision
+    pub info: ReleaseInfo,
+}
+
+/// Deployment details of a release revision
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseInfo {
+    /// Status of the revision, e.g. `deployed` or `failed`
+    pub status: String,
+    /// Date/time when the release was first deployed
+    #[serde(default)]
+    pub first_deployed: String,
+    /// Date/time when this revision was deployed
+    #[serde(default)]
+    pub last_deployed: String,
+    /// Description of the operation that produced the revision
+    #[serde(default)]
+    pub description: String,
+    /// The rendered NOTES.txt of the chart
+    #[serde(default)]
+    pub notes: String,
+}
+
+/// Release metadata, as returned by `helm get metadata`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReleaseMetadata {
+    /// The release name
+    pub name: String,
+    /// The chart name
+    pub chart: String,
+    /// The chart version
+    pub version: String,
+    /// The version of the app packaged by the chart
+    #[serde(default)]
+    pub app_version: String,
+    /// The namespace the release is installed in
+    pub namespace: String,
+    /// The release revision
+    pub revision: u32,
+    /// Status of the release, e.g. `deployed`
+    #[serde(default)]
+    pub status: String,
+    /// Date/time when the revision was deployed
+    #[serde(default)]
+    pub deployed_at: String,
+}
+
+/// Everything helm knows about a release, as returned by `helm get all`
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseDump {
+    /// The user supplied values, as YAML
+    pub values: String,
+    /// The values after merging with chart defaults, as YAML
+    pub computed_values: String,
+    /// The hook resources
+    pub hooks: Vec<HookManifest>,
+    /// The rendered manifest, as YAML
+    pub manifest: String,
+    /// The rendered NOTES.txt
+    pub notes: String,
+}
+
+impl ReleaseDump {
+    /// Splits the output of `helm get all` into its sections
+    pub(crate) fn parse(output: &str) -> Result<Self, HelmError> {
+        const HEADERS: [&str; 5] = [
+            "USER-SUPPLIED VALUES:",
+            "COMPUTED VALUES:",
+            "HOOKS:",
+            "MANIFEST:",
+            "NOTES:",
+        ];
+        let mut sections: [String; 5] = Default::default();
+        // the release summary before the first section is skipped
+        let mut current = None;
+
+        for line in output.lines() {
+            if let Some(index) = HEADERS.iter().position(|header| *header == line) {
+                current = Some(index);
+            } else if let Some(index) = current {
+                sections[index].push_str(line);
+                sections[index].push('\n');
+            }
+        }
+
+        let [values, computed_values, hooks, manifest, notes] = sections;
+        Ok(Self {
+            values,
+            computed_values,
+            hooks: parse_hooks(&hooks)?,
+            manifest,
+            notes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_history() {
+        const JSON_RESPONSE: &str = r#"[{"revision":1,"updated":"2021-03-17T08:42:54.546347741Z","status":"superseded","chart":"fluvio-app-0.9.0","app_version":"0.9.0","description":"Install complete"},{"revision":2,"updated":"2021-03-18T10:12:01.12345Z","status":"failed","chart":"fluvio-app-0.9.1","app_version":"0.9.1","description":"Upgrade \"fluvio\" failed: timed out waiting for the condition"}]"#;
+        let history: Vec<HistoryEntry> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].revision, 1);
+        assert_eq!(history[1].status, "failed");
+        assert_eq!(history[1].chart, "fluvio-app-0.9.1");
+    }
+
+    #[test]
+    fn test_parse_status() {
+        const JSON_RESPONSE: &str = r#"{"name":"fluvio","info":{"first_deployed":"2021-03-17T08:42:54.546347741Z","last_deployed":"2021-03-18T10:12:01.12345Z","deleted":"","description":"Upgrade complete","status":"deployed","notes":"Fluvio is installed"},"config":{},"version":2,"namespace":"default"}"#;
+        let status: ReleaseStatus =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        assert_eq!(status.name, "fluvio");
+        assert_eq!(status.revision, 2);
+        assert_eq!(status.info.status, "deployed");
+        assert_eq!(status.info.notes, "Fluvio is installed");
+    }
+
+    #[test]
+    fn test_parse_metadata() {
+        const JSON_RESPONSE: &str = r#"{"name":"fluvio","chart":"fluvio-app","version":"0.9.2","appVersion":"0.9.2","namespace":"default","revision":3,"status":"deployed","deployedAt":"2021-03-18T10:12:01.12345Z"}"#;
+        let metadata: ReleaseMetadata =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        assert_eq!(metadata.chart, "fluvio-app");
+        assert_eq!(metadata.app_version, "0.9.2");
+        assert_eq!(metadata.revision, 3);
+        assert_eq!(metadata.deployed_at, "2021-03-18T10:12:01.12345Z");
+    }
+
+    #[test]
+    fn test_parse_release_dump() {
+        const OUTPUT: &str = r#"NAME: fluvio
+LAST DEPLOYED: Wed Mar 17 08:42:54 2021
+NAMESPACE: default
+STATUS: deployed
+REVISION: 1
+TEST SUITE: None
+USER-SUPPLIED VALUES:
+image:
+  tag: 0.9.2
+
+COMPUTED VALUES:
+image:
+  registry: infinyon
+  tag: 0.9.2
+
+HOOKS:
+---
+# Source: fluvio-app/templates/pre-install.yaml
+apiVersion: batch/v1
+kind: Job
+metadata:
+  name: fluvio-pre-install
+  annotations:
+    "helm.sh/hook": pre-install
+MANIFEST:
+---
+# Source: fluvio-app/templates/service.yaml
+apiVersion: v1
+kind: Service
+metadata:
+  name: fluvio-sc
+
+NOTES:
+Fluvio is installed
+"#;
+        let dump = ReleaseDump::parse(OUTPUT).expect("can not parse dump");
+        assert_eq!(dump.values, "image:\n  tag: 0.9.2\n\n");
+        assert!(dump.computed_values.contains("registry: infinyon"));
+        assert_eq!(dump.hooks.len(), 1);
+        assert_eq!(dump.hooks[0].name, "fluvio-pre-install");
+        assert!(dump.manifest.contains("kind: Service"));
+        assert_eq!(dump.notes, "Fluvio is installed\n");
+    }
+
+    #[test]
+    fn test_parse_test_result() {
+        const OUTPUT: &str = r#"NAME: fluvio
+LAST DEPLOYED: Wed Mar 17 08:42:54 2021
+NAMESPACE: default
+STATUS: deployed
+REVISION: 1
+TEST SUITE:     fluvio-test-connection
+Last Started:   Wed Mar 17 08:50:01 2021
+Last Completed: Wed Mar 17 08:50:05 2021
+Phase:          Succeeded
+TEST SUITE:     fluvio-test-produce
+Last Started:   Wed Mar 17 08:50:05 2021
+Last Completed: Wed Mar 17 08:50:09 2021
+Phase:          Failed
+NOTES:
+Fluvio is installed
+POD LOGS: fluvio-test-connection
+connected to fluvio-sc:9003
+
+POD LOGS: fluvio-test-produce
+producing to topic test
+error: topic not found
+
+"#;
+        let result = TestResult::parse(OUTPUT);
+        assert_eq!(result.suites.len(), 2);
+        assert!(result.suites[0].passed());
+        assert_eq!(result.suites[1].phase, "Failed");
+        assert!(!result.passed());
+        assert_eq!(
+            result.logs,
+            vec![
+                (
+                    "fluvio-test-connection".to_string(),
+                    "connected to fluvio-sc:9003\n".to_string()
+                ),
+                (
+                    "fluvio-test-produce".to_string(),
+                    "producing to topic test\nerror: topic not found\n".to_string()
+                ),
+            ]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::process::Command;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::HelmError;
+
+/// Schemes helm (and its common getter plugins) know how to fetch from
+const SUPPORTED_SCHEMES: &[&str] = &["http", "https", "oci", "s3", "gs", "file"];
+
+/// Location of a chart repository or OCI registry.
+///
+/// The scheme is validated on construction, so a typo such as a missing
+/// `https://` is reported here instead of as an index fetch error from helm.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepoUrl(String);
+
+impl RepoUrl {
+    pub fn new<S: Into<String>>(url: S) -> Result<Self, HelmError> {
+        let url = url.into();
+        let invalid = |reason: &str| HelmError::InvalidRepoUrl {
+            url: url.clone(),
+            reason: reason.to_string(),
+        };
+
+        let (scheme, rest) = url
+            .split_once("://")
+            .ok_or_else(|| invalid("missing scheme, expected e.g. https:// or oci://"))?;
+
+        if !SUPPORTED_SCHEMES.contains(&scheme) {
+            return Err(invalid(&format!(
+                "unsupported scheme '{}', expected one of: {}",
+                scheme,
+                SUPPORTED_SCHEMES.join(", ")
+            )));
+        }
+
+        if rest.is_empty() || (rest.starts_with('/') && scheme != "file") {
+            return Err(invalid("missing host"));
+        }
+
+        Ok(Self(url))
+    }
+
+    /// the url scheme, e.g. `https` or `oci`
+    pub fn scheme(&self) -> &str {
+        self.0.split_once("://").map(|(s, _)| s).unwrap_or_default()
+    }
+
+    /// the host (and port) part of the url, without any user info
+    pub fn host(&self) -> &str {
+        let rest = self.0.split_once("://").map(|(_, r)| r).unwrap_or_default();
+        let authority = rest.split('/').next().unwrap_or_default();
+        authority
+            .rsplit_once('@')
+            .map(|(_, host)| host)
+            .unwrap_or(authority)
+    }
+
+    /// true if this points to an OCI registry
+    pub fn is_oci(&self) -> bool {
+        self.scheme() == "oci"
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for RepoUrl {
+    type Err = HelmError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl fmt::Display for RepoUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for RepoUrl {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Username and password used to authenticate against a repository or registry
+#[derive(Clone)]
+pub struct RepoCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl RepoCredentials {
+    pub fn new<U: Into<String>, P: Into<String>>(username: U, password: P) -> Self {
+        Self {
+            username: username.into(),
+            password: password.into(),
+        }
+    }
+
+    /// add `--username` and `--password` flags
+    pub(crate) fn apply(&self, command: &mut Command) {
+        command
+            .args(["--username", &self.username])
+            .args(["--password", &self.password]);
+    }
+
+    /// add `--username` and `--password-stdin` flags, for commands reading
+    /// the password from their input so it doesn't show in the process list
+    pub(crate) fn apply_stdin(&self, command: &mut Command) {
+        command
+            .args(["--username", &self.username])
+            .arg("--password-stdin");
+    }
+}
+
+impl fmt::Debug for RepoCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepoCredentials")
+            .field("username", &self.username)
+            .field("password", &"****")
+            .finish()
+    }
+}
+
+/// Repo add Argument
+#[derive(Debug)]
+pub struct RepoAddArg<'a> {
+    pub name: Cow<'a, str>,
+    pub url: RepoUrl,
+    pub credentials: Option<RepoCredentials>,
+    pub force_update: bool,
+}
+
+impl<'a> RepoAddArg<'a> {
+    pub fn new<N: Into<Cow<'a, str>>>(name: N, url: RepoUrl) -> Self {
+        Self {
+            name: name.into(),
+            url,
+            credentials: None,
+            force_update: false,
+        }
+    }
+
+    /// set basic auth credentials
+    pub fn credentials<U: Into<String>, P: Into<String>>(
+        mut self,
+        username: U,
+        password: P,
+    ) -> Self {
+        self.credentials = Some(RepoCredentials::new(username, password));
+        self
+    }
+
+    /// replace the repo if it already exists
+    pub fn force_update(mut self) -> Self {
+        self.force_update = true;
+        self
+    }
+}
+
+impl From<RepoAddArg<'_>> for Command {
+    fn from(arg: RepoAddArg) -> Self {
+        let mut command = Command::new("helm");
+        command.args(["repo", "add", &arg.name, arg.url.as_str()]);
+
+        // the password is written to the input of helm by the client
+        if let Some(credentials) = &arg.credentials {
+            credentials.apply_stdin(&mut command);
+        }
+
+        if arg.force_update {
+            command.arg("--force-update");
+        }
+
+        command
+    }
+}
+
+/// Reason `helm repo add` failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoAddFailure {
+    /// The repository rejected the credentials, or they are missing
+    Unauthorized,
+    /// No chart index was found at the url, usually a wrong url
+    IndexNotFound,
+    /// The repository certificate could not be verified
+    TlsVerification,
+    /// The repository did not answer in time
+    Timeout,
+    /// Any other failure
+    Other,
+}
+
+impl RepoAddFailure {
+    /// Classifies a failure from the stderr of `helm repo add`
+    pub fn from_stderr(stderr: &str) -> Self {
+        const PATTERNS: &[(&str, RepoAddFailure)] = &[
+            ("401 Unauthorized", RepoAddFailure::Unauthorized),
+            ("403 Forbidden", RepoAddFailure::Unauthorized),
+            ("404 Not Found", RepoAddFailure::IndexNotFound),
+            ("x509:", RepoAddFailure::TlsVerification),
+            ("tls: ", RepoAddFailure::TlsVerification),
+            ("Client.Timeout exceeded", RepoAddFailure::Timeout),
+            ("i/o timeout", RepoAddFailure::Timeout),
+            ("context deadline exceeded", RepoAddFailure::Timeout),
+        ];
+        PATTERNS
+            .iter()
+            .find(|(pattern, _)| stderr.contains(pattern))
+            .map(|(_, failure)| *failure)
+            .unwrap_or(Self::Other)
+    }
+}
+
+impl fmt::Display for RepoAddFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let text = match self {
+            Self::Unauthorized => "unauthorized, check the repository credentials",
+            Self::IndexNotFound => "no chart index found, check the repository url",
+            Self::TlsVerification => "certificate verification failed",
+            Self::Timeout => "timed out",
+            Self::Other => "unclassified error",
+        };
+        f.write_str(text)
+    }
+}
+
+/// A configured chart repository, as listed by `helm repo list`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct HelmRepo {
+    pub name: String,
+    pub url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_url_validation() {
+        assert!(RepoUrl::new("https://charts.fluvio.io").is_ok());
+        assert!(RepoUrl::new("oci://ghcr.io/infinyon/charts").is_ok());
+        assert!(RepoUrl::new("s3://bucket/charts").is_ok());
+        assert!(RepoUrl::new("file:///tmp/charts").is_ok());
+
+        assert!(RepoUrl::new("charts.fluvio.io").is_err());
+        assert!(RepoUrl::new("htps://charts.fluvio.io").is_err());
+        assert!(RepoUrl::new("https://").is_err());
+
+        let url = RepoUrl::new("oci://ghcr.io/infinyon/charts").unwrap();
+        assert_eq!(url.scheme(), "oci");
+        assert_eq!(url.host(), "ghcr.io");
+        assert!(url.is_oci());
+
+        let url = RepoUrl::new("https://user@charts.local:8443/stable").unwrap();
+        assert_eq!(url.host(), "charts.local:8443");
+    }
+
+    #[test]
+    fn test_repo_add_failure() {
+        let cases = [
+            ("Error: looks like \"https://charts.fluvio.io\" is not a valid chart repository or cannot be reached: failed to fetch https://charts.fluvio.io/index.yaml : 401 Unauthorized", RepoAddFailure::Unauthorized),
+            ("Error: looks like \"https://fluvio.io\" is not a valid chart repository or cannot be reached: failed to fetch https://fluvio.io/index.yaml : 404 Not Found", RepoAddFailure::IndexNotFound),
+            ("Error: looks like \"https://charts.local\" is not a valid chart repository or cannot be reached: Get \"https://charts.local/index.yaml\": x509: certificate signed by unknown authority", RepoAddFailure::TlsVerification),
+            ("Error: looks like \"https://charts.local\" is not a valid chart repository or cannot be reached: Get \"https://charts.local/index.yaml\": dial tcp 10.0.0.1:443: i/o timeout", RepoAddFailure::Timeout),
+            ("Error: repository name (fluvio) already exists", RepoAddFailure::Other),
+        ];
+        for (stderr, failure) in cases {
+            assert_eq!(RepoAddFailure::from_stderr(stderr), failure, "{}", stderr);
+        }
+    }
+
+    #[test]
+    fn test_parse_repo_list() {
+        const JSON_RESPONSE: &str = r#"[{"name":"fluvio","url":"https://charts.fluvio.io"},{"name":"stable","url":"https://charts.helm.sh/stable"}]"#;
+        let repos: Vec<HelmRepo> = serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        assert_eq!(
+            repos[0],
+            HelmRepo {
+                name: "fluvio".to_string(),
+                url: "https://charts.fluvio.io".to_string(),
+            }
+        );
+        assert_eq!(repos[1].name, "stable");
+    }
+}
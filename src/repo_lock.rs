@@ -0,0 +1,98 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::HelmError;
+
+/// Serializes the repo config mutations of every client in the process
+static REPO_CONFIG: Mutex<()> = Mutex::new(());
+
+/// Interval between attempts to take the lock file
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait for the lock file before giving up
+pub(crate) const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Holds the repo config until dropped
+pub(crate) struct RepoConfigGuard {
+    /// The lock file, locked until closed
+    _lock_file: Option<File>,
+    _guard: MutexGuard<'static, ()>,
+}
+
+/// Takes the in-process lock of the repo config and, when given, the lock
+/// file shared with other processes.
+///
+/// The lock file is locked with an OS advisory lock (`flock` or
+/// `LockFileEx`), retrying until `timeout`. The file is kept afterwards,
+/// and the OS releases the lock if the process dies, so no stale lock is
+/// left behind.
+pub(crate) fn lock_repo_config(
+    lock_file: Option<&Path>,
+    timeout: Duration,
+) -> Result<RepoConfigGuard, HelmError> {
+    // a panic while holding the lock leaves nothing to recover
+    let guard = REPO_CONFIG.lock().unwrap_or_else(PoisonError::into_inner);
+    let lock_file = match lock_file {
+        Some(path) => Some(acquire_lock_file(path, timeout)?),
+        None => None,
+    };
+    Ok(RepoConfigGuard {
+        _lock_file: lock_file,
+        _guard: guard,
+    })
+}
+
+fn acquire_lock_file(path: &Path, timeout: Duration) -> Result<File, HelmError> {
+    let io_error = |source| HelmError::Io {
+        path: path.to_path_buf(),
+        source,
+    };
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+        .map_err(io_error)?;
+
+    let started = Instant::now();
+    loop {
+        match file.try_lock() {
+            Ok(()) => return Ok(file),
+            Err(TryLockError::WouldBlock) => {
+                if started.elapsed() >= timeout {
+                    return Err(HelmError::RepoConfigLocked(path.to_path_buf()));
+                }
+                thread::sleep(LOCK_RETRY_INTERVAL);
+            }
+            Err(TryLockError::Error(source)) => return Err(io_error(source)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    #[test]
+    fn test_lock_repo_config() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("repositories.lock");
+
+        let guard = lock_repo_config(Some(&path), DEFAULT_LOCK_TIMEOUT).unwrap();
+        assert!(path.exists());
+        // another process holding the lock file
+        assert!(matches!(
+            acquire_lock_file(&path, Duration::from_millis(200)),
+            Err(HelmError::RepoConfigLocked(_))
+        ));
+        drop(guard);
+
+        // the file is kept, only its lock is released
+        assert!(path.exists());
+        acquire_lock_file(&path, Duration::ZERO).unwrap();
+    }
+}
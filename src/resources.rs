@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::hooks::parse_yaml_documents;
+use crate::HelmError;
+
+/// Label charts put on their resources to mark the release they belong to
+pub(crate) const INSTANCE_LABEL: &str = "app.kubernetes.io/instance";
+
+/// Reference to a Kubernetes object
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+pub struct ResourceRef {
+    #[serde(rename = "apiVersion")]
+    pub api_version: String,
+    pub kind: String,
+    pub name: String,
+    /// The object namespace, `None` for cluster scoped objects or objects
+    /// created in the release namespace
+    pub namespace: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Object {
+    #[serde(rename = "apiVersion")]
+    api_version: String,
+    kind: String,
+    metadata: ObjectMetadata,
+}
+
+#[derive(Deserialize)]
+struct ObjectMetadata {
+    name: String,
+    #[serde(default)]
+    namespace: Option<String>,
+}
+
+impl From<Object> for ResourceRef {
+    fn from(object: Object) -> Self {
+        Self {
+            api_version: object.api_version,
+            kind: object.kind,
+            name: object.metadata.name,
+            namespace: object.metadata.namespace,
+        }
+    }
+}
+
+/// The objects rendered in a release manifest
+#[derive(Debug, Clone, Default)]
+pub struct ResourceInventory {
+    pub resources: Vec<ResourceRef>,
+}
+
+impl ResourceInventory {
+    /// Parses a release manifest
+    pub(crate) fn parse(manifest: &str) -> Result<Self, HelmError> {
+        let mut resources = parse_yaml_documents(manifest)?
+            .into_iter()
+            .map(|document| Ok(serde_yaml::from_value::<Object>(document)?.into()))
+            .collect::<Result<Vec<ResourceRef>, HelmError>>()?;
+        resources.sort();
+        Ok(Self { resources })
+    }
+
+    /// number of objects per kind
+    pub fn counts(&self) -> BTreeMap<&str, usize> {
+        let mut counts = BTreeMap::new();
+        for resource in &self.resources {
+            *counts.entry(resource.kind.as_str()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// objects of the given kind
+    pub fn of_kind(&self, kind: &str) -> Vec<&ResourceRef> {
+        self.resources
+            .iter()
+            .filter(|resource| resource.kind == kind)
+            .collect()
+    }
+
+    /// the distinct kinds in the manifest
+    pub fn kinds(&self) -> Vec<&str> {
+        self.counts().into_keys().collect()
+    }
+
+    /// whether the manifest holds the object, objects without namespace
+    /// being in `release_namespace`
+    fn contains(&self, object: &ResourceRef, release_namespace: &str) -> bool {
+        self.resources.iter().any(|resource| {
+            resource.kind == object.kind
+                && resource.name == object.name
+                && (object.namespace.is_none()
+                    || resource.namespace.as_deref().unwrap_or(release_namespace)
+                        == object.namespace.as_deref().unwrap_or_default())
+        })
+    }
+
+    /// Objects from a `kubectl get --output json` list which are not in
+    /// the manifest
+    pub(crate) fn orphans(
+        &self,
+        cluster_objects: &[u8],
+        release_namespace: &str,
+    ) -> Result<Vec<ResourceRef>, HelmError> {
+        #[derive(Deserialize)]
+        struct List {
+            items: Vec<Object>,
+        }
+
+        let list: List = serde_json::from_slice(cluster_objects)?;
+        let mut orphans: Vec<ResourceRef> = list
+            .items
+            .into_iter()
+            .map(ResourceRef::from)
+            .filter(|object| !self.contains(object, release_namespace))
+            .collect();
+        orphans.sort();
+        Ok(orphans)
+    }
+}
+
+/// Parses the objects `helm uninstall` reports as kept due to their
+/// resource policy, e.g. `[Secret] fluvio-tls`
+pub(crate) fn parse_kept_resources(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip_while(|line| !line.starts_with("These resources were kept"))
+        .skip(1)
+        .map(str::trim)
+        .take_while(|line| line.starts_with('['))
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"---
+# Source: fluvio-app/templates/service.yaml
+apiVersion: v1
+kind: Service
+metadata:
+  name: fluvio-sc
+---
+# Source: fluvio-app/templates/deployment.yaml
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: fluvio-sc
+---
+# Source: fluvio-app/templates/service-public.yaml
+apiVersion: v1
+kind: Service
+metadata:
+  name: fluvio-sc-public
+  namespace: fluvio
+"#;
+
+    #[test]
+    fn test_resource_inventory() {
+        let inventory = ResourceInventory::parse(MANIFEST).expect("can not parse manifest");
+        assert_eq!(inventory.resources.len(), 3);
+        assert_eq!(inventory.counts()["Service"], 2);
+        assert_eq!(inventory.kinds(), vec!["Deployment", "Service"]);
+        assert_eq!(inventory.of_kind("Deployment")[0].name, "fluvio-sc");
+    }
+
+    #[test]
+    fn test_orphans() {
+        const CLUSTER: &str = r#"{"apiVersion":"v1","kind":"List","items":[
+            {"apiVersion":"v1","kind":"Service","metadata":{"name":"fluvio-sc","namespace":"fluvio"}},
+            {"apiVersion":"v1","kind":"Service","metadata":{"name":"fluvio-sc-public","namespace":"fluvio"}},
+            {"apiVersion":"apps/v1","kind":"Deployment","metadata":{"name":"fluvio-sc","namespace":"fluvio"}},
+            {"apiVersion":"batch/v1","kind":"Job","metadata":{"name":"fluvio-pre-install","namespace":"fluvio"}}
+        ]}"#;
+        let inventory = ResourceInventory::parse(MANIFEST).expect("can not parse manifest");
+        let orphans = inventory
+            .orphans(CLUSTER.as_bytes(), "fluvio")
+            .expect("can not parse objects");
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].kind, "Job");
+        assert_eq!(orphans[0].name, "fluvio-pre-install");
+    }
+
+    #[test]
+    fn test_parse_kept_resources() {
+        const OUTPUT: &str = r#"These resources were kept due to the resource policy:
+[Secret] fluvio-tls
+[PersistentVolumeClaim] fluvio-data
+
+release "fluvio" uninstalled
+"#;
+        assert_eq!(
+            parse_kept_resources(OUTPUT),
+            vec!["[Secret] fluvio-tls", "[PersistentVolumeClaim] fluvio-data"]
+        );
+        assert!(parse_kept_resources("release \"fluvio\" uninstalled\n").is_empty());
+    }
+}
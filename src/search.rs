@@ -0,0 +1,159 @@
+use std::borrow::Cow;
+use std::process::Command;
+
+use serde::Deserialize;
+
+/// Search Argument, looks charts up in the configured repos with `helm search repo`
+#[derive(Debug, Clone)]
+pub struct SearchRepoArg<'a> {
+    pub term: Cow<'a, str>,
+    pub version: Option<Cow<'a, str>>,
+    pub regexp: bool,
+    pub devel: bool,
+    pub versions: bool,
+    pub limit: Option<usize>,
+}
+
+impl<'a> SearchRepoArg<'a> {
+    pub fn new<S: Into<Cow<'a, str>>>(term: S) -> Self {
+        Self {
+            term: term.into(),
+            version: None,
+            regexp: false,
+            devel: false,
+            versions: false,
+            limit: None,
+        }
+    }
+
+    /// set version constraint, e.g. `^0.9`, defaults to the latest version
+    pub fn version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// interpret the term as a regular expression
+    pub fn regexp(mut self) -> Self {
+        self.regexp = true;
+        self
+    }
+
+    /// include development versions
+    pub fn devel(mut self) -> Self {
+        self.devel = true;
+        self
+    }
+
+    /// list every matching version instead of only the latest
+    pub fn versions(mut self) -> Self {
+        self.versions = true;
+        self
+    }
+
+    /// keep at most `limit` results
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// the same search for another term
+    pub(crate) fn with_term<S: Into<Cow<'a, str>>>(&self, term: S) -> Self {
+        Self {
+            term: term.into(),
+            ..self.clone()
+        }
+    }
+
+    /// the `helm search repo` command, with json output
+    pub(crate) fn command(&self) -> Command {
+        let mut command = Command::new("helm");
+        command.args(["search", "repo", &self.term]);
+        if let Some(version) = &self.version {
+            command.args(["--version", version]);
+        }
+        if self.regexp {
+            command.arg("--regexp");
+        }
+        if self.devel {
+            command.arg("--devel");
+        }
+        if self.versions {
+            command.arg("--versions");
+        }
+        command.args(["--output", "json"]);
+        command
+    }
+}
+
+/// A chart found on Artifact Hub, or another hub, by `helm search hub`
+#[derive(Debug, Clone, Deserialize)]
+pub struct HubResult {
+    /// The chart page on the hub
+    pub url: String,
+    /// The latest chart version
+    pub version: String,
+    /// The version of the app packaged by the chart
+    #[serde(default)]
+    pub app_version: String,
+    #[serde(default)]
+    pub description: String,
+    /// The repository publishing the chart
+    #[serde(default)]
+    pub repository: Option<HubRepository>,
+}
+
+/// The repository of a chart found on a hub
+#[derive(Debug, Clone, Deserialize)]
+pub struct HubRepository {
+    pub name: String,
+    pub url: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_repo_command() {
+        let arg = SearchRepoArg::new("fluvio/")
+            .version("^0.9")
+            .regexp()
+            .devel()
+            .versions()
+            .limit(5);
+        let command = arg.command();
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(
+            args,
+            [
+                "search",
+                "repo",
+                "fluvio/",
+                "--version",
+                "^0.9",
+                "--regexp",
+                "--devel",
+                "--versions",
+                "--output",
+                "json"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_hub_results() {
+        const JSON_RESPONSE: &str = r#"[{"url":"https://artifacthub.io/packages/helm/fluvio/fluvio-app","version":"0.11.0","app_version":"0.11.0","description":"A Helm chart for Fluvio","repository":{"url":"https://charts.fluvio.io","name":"fluvio"}},{"url":"https://artifacthub.io/packages/helm/other/fluvio","version":"1.0.0","app_version":"","description":""}]"#;
+        let results: Vec<HubResult> =
+            serde_json::from_str(JSON_RESPONSE).expect("can not parse json");
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].version, "0.11.0");
+        assert_eq!(
+            results[0]
+                .repository
+                .as_ref()
+                .map(|repo| repo.name.as_str()),
+            Some("fluvio")
+        );
+        assert!(results[1].repository.is_none());
+    }
+}
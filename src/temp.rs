@@ -0,0 +1,44 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::HelmError;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Scratch directory removed when dropped
+#[derive(Debug)]
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    pub(crate) fn new() -> Result<Self, HelmError> {
+        Self::new_in(&env::temp_dir())
+    }
+
+    /// creates the scratch directory under `parent`, e.g. to rename files
+    /// out of it without crossing file systems
+    pub(crate) fn new_in(parent: &Path) -> Result<Self, HelmError> {
+        let path = parent.join(format!(
+            ".fluvio-helm-{}-{}",
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::create_dir_all(&path).map_err(|source| HelmError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(Self(path))
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
@@ -0,0 +1,191 @@
+use std::borrow::Cow;
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::ChartRef;
+
+/// Template Argument, renders a chart locally without contacting the cluster
+#[derive(Debug, Clone)]
+pub struct TemplateArg<'a> {
+    pub name: Cow<'a, str>,
+    pub chart: ChartRef,
+    pub version: Option<Cow<'a, str>>,
+    pub namespace: Option<Cow<'a, str>>,
+    pub opts: Vec<(Cow<'a, str>, Cow<'a, str>)>,
+    pub values: Vec<PathBuf>,
+    pub show_only: Vec<Cow<'a, str>>,
+    pub output_dir: Option<PathBuf>,
+    pub validate: bool,
+    pub kube_version: Option<Cow<'a, str>>,
+    pub api_versions: Vec<Cow<'a, str>>,
+}
+
+impl<'a> TemplateArg<'a> {
+    pub fn new<N: Into<Cow<'a, str>>, C: Into<ChartRef>>(name: N, chart: C) -> Self {
+        Self {
+            name: name.into(),
+            chart: chart.into(),
+            version: None,
+            namespace: None,
+            opts: vec![],
+            values: vec![],
+            show_only: vec![],
+            output_dir: None,
+            validate: false,
+            kube_version: None,
+            api_versions: vec![],
+        }
+    }
+
+    /// set chart version
+    pub fn version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.version = Some(version.into());
+        self
+    }
+
+    /// set namespace
+    pub fn namespace<S: Into<Cow<'a, str>>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// reset array of options
+    pub fn opts<I, K, V>(mut self, options: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<Cow<'a, str>>,
+        V: Into<Cow<'a, str>>,
+    {
+        self.opts = options
+            .into_iter()
+            .map(|(key, value)| (key.into(), value.into()))
+            .collect();
+        self
+    }
+
+    /// set a single option
+    pub fn opt<K: Into<Cow<'a, str>>, V: Into<Cow<'a, str>>>(mut self, key: K, value: V) -> Self {
+        self.opts.push((key.into(), value.into()));
+        self
+    }
+
+    /// set list of values
+    pub fn values(mut self, values: Vec<PathBuf>) -> Self {
+        self.values = values;
+        self
+    }
+
+    /// set one value
+    pub fn value(mut self, value: PathBuf) -> Self {
+        self.values.push(value);
+        self
+    }
+
+    /// only render the given template, e.g. `templates/service.yaml`
+    pub fn show_only<S: Into<Cow<'a, str>>>(mut self, template: S) -> Self {
+        self.show_only.push(template.into());
+        self
+    }
+
+    /// write rendered templates into files under `dir`
+    pub fn output_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.output_dir = Some(dir.into());
+        self
+    }
+
+    /// validate the manifests against the cluster the client points to
+    pub fn validate(mut self) -> Self {
+        self.validate = true;
+        self
+    }
+
+    /// set kubernetes version used for `Capabilities.KubeVersion`, e.g. `1.27.0`
+    pub fn kube_version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.kube_version = Some(version.into());
+        self
+    }
+
+    /// add an api version used for `Capabilities.APIVersions`, e.g. `monitoring.coreos.com/v1`
+    pub fn api_version<S: Into<Cow<'a, str>>>(mut self, version: S) -> Self {
+        self.api_versions.push(version.into());
+        self
+    }
+
+    pub fn template(&self) -> Command {
+        let mut command = Command::new("helm");
+        command.args(["template", &self.name]);
+        self.chart.apply(&mut command);
+
+        if let Some(namespace) = &self.namespace {
+            command.args(["--namespace", namespace]);
+        }
+
+        if let Some(version) = &self.version {
+            command.args(["--version", version]);
+        }
+
+        for value_path in &self.values {
+            command.arg("--values").arg(value_path);
+        }
+
+        for (key, val) in &self.opts {
+            command.arg("--set").arg(format!("{}={}", key, val));
+        }
+
+        for template in &self.show_only {
+            command.args(["--show-only", template]);
+        }
+
+        if let Some(dir) = &self.output_dir {
+            command.arg("--output-dir").arg(dir);
+        }
+
+        if self.validate {
+            command.arg("--validate");
+        }
+
+        if let Some(version) = &self.kube_version {
+            command.args(["--kube-version", version]);
+        }
+
+        for version in &self.api_versions {
+            command.args(["--api-versions", version]);
+        }
+
+        command
+    }
+}
+
+/// Extracts the files written by `helm template --output-dir`.
+///
+/// Helm reports a `wrote <path>` line for every document, so files holding
+/// several documents are only returned once.
+pub(crate) fn parse_written_paths(output: &str) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = vec![];
+    for line in output.lines() {
+        if let Some(path) = line.strip_prefix("wrote ") {
+            let path = PathBuf::from(path.trim());
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_written_paths() {
+        const OUTPUT: &str = "wrote out/fluvio-app/templates/service.yaml\nwrote out/fluvio-app/templates/deployment.yaml\nwrote out/fluvio-app/templates/service.yaml\n";
+        assert_eq!(
+            parse_written_paths(OUTPUT),
+            vec![
+                PathBuf::from("out/fluvio-app/templates/service.yaml"),
+                PathBuf::from("out/fluvio-app/templates/deployment.yaml"),
+            ]
+        );
+    }
+}
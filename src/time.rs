@@ -0,0 +1,138 @@
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Parses the timestamps helm prints, returning `None` if the format is
+/// not recognized.
+///
+/// Accepts both the Go default format used by `helm list`
+/// (`2021-03-17 08:42:54.546347741 +0000 UTC`) and RFC 3339 as used in
+/// JSON output (`2021-03-17T08:42:54.546347741Z`).
+pub(crate) fn parse_helm_timestamp(text: &str) -> Option<SystemTime> {
+    let text = text.trim();
+    let (date, rest) = text.split_at(text.find([' ', 'T'])?);
+    let rest = &rest[1..];
+
+    let mut date_parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let year = date_parts.next()??;
+    let month = date_parts.next()??;
+    let day = date_parts.next()??;
+
+    // split off the utc offset, either `Z`, `+hh:mm` or ` +hhmm ZONE`
+    let offset_start = rest.find(['Z', '+', '-', ' ']);
+    let (time, offset) = match offset_start {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+    let offset_seconds = parse_offset(offset.trim())?;
+
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let mut clock_parts = clock.splitn(3, ':').map(|part| part.parse::<i64>().ok());
+    let hour = clock_parts.next()??;
+    let minute = clock_parts.next()??;
+    let second = clock_parts.next()??;
+    let nanos = if fraction.is_empty() {
+        0
+    } else {
+        let digits: String = fraction.chars().take(9).collect();
+        digits.parse::<u32>().ok()? * 10u32.pow(9 - digits.len() as u32)
+    };
+
+    let seconds = days_from_civil(year, month, day) * 86_400 + hour * 3_600 + minute * 60 + second
+        - offset_seconds;
+    let seconds = u64::try_from(seconds).ok()?;
+    Some(UNIX_EPOCH + Duration::new(seconds, nanos))
+}
+
+/// Formats a duration as a Go duration, as accepted by helm's `--timeout`,
+/// e.g. `5m0s` or `1.5s`
+pub(crate) fn go_duration(duration: Duration) -> String {
+    let nanos = duration.subsec_nanos();
+    if duration.as_secs() == 0 {
+        return match nanos {
+            0 => "0s".to_string(),
+            nanos if nanos % 1_000_000 == 0 => format!("{}ms", nanos / 1_000_000),
+            nanos => format!("{}ns", nanos),
+        };
+    }
+
+    let secs = duration.as_secs();
+    let (hours, minutes, seconds) = (secs / 3_600, secs % 3_600 / 60, secs % 60);
+    let mut formatted = String::new();
+    if hours > 0 {
+        formatted.push_str(&format!("{}h", hours));
+    }
+    if hours > 0 || minutes > 0 {
+        formatted.push_str(&format!("{}m", minutes));
+    }
+    if nanos == 0 {
+        formatted.push_str(&format!("{}s", seconds));
+    } else {
+        let fraction = format!("{:09}", nanos);
+        formatted.push_str(&format!("{}.{}s", seconds, fraction.trim_end_matches('0')));
+    }
+    formatted
+}
+
+/// Parses a utc offset such as `Z`, `+02:00` or `+0000 UTC` into seconds
+fn parse_offset(offset: &str) -> Option<i64> {
+    let offset = offset.split_whitespace().next().unwrap_or("Z");
+    if offset == "Z" {
+        return Some(0);
+    }
+    let (sign, digits) = match offset.split_at(1) {
+        ("+", digits) => (1, digits),
+        ("-", digits) => (-1, digits),
+        _ => return None,
+    };
+    let digits = digits.replace(':', "");
+    if digits.len() != 4 {
+        return None;
+    }
+    let hours: i64 = digits[..2].parse().ok()?;
+    let minutes: i64 = digits[2..].parse().ok()?;
+    Some(sign * (hours * 3_600 + minutes * 60))
+}
+
+/// Days since the unix epoch for a proleptic Gregorian calendar date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_helm_timestamp() {
+        let expected = UNIX_EPOCH + Duration::new(1_615_970_574, 546_347_741);
+        assert_eq!(
+            parse_helm_timestamp("2021-03-17 08:42:54.546347741 +0000 UTC"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_helm_timestamp("2021-03-17T08:42:54.546347741Z"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_helm_timestamp("2021-03-17T10:42:54.546347741+02:00"),
+            Some(expected)
+        );
+        assert_eq!(parse_helm_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_go_duration() {
+        assert_eq!(go_duration(Duration::from_secs(300)), "5m0s");
+        assert_eq!(go_duration(Duration::from_secs(3_723)), "1h2m3s");
+        assert_eq!(go_duration(Duration::from_secs(3_600)), "1h0m0s");
+        assert_eq!(go_duration(Duration::from_millis(1_500)), "1.5s");
+        assert_eq!(go_duration(Duration::from_millis(250)), "250ms");
+        assert_eq!(go_duration(Duration::ZERO), "0s");
+    }
+}
@@ -0,0 +1,202 @@
+use std::ffi::OsStr;
+use std::fmt::Debug;
+use std::process::Command;
+
+/// Where the helm and kubectl commands of a client are executed.
+///
+/// The client builds commands for local binaries, a transport wraps them
+/// into the command actually spawned. Paths passed to the client, such as
+/// values files or pull destinations, are resolved where the command runs.
+pub trait Transport: Debug + Send + Sync {
+    /// Wraps a command built for the local binaries. The wrapped command
+    /// must pass its input on, helm reads passwords from it.
+    fn wrap(&self, command: &Command) -> Command;
+}
+
+/// Program, arguments and environment of a command, as an argv
+fn command_words(command: &Command) -> Vec<String> {
+    let lossy = |s: &OsStr| s.to_string_lossy().into_owned();
+    let env: Vec<String> = command
+        .get_envs()
+        .filter_map(|(key, value)| Some(format!("{}={}", lossy(key), lossy(value?))))
+        .collect();
+
+    let mut words = vec![];
+    if !env.is_empty() {
+        words.push("env".to_string());
+        words.extend(env);
+    }
+    words.push(lossy(command.get_program()));
+    words.extend(command.get_args().map(lossy));
+    words
+}
+
+/// Quotes a word for a POSIX shell
+fn shell_quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        word.to_string()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+/// Runs commands on a remote host with `ssh`, e.g. a bastion with helm
+/// installed and cluster access
+#[derive(Debug, Clone)]
+pub struct SshTransport {
+    pub destination: String,
+    pub options: Vec<String>,
+}
+
+impl SshTransport {
+    pub fn new<D: Into<String>>(destination: D) -> Self {
+        Self {
+            destination: destination.into(),
+            options: vec![],
+        }
+    }
+
+    /// add an ssh option, e.g. `-i` then the identity file
+    pub fn option<S: Into<String>>(mut self, option: S) -> Self {
+        self.options.push(option.into());
+        self
+    }
+}
+
+impl Transport for SshTransport {
+    fn wrap(&self, command: &Command) -> Command {
+        // ssh joins the remote command into a shell command line
+        let remote: Vec<String> = command_words(command)
+            .iter()
+            .map(|word| shell_quote(word))
+            .collect();
+
+        let mut ssh = Command::new("ssh");
+        ssh.args(&self.options)
+            .arg(&self.destination)
+            .arg("--")
+            .arg(remote.join(" "));
+        ssh
+    }
+}
+
+/// Runs commands inside a running pod with `kubectl exec`, e.g. a pod of a
+/// helm image
+#[derive(Debug, Clone)]
+pub struct KubectlExecTransport {
+    pub pod: String,
+    pub namespace: Option<String>,
+    pub container: Option<String>,
+    pub context: Option<String>,
+}
+
+impl KubectlExecTransport {
+    pub fn new<P: Into<String>>(pod: P) -> Self {
+        Self {
+            pod: pod.into(),
+            namespace: None,
+            container: None,
+            context: None,
+        }
+    }
+
+    /// set namespace of the pod
+    pub fn namespace<S: Into<String>>(mut self, ns: S) -> Self {
+        self.namespace = Some(ns.into());
+        self
+    }
+
+    /// set container of the pod
+    pub fn container<S: Into<String>>(mut self, container: S) -> Self {
+        self.container = Some(container.into());
+        self
+    }
+
+    /// set kubeconfig context used to reach the pod
+    pub fn context<S: Into<String>>(mut self, context: S) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+impl Transport for KubectlExecTransport {
+    fn wrap(&self, command: &Command) -> Command {
+        let mut kubectl = Command::new("kubectl");
+        kubectl.args(["exec", "--stdin"]);
+        if let Some(context) = &self.context {
+            kubectl.args(["--context", context]);
+        }
+        if let Some(namespace) = &self.namespace {
+            kubectl.args(["--namespace", namespace]);
+        }
+        if let Some(container) = &self.container {
+            kubectl.args(["--container", container]);
+        }
+        kubectl
+            .arg(&self.pod)
+            .arg("--")
+            .args(command_words(command));
+        kubectl
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn argv(command: &Command) -> Vec<String> {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[test]
+    fn test_wrap() {
+        let mut command = Command::new("helm");
+        command
+            .args(["install", "fluvio", "fluvio/fluvio-app"])
+            .args(["--set", "image.tag=it's"])
+            .env("HELM_NAMESPACE", "fluvio");
+
+        let ssh = SshTransport::new("ops@bastion").option("-i").option("key");
+        assert_eq!(
+            argv(&ssh.wrap(&command)),
+            vec![
+                "ssh",
+                "-i",
+                "key",
+                "ops@bastion",
+                "--",
+                r"env HELM_NAMESPACE=fluvio helm install fluvio fluvio/fluvio-app --set 'image.tag=it'\''s'"
+            ]
+        );
+
+        let exec = KubectlExecTransport::new("helm-0")
+            .namespace("tools")
+            .container("helm");
+        assert_eq!(
+            argv(&exec.wrap(&command)),
+            vec![
+                "kubectl",
+                "exec",
+                "--stdin",
+                "--namespace",
+                "tools",
+                "--container",
+                "helm",
+                "helm-0",
+                "--",
+                "env",
+                "HELM_NAMESPACE=fluvio",
+                "helm",
+                "install",
+                "fluvio",
+                "fluvio/fluvio-app",
+                "--set",
+                "image.tag=it's"
+            ]
+        );
+    }
+}
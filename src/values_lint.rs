@@ -0,0 +1,197 @@
+use serde_yaml::{Mapping, Value};
+
+/// A key of the user supplied values which the chart doesn't define
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownValueKey {
+    /// Dotted path of the key, e.g. `resouces.limits`
+    pub path: String,
+    /// A known key with a similar name at the same level, e.g. `resources`
+    pub suggestion: Option<String>,
+}
+
+/// Finds the keys of `values` missing from the chart defaults and schema.
+///
+/// Maps which are empty or null in the defaults accept any key, unless the
+/// schema forbids additional properties. Top level keys of `subcharts` and
+/// `global` are not checked.
+pub(crate) fn unknown_keys(
+    values: &Value,
+    defaults: &Value,
+    schema: Option<&serde_json::Value>,
+    subcharts: &[String],
+) -> Vec<UnknownValueKey> {
+    let mut unknown = vec![];
+    if let Value::Mapping(values) = values {
+        let skip: Vec<&str> = subcharts
+            .iter()
+            .map(String::as_str)
+            .chain(["global"])
+            .collect();
+        walk(values, defaults, schema, "", &skip, &mut unknown);
+    }
+    unknown
+}
+
+/// Values keys of the dependencies declared in a Chart.yaml, their alias
+/// or name
+pub(crate) fn subchart_keys(chart_yaml: &Value) -> Vec<String> {
+    chart_yaml["dependencies"]
+        .as_sequence()
+        .into_iter()
+        .flatten()
+        .filter_map(|dependency| {
+            dependency["alias"]
+                .as_str()
+                .or_else(|| dependency["name"].as_str())
+        })
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn walk(
+    values: &Mapping,
+    defaults: &Value,
+    schema: Option<&serde_json::Value>,
+    prefix: &str,
+    skip: &[&str],
+    unknown: &mut Vec<UnknownValueKey>,
+) {
+    let defaults_map = defaults.as_mapping();
+    let properties = schema.and_then(|schema| schema["properties"].as_object());
+    let additional = schema.map(|schema| &schema["additionalProperties"]);
+    let open = match additional {
+        Some(serde_json::Value::Bool(allowed)) => *allowed,
+        Some(serde_json::Value::Object(_)) => true,
+        _ => defaults_map.is_none_or(Mapping::is_empty) && properties.is_none(),
+    };
+
+    for (key, value) in values {
+        let key = match key.as_str() {
+            Some(key) => key,
+            None => continue,
+        };
+        if skip.contains(&key) {
+            continue;
+        }
+        let path = if prefix.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+
+        let default = defaults_map.and_then(|defaults| defaults.get(key));
+        let property = properties
+            .and_then(|properties| properties.get(key))
+            .or_else(|| additional.filter(|additional| additional.is_object()));
+        if default.is_none() && property.is_none() {
+            if !open {
+                let known = defaults_map
+                    .into_iter()
+                    .flat_map(|defaults| defaults.keys().filter_map(Value::as_str))
+                    .chain(
+                        properties
+                            .into_iter()
+                            .flat_map(|p| p.keys().map(String::as_str)),
+                    );
+                unknown.push(UnknownValueKey {
+                    path,
+                    suggestion: closest(key, known),
+                });
+            }
+            continue;
+        }
+
+        if let Value::Mapping(value) = value {
+            let default = default.cloned().unwrap_or(Value::Null);
+            walk(value, &default, property, &path, &[], unknown);
+        }
+    }
+}
+
+/// The candidate within two edits of `key`, if any
+fn closest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<String> {
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, candidate)| candidate.to_string())
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unknown_keys() {
+        let defaults: Value = serde_yaml::from_str(
+            r#"
+image:
+  registry: infinyon
+  tag: ""
+resources:
+  limits:
+    memory: 1Gi
+podAnnotations: {}
+tls: false
+"#,
+        )
+        .unwrap();
+        let schema: serde_json::Value = serde_json::from_str(
+            r#"{"properties":{"image":{"additionalProperties":false,"properties":{"pullPolicy":{}}},"podAnnotations":{"additionalProperties":{"type":"string"}}}}"#,
+        )
+        .unwrap();
+        let values: Value = serde_yaml::from_str(
+            r#"
+image:
+  tag: 0.9.2
+  pullPolicy: Always
+  registy: example
+resouces:
+  limits:
+    memory: 2Gi
+podAnnotations:
+  team: streaming
+fluvio-sys:
+  anything: true
+global:
+  anything: true
+"#,
+        )
+        .unwrap();
+
+        let unknown = unknown_keys(
+            &values,
+            &defaults,
+            Some(&schema),
+            &["fluvio-sys".to_string()],
+        );
+        assert_eq!(
+            unknown,
+            vec![
+                UnknownValueKey {
+                    path: "image.registy".to_string(),
+                    suggestion: Some("registry".to_string()),
+                },
+                UnknownValueKey {
+                    path: "resouces".to_string(),
+                    suggestion: Some("resources".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+use std::process::Command;
+
+/// Verify Argument, checks a chart archive against its `.prov` file
+#[derive(Debug)]
+pub struct VerifyArg {
+    pub archive: PathBuf,
+    pub keyring: Option<PathBuf>,
+}
+
+impl VerifyArg {
+    /// verify the archive, its provenance file must be next to it
+    pub fn new<P: Into<PathBuf>>(archive: P) -> Self {
+        Self {
+            archive: archive.into(),
+            keyring: None,
+        }
+    }
+
+    /// set keyring holding the public keys, defaults to `~/.gnupg/pubring.gpg`
+    pub fn keyring<P: Into<PathBuf>>(mut self, keyring: P) -> Self {
+        self.keyring = Some(keyring.into());
+        self
+    }
+}
+
+impl From<VerifyArg> for Command {
+    fn from(arg: VerifyArg) -> Self {
+        let mut command = Command::new("helm");
+        command.arg("verify").arg(&arg.archive);
+
+        if let Some(keyring) = &arg.keyring {
+            command.arg("--keyring").arg(keyring);
+        }
+
+        command
+    }
+}
@@ -0,0 +1,122 @@
+use std::cmp::Ordering;
+
+use semver::{Version, VersionReq};
+
+/// Parses a chart version, accepting a leading `v`
+pub(crate) fn parse_version(version: &str) -> Option<Version> {
+    Version::parse(version.trim().trim_start_matches('v')).ok()
+}
+
+/// Orders chart versions by semver, falling back to string order for
+/// versions that don't parse
+pub(crate) fn compare_versions(a: &str, b: &str) -> Ordering {
+    match (parse_version(a), parse_version(b)) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Greater,
+        (None, Some(_)) => Ordering::Less,
+        (None, None) => a.cmp(b),
+    }
+}
+
+/// Whether a version satisfies a requirement, pre-releases being accepted
+/// only when `develop` is set, by the version they precede
+pub(crate) fn matches_requirement(version: &Version, req: &VersionReq, develop: bool) -> bool {
+    if version.pre.is_empty() {
+        req.matches(version)
+    } else {
+        develop && req.matches(&Version::new(version.major, version.minor, version.patch))
+    }
+}
+
+/// The newest of the versions matching `req`
+pub(crate) fn newest_matching(
+    versions: &[String],
+    req: &VersionReq,
+    develop: bool,
+) -> Option<String> {
+    versions
+        .iter()
+        .filter_map(|version| Some((Version::parse(version).ok()?, version)))
+        .filter(|(version, _)| matches_requirement(version, req, develop))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, version)| version.clone())
+}
+
+/// The most significant version component that differs between two versions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VersionDelta {
+    /// Only pre-release or build metadata differ
+    Prerelease,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl VersionDelta {
+    /// Compares two versions, returning `None` if either doesn't parse or
+    /// they are equal
+    pub fn between(current: &str, latest: &str) -> Option<Self> {
+        let current = parse_version(current)?;
+        let latest = parse_version(latest)?;
+        if current.major != latest.major {
+            Some(Self::Major)
+        } else if current.minor != latest.minor {
+            Some(Self::Minor)
+        } else if current.patch != latest.patch {
+            Some(Self::Patch)
+        } else if current != latest {
+            Some(Self::Prerelease)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("0.10.0", "0.9.2"), Ordering::Greater);
+        assert_eq!(compare_versions("v1.0.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0-rc1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("latest", "0.1.0"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_version_delta() {
+        assert_eq!(
+            VersionDelta::between("0.9.2", "0.10.0"),
+            Some(VersionDelta::Minor)
+        );
+        assert_eq!(
+            VersionDelta::between("0.9.2", "1.0.0"),
+            Some(VersionDelta::Major)
+        );
+        assert_eq!(
+            VersionDelta::between("1.0.0-rc1", "1.0.0"),
+            Some(VersionDelta::Prerelease)
+        );
+        assert_eq!(VersionDelta::between("1.0.0", "1.0.0"), None);
+    }
+
+    #[test]
+    fn test_newest_matching() {
+        let versions: Vec<String> = ["0.9.1", "0.9.3", "0.10.0-rc.1", "0.9.2", "1.0.0"]
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let req = VersionReq::parse(">=0.9, <1.0").unwrap();
+        assert_eq!(
+            newest_matching(&versions, &req, false).as_deref(),
+            Some("0.9.3")
+        );
+        assert_eq!(
+            newest_matching(&versions, &req, true).as_deref(),
+            Some("0.10.0-rc.1")
+        );
+        let req = VersionReq::parse(">=2").unwrap();
+        assert_eq!(newest_matching(&versions, &req, false), None);
+    }
+}